@@ -0,0 +1,329 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file backup.rs - 自动本地备份
+ * @author Noah
+ * @description 定时为 snippets_hub.sqlite 创建快照，按日/周轮换，并支持从快照恢复
+ * @created 2026-02-17
+ * @version 1.0.0
+ *
+ * 功能特性:
+ * - 快照通过 VACUUM INTO 生成，应用运行期间也能得到一致的数据库副本
+ * - 每个资料库的快照放在备份目录下以其规范路径哈希命名的子目录中，
+ *   列出、轮换与恢复都只作用于当前资料库
+ * - 文件名格式: snippets_hub-<auto|manual|pre-restore>-YYYYMMDD-HHMMSS.sqlite，
+ *   同一秒内的后续快照追加 -1、-2 等后缀
+ * - 自动快照按日、按周各保留最新一份，超出保留数量的自动删除
+ * - 手动快照不参与轮换；恢复前的安全快照保留最近 PRE_RESTORE_KEEP 份
+ *
+ * 架构说明:
+ * 调度器在数据库初始化完成后由 lib.rs 启动，每隔 CHECK_INTERVAL 检查一次
+ * 最新自动快照是否已超过设定间隔。设置保存在应用数据目录的 JSON 文件中，
+ * 修改后在下一次检查时生效。快照的创建与恢复通过 SNAPSHOT_LOCK 串行执行。
+ */
+use crate::database::Database;
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::formats::archive::sha256_hex;
+use crate::models::{
+    BackupSettings, BackupSnapshot, BackupSnapshotKind, BackupSnapshotRestoreReport,
+};
+use chrono::{Datelike, NaiveDateTime};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const SETTINGS_FILE: &str = "backup_settings.json";
+const FILE_PREFIX: &str = "snippets_hub-";
+const FILE_EXTENSION: &str = ".sqlite";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const PRE_RESTORE_KEEP: usize = 5;
+const CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+static SNAPSHOT_LOCK: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+fn kind_label(kind: BackupSnapshotKind) -> &'static str {
+    match kind {
+        BackupSnapshotKind::Auto => "auto",
+        BackupSnapshotKind::Manual => "manual",
+        BackupSnapshotKind::PreRestore => "pre-restore",
+    }
+}
+
+// ============================================================================
+// 设置
+// ============================================================================
+
+fn app_data_dir(app: &AppHandle) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?)
+}
+
+/// 读取备份设置，文件不存在或无法解析时使用默认值
+pub fn load_settings(app: &AppHandle) -> AppResult<BackupSettings> {
+    let path = app_data_dir(app)?.join(SETTINGS_FILE);
+    match std::fs::read_to_string(&path) {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+        Err(_) => Ok(BackupSettings::default()),
+    }
+}
+
+pub fn save_settings(app: &AppHandle, settings: &BackupSettings) -> AppResult<()> {
+    if settings.interval_hours == 0 {
+        return Err(AppError::validation(
+            "Backup interval must be at least 1 hour",
+        ));
+    }
+    if let Some(directory) = &settings.directory {
+        if !Path::new(directory).is_absolute() {
+            return Err(AppError::validation(format!(
+                "Backup directory must be an absolute path: {}",
+                directory
+            )));
+        }
+    }
+
+    let dir = app_data_dir(app)?;
+    std::fs::create_dir_all(&dir)?;
+    let content =
+        serde_json::to_string_pretty(settings).context("Failed to serialize backup settings")?;
+    std::fs::write(dir.join(SETTINGS_FILE), content).context("Failed to save backup settings")
+}
+
+/// 备份根目录，不存在时创建
+pub fn backup_root(app: &AppHandle, settings: &BackupSettings) -> AppResult<PathBuf> {
+    let dir = match &settings.directory {
+        Some(directory) => PathBuf::from(directory),
+        None => app_data_dir(app)?.join("backups"),
+    };
+    std::fs::create_dir_all(&dir).context("Failed to create backup directory")?;
+    Ok(dir)
+}
+
+/// 资料库在备份根目录下的子目录名：规范路径 SHA-256 的前 16 位
+pub fn library_key(library_path: &Path) -> String {
+    let path = std::fs::canonicalize(library_path).unwrap_or_else(|_| library_path.to_path_buf());
+    sha256_hex(path.to_string_lossy().as_bytes())[..16].to_string()
+}
+
+/// 当前资料库的快照目录，不存在时创建
+pub fn backup_dir(app: &AppHandle, settings: &BackupSettings, db: &Database) -> AppResult<PathBuf> {
+    let library_path = db
+        .path()
+        .ok_or_else(|| AppError::validation("In-memory libraries cannot be backed up"))?;
+    let dir = backup_root(app, settings)?.join(library_key(&library_path));
+    std::fs::create_dir_all(&dir).context("Failed to create backup directory")?;
+    Ok(dir)
+}
+
+// ============================================================================
+// 快照
+// ============================================================================
+
+/// 拆分快照文件名，返回 (类型, 创建时间, 序号)，不是快照的文件名返回 None
+///
+/// 同一秒内创建的快照带有 -N 后缀，序号即 N，没有后缀的为 0
+fn split_file_name(file_name: &str) -> Option<(BackupSnapshotKind, i64, u32)> {
+    let stem = file_name
+        .strip_prefix(FILE_PREFIX)?
+        .strip_suffix(FILE_EXTENSION)?;
+
+    let (kind, rest) = [
+        BackupSnapshotKind::PreRestore,
+        BackupSnapshotKind::Manual,
+        BackupSnapshotKind::Auto,
+    ]
+    .into_iter()
+    .find_map(|kind| {
+        stem.strip_prefix(kind_label(kind))
+            .and_then(|rest| rest.strip_prefix('-'))
+            .map(|rest| (kind, rest))
+    })?;
+    let created_at = NaiveDateTime::parse_from_str(rest.get(..15)?, TIMESTAMP_FORMAT)
+        .ok()?
+        .and_utc()
+        .timestamp_millis();
+    let sequence = match &rest[15..] {
+        "" => 0,
+        suffix => suffix.strip_prefix('-')?.parse().ok()?,
+    };
+    Some((kind, created_at, sequence))
+}
+
+/// 从文件名解析快照信息，不是快照的文件返回 None
+fn parse_snapshot(path: &Path) -> Option<BackupSnapshot> {
+    let file_name = path.file_name()?.to_str()?.to_string();
+    let (kind, created_at, _) = split_file_name(&file_name)?;
+    let size = std::fs::metadata(path).ok()?.len();
+
+    Some(BackupSnapshot {
+        file_name,
+        path: path.to_string_lossy().to_string(),
+        kind,
+        created_at,
+        size,
+    })
+}
+
+/// 列出备份目录中的快照，按创建时间从新到旧排列，同一秒内的按序号从大到小
+pub fn list_snapshots(dir: &Path) -> AppResult<Vec<BackupSnapshot>> {
+    let entries = std::fs::read_dir(dir).context("Failed to read backup directory")?;
+    let mut snapshots: Vec<BackupSnapshot> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| parse_snapshot(&entry.path()))
+        .collect();
+    snapshots.sort_by_cached_key(|s| {
+        let sequence = split_file_name(&s.file_name).map_or(0, |(_, _, sequence)| sequence);
+        std::cmp::Reverse((s.created_at, sequence, s.file_name.clone()))
+    });
+    Ok(snapshots)
+}
+
+async fn write_snapshot(
+    db: &Database,
+    dir: &Path,
+    kind: BackupSnapshotKind,
+) -> AppResult<BackupSnapshot> {
+    let timestamp = chrono::Utc::now().format(TIMESTAMP_FORMAT).to_string();
+    let base = format!("{}{}-{}", FILE_PREFIX, kind_label(kind), timestamp);
+    let mut path = dir.join(format!("{}{}", base, FILE_EXTENSION));
+    let mut counter = 1;
+    while path.exists() {
+        path = dir.join(format!("{}-{}{}", base, counter, FILE_EXTENSION));
+        counter += 1;
+    }
+
+    db.snapshot_into(&path).await?;
+    parse_snapshot(&path).ok_or_else(|| AppError::internal("Failed to read snapshot after writing"))
+}
+
+/// 创建一个快照
+pub async fn create_snapshot(
+    db: &Database,
+    dir: &Path,
+    kind: BackupSnapshotKind,
+) -> AppResult<BackupSnapshot> {
+    let _guard = SNAPSHOT_LOCK.lock().await;
+    write_snapshot(db, dir, kind).await
+}
+
+/// 按轮换规则删除过期的快照，返回被删除的文件名
+///
+/// 自动快照：最近 keep_daily 天每天最新一份、最近 keep_weekly 周每周最新一份，
+/// 且始终保留最新的一份；安全快照：最近 PRE_RESTORE_KEEP 份
+pub fn rotate(dir: &Path, settings: &BackupSettings) -> AppResult<Vec<String>> {
+    let snapshots = list_snapshots(dir)?;
+    let mut keep = HashSet::new();
+
+    let auto: Vec<&BackupSnapshot> = snapshots
+        .iter()
+        .filter(|s| s.kind == BackupSnapshotKind::Auto)
+        .collect();
+    if let Some(latest) = auto.first() {
+        keep.insert(latest.file_name.clone());
+    }
+
+    let mut days = HashSet::new();
+    let mut weeks = HashSet::new();
+    for snapshot in &auto {
+        let Some(time) = chrono::DateTime::from_timestamp_millis(snapshot.created_at) else {
+            continue;
+        };
+        let week = time.iso_week();
+        if days.len() < settings.keep_daily && days.insert(time.date_naive()) {
+            keep.insert(snapshot.file_name.clone());
+        }
+        if weeks.len() < settings.keep_weekly && weeks.insert((week.year(), week.week())) {
+            keep.insert(snapshot.file_name.clone());
+        }
+    }
+
+    snapshots
+        .iter()
+        .filter(|s| s.kind == BackupSnapshotKind::PreRestore)
+        .take(PRE_RESTORE_KEEP)
+        .for_each(|s| {
+            keep.insert(s.file_name.clone());
+        });
+
+    let mut removed = Vec::new();
+    for snapshot in snapshots {
+        if snapshot.kind == BackupSnapshotKind::Manual || keep.contains(&snapshot.file_name) {
+            continue;
+        }
+        std::fs::remove_file(&snapshot.path)
+            .with_context(|| format!("Failed to remove {}", snapshot.file_name))?;
+        removed.push(snapshot.file_name);
+    }
+
+    Ok(removed)
+}
+
+/// 用快照替换当前数据，替换前先创建一份安全快照
+pub async fn restore_snapshot(
+    db: &Database,
+    dir: &Path,
+    file_name: &str,
+) -> AppResult<BackupSnapshotRestoreReport> {
+    let _guard = SNAPSHOT_LOCK.lock().await;
+
+    let snapshot = list_snapshots(dir)?
+        .into_iter()
+        .find(|s| s.file_name == file_name)
+        .ok_or_else(|| AppError::not_found("backup_snapshot", file_name))?;
+    let tables = Database::read_snapshot_tables(Path::new(&snapshot.path)).await?;
+
+    let safety_snapshot = write_snapshot(db, dir, BackupSnapshotKind::PreRestore).await?;
+    let restored = db.restore_tables(&tables).await?;
+
+    Ok(BackupSnapshotRestoreReport {
+        snapshot,
+        safety_snapshot,
+        restored,
+    })
+}
+
+// ============================================================================
+// 调度
+// ============================================================================
+
+/// 自动快照已过期时创建新快照并执行轮换
+async fn run_due_backup(app: &AppHandle) -> AppResult<Option<BackupSnapshot>> {
+    let settings = load_settings(app)?;
+    if !settings.enabled {
+        return Ok(None);
+    }
+
+    let db = app.state::<Database>();
+    let dir = backup_dir(app, &settings, &db)?;
+    let interval_millis = i64::from(settings.interval_hours) * 60 * 60 * 1000;
+    let now = chrono::Utc::now().timestamp_millis();
+    let due = list_snapshots(&dir)?
+        .iter()
+        .find(|s| s.kind == BackupSnapshotKind::Auto)
+        .is_none_or(|latest| now - latest.created_at >= interval_millis);
+    if !due {
+        return Ok(None);
+    }
+
+    let snapshot = create_snapshot(&db, &dir, BackupSnapshotKind::Auto).await?;
+    rotate(&dir, &settings)?;
+    Ok(Some(snapshot))
+}
+
+/// 启动后台备份调度器，需在 Database 注册为托管状态之后调用
+pub fn start_scheduler(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            match run_due_backup(&app).await {
+                Ok(Some(snapshot)) => {
+                    let _ = app.emit("backup-created", snapshot);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    let _ = app.emit("backup-error", e);
+                }
+            }
+            tokio::time::sleep(CHECK_INTERVAL).await;
+        }
+    });
+}
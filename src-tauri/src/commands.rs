@@ -11,20 +11,28 @@
  * 功能特性:
  * - 代码片段 CRUD 操作
  * - 文件夹管理
+ * - 导入导出与全库备份
  * - 数据库交互接口
  * - LSP 服务集成与管理
  * - 项目与工作区管理
  * - Git 版本控制集成
  * - 文件系统操作
  * - 代码执行引擎接口
+ * - 代码片段静态检查
  * - TODO 任务管理
  *
  * 架构说明:
  * 本模块作为前端与后端逻辑的桥梁，接收前端 invoke 调用，
  * 通过 State 获取数据库连接或其他共享状态，执行具体业务逻辑后返回结果。
  */
-use crate::database::Database;
+use crate::database::{build_todo_tree, Database};
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::formats::EntryError;
+use crate::library::MountedLibraries;
 use crate::models::*;
+use crate::repository::{
+    FolderRepository, GitRepoRepository, ProjectRepository, SnippetRepository, TodoRepository,
+};
 use std::collections::HashMap;
 use std::process::{Command, Stdio};
 use std::sync::{Arc, Mutex};
@@ -38,42 +46,60 @@ type LSPServers = Arc<Mutex<HashMap<String, tokio::process::Child>>>;
 pub async fn create_snippet(
     db: State<'_, Database>,
     req: CreateSnippetRequest,
-) -> Result<CodeSnippet, String> {
+) -> AppResult<CodeSnippet> {
     db.create_snippet(req).await
 }
 
+/// 当前资料库与挂载资料库的全部代码片段
 #[tauri::command]
-pub async fn get_all_snippets(db: State<'_, Database>) -> Result<Vec<CodeSnippet>, String> {
-    db.get_all_snippets().await
+pub async fn get_all_snippets(
+    db: State<'_, Database>,
+    mounts: State<'_, MountedLibraries>,
+) -> AppResult<Vec<CodeSnippet>> {
+    let snippets = db.get_all_snippets().await?;
+    Ok(mounts.merge(snippets, None).await)
 }
 
 #[tauri::command]
 pub async fn get_snippet(
     db: State<'_, Database>,
+    mounts: State<'_, MountedLibraries>,
     id: String,
-) -> Result<Option<CodeSnippet>, String> {
-    db.get_snippet(&id).await
+) -> AppResult<Option<CodeSnippet>> {
+    match db.get_snippet(&id).await? {
+        Some(snippet) => Ok(Some(snippet)),
+        None => mounts.find_snippet(&id).await,
+    }
 }
 
 #[tauri::command]
 pub async fn update_snippet(
     db: State<'_, Database>,
+    mounts: State<'_, MountedLibraries>,
     req: UpdateSnippetRequest,
-) -> Result<CodeSnippet, String> {
+) -> AppResult<CodeSnippet> {
+    mounts.ensure_writable(&db, &req.id).await?;
     db.update_snippet(req).await
 }
 
 #[tauri::command]
-pub async fn delete_snippet(db: State<'_, Database>, id: String) -> Result<(), String> {
+pub async fn delete_snippet(
+    db: State<'_, Database>,
+    mounts: State<'_, MountedLibraries>,
+    id: String,
+) -> AppResult<()> {
+    mounts.ensure_writable(&db, &id).await?;
     db.delete_snippet(&id).await
 }
 
 #[tauri::command]
 pub async fn search_snippets(
     db: State<'_, Database>,
+    mounts: State<'_, MountedLibraries>,
     query: SearchQuery,
-) -> Result<Vec<CodeSnippet>, String> {
-    db.search_snippets(query).await
+) -> AppResult<Vec<CodeSnippet>> {
+    let snippets = db.search_snippets(query.clone()).await?;
+    Ok(mounts.merge(snippets, Some(&query)).await)
 }
 
 #[tauri::command]
@@ -81,28 +107,596 @@ pub async fn create_folder(
     db: State<'_, Database>,
     name: String,
     parent_id: Option<String>,
-) -> Result<Folder, String> {
+) -> AppResult<Folder> {
     db.create_folder(name, parent_id).await
 }
 
 #[tauri::command]
-pub async fn get_all_folders(db: State<'_, Database>) -> Result<Vec<Folder>, String> {
+pub async fn get_all_folders(db: State<'_, Database>) -> AppResult<Vec<Folder>> {
     db.get_all_folders().await
 }
 
 #[tauri::command]
-pub async fn delete_folder(db: State<'_, Database>, id: String) -> Result<(), String> {
+pub async fn delete_folder(db: State<'_, Database>, id: String) -> AppResult<()> {
     db.delete_folder(&id).await
 }
 
 #[tauri::command]
-pub async fn export_to_json(db: State<'_, Database>) -> Result<String, String> {
+pub async fn export_to_json(db: State<'_, Database>) -> AppResult<String> {
     let snippets = db.get_all_snippets().await?;
-    serde_json::to_string_pretty(&snippets).map_err(|e| e.to_string())
+    Ok(serde_json::to_string_pretty(&snippets)?)
+}
+
+#[tauri::command]
+pub async fn import_from_json(
+    db: State<'_, Database>,
+    content: String,
+    strategy: ImportConflictStrategy,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let items = crate::formats::json::parse_snippets(&content).map_err(AppError::InvalidData)?;
+
+    let mut results = Vec::new();
+    let mut snippets = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        match item {
+            Ok(snippet) => snippets.push((index, snippet)),
+            Err(invalid) => results.push(ImportItemResult {
+                index,
+                id: invalid.id,
+                title: invalid.title,
+                action: ImportAction::Error,
+                message: Some(invalid.message),
+            }),
+        }
+    }
+    results.extend(db.import_snippets(snippets, strategy, dry_run).await?);
+    results.sort_by_key(|result| result.index);
+
+    let mut report = ImportReport::new(dry_run);
+    for result in results {
+        report.push(result);
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn export_to_html(
+    db: State<'_, Database>,
+    scope: ExportScope,
+    options: HtmlExportOptions,
+) -> AppResult<String> {
+    let snippets = db.get_snippets_in_scope(scope).await?;
+    crate::formats::html::render(&snippets, &options).map_err(AppError::internal)
+}
+
+#[tauri::command]
+pub fn get_html_export_themes() -> Vec<String> {
+    crate::formats::html::available_themes()
+}
+
+// ============================================================================
+// Backup Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn create_backup_archive(
+    db: State<'_, Database>,
+    path: String,
+) -> AppResult<BackupManifest> {
+    let tables = db.dump_tables().await?;
+    let (bytes, manifest) =
+        crate::formats::archive::write(&tables, crate::database::SCHEMA_VERSION)
+            .map_err(AppError::internal)?;
+
+    tokio::fs::write(&path, bytes)
+        .await
+        .context("Failed to write backup archive")?;
+
+    Ok(manifest)
+}
+
+#[tauri::command]
+pub async fn inspect_backup_archive(path: String) -> AppResult<BackupManifest> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .context("Failed to read backup archive")?;
+    let archive = crate::formats::archive::read(&bytes, crate::database::SCHEMA_VERSION)
+        .map_err(AppError::InvalidData)?;
+    Ok(archive.manifest)
+}
+
+#[tauri::command]
+pub async fn restore_backup_archive(
+    db: State<'_, Database>,
+    path: String,
+) -> AppResult<BackupRestoreReport> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .context("Failed to read backup archive")?;
+    let archive = crate::formats::archive::read(&bytes, crate::database::SCHEMA_VERSION)
+        .map_err(AppError::InvalidData)?;
+
+    let ignored_tables = archive
+        .tables
+        .keys()
+        .filter(|name| !crate::database::BACKUP_TABLES.contains(&name.as_str()))
+        .cloned()
+        .collect();
+    let restored = db.restore_tables(&archive.tables).await?;
+
+    Ok(BackupRestoreReport {
+        manifest: archive.manifest,
+        upgraded_from: archive.upgraded_from,
+        restored,
+        ignored_tables,
+    })
+}
+
+#[tauri::command]
+pub async fn get_backup_settings(app: tauri::AppHandle) -> AppResult<BackupSettings> {
+    crate::backup::load_settings(&app)
+}
+
+#[tauri::command]
+pub async fn update_backup_settings(
+    app: tauri::AppHandle,
+    settings: BackupSettings,
+) -> AppResult<BackupSettings> {
+    crate::backup::save_settings(&app, &settings)?;
+    crate::backup::backup_root(&app, &settings)?;
+    Ok(settings)
+}
+
+/// 立即创建一个手动快照
+#[tauri::command]
+pub async fn create_backup_snapshot(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+) -> AppResult<BackupSnapshot> {
+    let settings = crate::backup::load_settings(&app)?;
+    let dir = crate::backup::backup_dir(&app, &settings, &db)?;
+    crate::backup::create_snapshot(&db, &dir, BackupSnapshotKind::Manual).await
+}
+
+#[tauri::command]
+pub async fn list_backup_snapshots(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+) -> AppResult<Vec<BackupSnapshot>> {
+    let settings = crate::backup::load_settings(&app)?;
+    let dir = crate::backup::backup_dir(&app, &settings, &db)?;
+    crate::backup::list_snapshots(&dir)
+}
+
+/// 从备份目录中的快照恢复，恢复前自动创建安全快照
+#[tauri::command]
+pub async fn restore_backup_snapshot(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+    file_name: String,
+) -> AppResult<BackupSnapshotRestoreReport> {
+    let settings = crate::backup::load_settings(&app)?;
+    let dir = crate::backup::backup_dir(&app, &settings, &db)?;
+    let report = crate::backup::restore_snapshot(&db, &dir, &file_name).await?;
+    crate::backup::rotate(&dir, &settings)?;
+    Ok(report)
+}
+
+// ============================================================================
+// Library Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_libraries(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+) -> AppResult<Vec<LibraryInfo>> {
+    crate::library::list(&app, &db)
+}
+
+#[tauri::command]
+pub async fn get_current_library(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+) -> AppResult<LibraryInfo> {
+    crate::library::current(&app, &db)
+}
+
+/// 在 path 处新建资料库并切换过去
+#[tauri::command]
+pub async fn create_library(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+    path: String,
+) -> AppResult<LibraryInfo> {
+    crate::library::switch(&app, &db, &path, true).await
+}
+
+/// 打开已有的资料库文件并切换过去
+#[tauri::command]
+pub async fn open_library(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+    path: String,
+) -> AppResult<LibraryInfo> {
+    crate::library::switch(&app, &db, &path, false).await
+}
+
+#[tauri::command]
+pub async fn remove_recent_library(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+    path: String,
+) -> AppResult<Vec<LibraryInfo>> {
+    crate::library::forget(&app, &db, &path)
+}
+
+#[tauri::command]
+pub async fn list_mounted_libraries(
+    mounts: State<'_, MountedLibraries>,
+) -> AppResult<Vec<MountedLibraryInfo>> {
+    Ok(mounts.list().await)
+}
+
+/// 以只读方式挂载资料库文件
+#[tauri::command]
+pub async fn mount_library(
+    app: tauri::AppHandle,
+    db: State<'_, Database>,
+    mounts: State<'_, MountedLibraries>,
+    path: String,
+    name: Option<String>,
+) -> AppResult<MountedLibraryInfo> {
+    mounts.mount(&app, &db, &path, name).await
+}
+
+#[tauri::command]
+pub async fn unmount_library(
+    app: tauri::AppHandle,
+    mounts: State<'_, MountedLibraries>,
+    library_id: String,
+) -> AppResult<()> {
+    mounts.unmount(&app, &library_id).await
+}
+
+/// 把挂载资料库中的代码片段复制到当前资料库，副本使用新的 ID
+#[tauri::command]
+pub async fn copy_snippet_to_library(
+    db: State<'_, Database>,
+    mounts: State<'_, MountedLibraries>,
+    library_id: String,
+    snippet_id: String,
+    folder_id: Option<String>,
+) -> AppResult<CodeSnippet> {
+    let snippet = mounts.get_snippet(&library_id, &snippet_id).await?;
+    db.create_snippet(CreateSnippetRequest {
+        title: snippet.title,
+        description: snippet.description,
+        code: snippet.code,
+        language: snippet.language,
+        tags: snippet.tags,
+        folder_id,
+        project_id: None,
+    })
+    .await
+}
+
+// ============================================================================
+// Undo Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_undo_state(db: State<'_, Database>) -> AppResult<UndoState> {
+    db.undo_state().await
+}
+
+/// 撤销最近的操作，返回被撤销的操作；没有可撤销的操作时返回 None
+#[tauri::command]
+pub async fn undo(db: State<'_, Database>) -> AppResult<Option<UndoEntry>> {
+    db.undo().await
+}
+
+/// 重做最近撤销的操作，返回被重做的操作；没有可重做的操作时返回 None
+#[tauri::command]
+pub async fn redo(db: State<'_, Database>) -> AppResult<Option<UndoEntry>> {
+    db.redo().await
+}
+
+// ============================================================================
+// Activity Commands
+// ============================================================================
+
+/// 查询活动记录（最近活动列表、排查同步问题）
+#[tauri::command]
+pub async fn get_activity_log(
+    db: State<'_, Database>,
+    query: ActivityQuery,
+) -> AppResult<Vec<ActivityEntry>> {
+    db.get_activity(query).await
+}
+
+/// 删除 before 之前的活动记录，before 为空时全部删除，返回删除的条数
+#[tauri::command]
+pub async fn clear_activity_log(db: State<'_, Database>, before: Option<i64>) -> AppResult<u64> {
+    db.clear_activity(before).await
+}
+
+// ============================================================================
+// Maintenance Commands
+// ============================================================================
+
+/// 检查数据库完整性与孤立记录，repair 为 true 时修复孤立记录
+#[tauri::command]
+pub async fn check_database_integrity(
+    db: State<'_, Database>,
+    repair: bool,
+) -> AppResult<IntegrityReport> {
+    db.check_integrity(repair).await
+}
+
+#[tauri::command]
+pub async fn get_database_stats(db: State<'_, Database>) -> AppResult<DatabaseStats> {
+    db.database_stats().await
+}
+
+/// 回收空闲空间并更新查询统计信息
+#[tauri::command]
+pub async fn vacuum_database(db: State<'_, Database>) -> AppResult<DatabaseStats> {
+    db.vacuum().await?;
+    db.analyze().await?;
+    db.database_stats().await
+}
+
+#[tauri::command]
+pub async fn optimize_search_index(db: State<'_, Database>) -> AppResult<()> {
+    db.optimize_fts().await
 }
 
 #[tauri::command]
-pub fn copy_to_clipboard(_content: String) -> Result<(), String> {
+pub async fn rebuild_search_index(db: State<'_, Database>) -> AppResult<()> {
+    db.rebuild_fts().await
+}
+
+/// 检查全文索引与代码片段是否一致，repair 为 true 时修复偏差
+#[tauri::command]
+pub async fn check_search_index(db: State<'_, Database>, repair: bool) -> AppResult<FtsSyncReport> {
+    db.check_fts_sync(repair).await
+}
+
+// ============================================================================
+// Editor Snippet Format Commands
+// ============================================================================
+
+/// 各编辑器格式共用的导入流程：在一个事务中写入草稿并汇总报告
+async fn import_drafts(
+    db: &Database,
+    items: Vec<Result<SnippetDraft, EntryError>>,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let items = items.into_iter().enumerate().collect();
+    import_indexed_drafts(db, items, folder_id, dry_run).await
+}
+
+/// 同 import_drafts，报告中的序号使用调用方给出的 index
+async fn import_indexed_drafts(
+    db: &Database,
+    items: Vec<(usize, Result<SnippetDraft, EntryError>)>,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let mut results = Vec::new();
+    let mut drafts = Vec::new();
+    for (index, item) in items {
+        match item {
+            Ok(draft) => drafts.push((index, draft)),
+            Err(error) => results.push(ImportItemResult {
+                index,
+                id: None,
+                title: error.title,
+                action: ImportAction::Error,
+                message: Some(error.message),
+            }),
+        }
+    }
+    results.extend(db.import_drafts(drafts, folder_id, dry_run).await?);
+    results.sort_by_key(|result| result.index);
+
+    let mut report = ImportReport::new(dry_run);
+    for result in results {
+        report.push(result);
+    }
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn import_vscode_snippets(
+    db: State<'_, Database>,
+    content: String,
+    default_language: Option<String>,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let items = crate::formats::vscode::parse(&content, default_language.as_deref())
+        .map_err(AppError::InvalidData)?;
+    import_drafts(&db, items, folder_id, dry_run).await
+}
+
+#[tauri::command]
+pub async fn export_vscode_snippets(
+    db: State<'_, Database>,
+    scope: ExportScope,
+) -> AppResult<String> {
+    let snippets = db.get_snippets_in_scope(scope).await?;
+    crate::formats::vscode::render(&snippets).map_err(AppError::internal)
+}
+
+#[tauri::command]
+pub async fn import_jetbrains_templates(
+    db: State<'_, Database>,
+    content: String,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let items = crate::formats::jetbrains::parse(&content).map_err(AppError::InvalidData)?;
+    import_drafts(&db, items, folder_id, dry_run).await
+}
+
+#[tauri::command]
+pub async fn export_jetbrains_templates(
+    db: State<'_, Database>,
+    scope: ExportScope,
+    group: Option<String>,
+) -> AppResult<String> {
+    let snippets = db.get_snippets_in_scope(scope).await?;
+    let group = group
+        .filter(|g| !g.trim().is_empty())
+        .unwrap_or_else(|| "SnippetsHub".to_string());
+    Ok(crate::formats::jetbrains::render(&snippets, &group))
+}
+
+#[tauri::command]
+pub async fn import_editor_snippets(
+    db: State<'_, Database>,
+    format: EditorSnippetFormat,
+    path: String,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let items = tokio::task::spawn_blocking(move || {
+        crate::formats::editors::collect(format, std::path::Path::new(&path))
+    })
+    .await
+    .context("Import task failed")?
+    .map_err(AppError::InvalidData)?;
+    import_drafts(&db, items, folder_id, dry_run).await
+}
+
+/// 导入 massCode 的 db.json，回收站中的代码片段记为跳过
+#[tauri::command]
+pub async fn import_masscode_db(
+    db: State<'_, Database>,
+    content: String,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let parsed = crate::formats::masscode::parse(&content).map_err(AppError::InvalidData)?;
+    let offset = parsed.items.len();
+    let mut report = import_drafts(&db, parsed.items, folder_id, dry_run).await?;
+
+    for (index, title) in parsed.trashed.into_iter().enumerate() {
+        report.push(ImportItemResult {
+            index: offset + index,
+            id: None,
+            title: Some(title),
+            action: ImportAction::Skip,
+            message: Some("Snippet is in the massCode trash".to_string()),
+        });
+    }
+
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn import_gist_export(
+    db: State<'_, Database>,
+    content: String,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let items = crate::formats::gist::parse(&content).map_err(AppError::InvalidData)?;
+    import_drafts(&db, items, folder_id, dry_run).await
+}
+
+/// 预览 Markdown 文件或目录中可提取的代码块
+#[tauri::command]
+pub async fn preview_markdown_code_blocks(path: String) -> AppResult<MarkdownHarvestPreview> {
+    let (blocks, errors) = tokio::task::spawn_blocking(move || {
+        crate::formats::markdown::collect(std::path::Path::new(&path))
+    })
+    .await
+    .context("Preview task failed")?
+    .map_err(AppError::InvalidData)?;
+
+    Ok(MarkdownHarvestPreview {
+        blocks,
+        errors: errors
+            .into_iter()
+            .map(|e| format!("{}: {}", e.title.unwrap_or_default(), e.message))
+            .collect(),
+    })
+}
+
+/// 导入 Markdown 代码块，selection 为预览中选中的 index，缺省时导入全部
+#[tauri::command]
+pub async fn import_markdown_code_blocks(
+    db: State<'_, Database>,
+    path: String,
+    selection: Option<Vec<usize>>,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let (blocks, errors) = tokio::task::spawn_blocking(move || {
+        crate::formats::markdown::collect(std::path::Path::new(&path))
+    })
+    .await
+    .context("Import task failed")?
+    .map_err(AppError::InvalidData)?;
+
+    let items = crate::formats::markdown::select(blocks, errors, selection.as_deref());
+    import_indexed_drafts(&db, items, folder_id, dry_run).await
+}
+
+/// 导出为 Markdown 目录树，目录结构与文件夹层级一致
+#[tauri::command]
+pub async fn export_markdown_tree(
+    db: State<'_, Database>,
+    scope: ExportScope,
+    directory: String,
+) -> AppResult<MarkdownExportReport> {
+    let snippets = db.get_snippets_in_scope(scope).await?;
+    let folders = db.get_all_folders().await?;
+    let files = crate::formats::markdown::render_tree(&snippets, &folders);
+
+    tokio::task::spawn_blocking(move || {
+        crate::formats::markdown::write_tree(std::path::Path::new(&directory), &files)
+    })
+    .await
+    .context("Export task failed")?
+    .map_err(|details| AppError::Io { details })
+}
+
+/// 导出为 yasnippet 目录，返回写入的文件路径
+#[tauri::command]
+pub async fn export_yasnippet(
+    db: State<'_, Database>,
+    scope: ExportScope,
+    directory: String,
+) -> AppResult<Vec<String>> {
+    let snippets = db.get_snippets_in_scope(scope).await?;
+    let folders = db.get_all_folders().await?;
+    let root = std::path::Path::new(&directory);
+
+    let mut written = Vec::new();
+    for (relative, content) in crate::formats::yasnippet::render(&snippets, &folders) {
+        let file = root.join(relative);
+        if let Some(parent) = file.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .context("Failed to create directory")?;
+        }
+        tokio::fs::write(&file, content)
+            .await
+            .with_context(|| format!("Failed to write {}", file.display()))?;
+        written.push(file.to_string_lossy().into_owned());
+    }
+
+    Ok(written)
+}
+
+#[tauri::command]
+pub fn copy_to_clipboard(_content: String) -> AppResult<()> {
     // Tauri 会自动处理剪贴板
     Ok(())
 }
@@ -112,7 +706,7 @@ pub fn copy_to_clipboard(_content: String) -> Result<(), String> {
 // ============================================================================
 
 #[tauri::command]
-pub async fn check_command_available(command: String) -> Result<bool, String> {
+pub async fn check_command_available(command: String) -> AppResult<bool> {
     #[cfg(target_os = "windows")]
     {
         use std::os::windows::process::CommandExt;
@@ -140,7 +734,7 @@ pub async fn start_language_server(
     language: String,
     command: String,
     args: Vec<String>,
-) -> Result<String, String> {
+) -> AppResult<String> {
     let server_id = format!("{}_{}", language, chrono::Utc::now().timestamp());
 
     let mut cmd = TokioCommand::new(command);
@@ -149,11 +743,11 @@ pub async fn start_language_server(
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
 
-    let child = cmd
-        .spawn()
-        .map_err(|e| format!("Failed to start LSP server: {}", e))?;
+    let child = cmd.spawn().context("Failed to start LSP server")?;
 
-    let mut servers_map = servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+    let mut servers_map = servers
+        .lock()
+        .map_err(|_| AppError::internal("LSP server registry lock poisoned"))?;
     servers_map.insert(server_id.clone(), child);
 
     Ok(server_id)
@@ -163,17 +757,16 @@ pub async fn start_language_server(
 pub async fn stop_language_server(
     servers: State<'_, LSPServers>,
     server_id: String,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut child = {
-        let mut servers_map = servers.lock().map_err(|e| format!("Lock error: {}", e))?;
+        let mut servers_map = servers
+            .lock()
+            .map_err(|_| AppError::internal("LSP server registry lock poisoned"))?;
         servers_map.remove(&server_id)
     };
 
     if let Some(ref mut child) = child {
-        child
-            .kill()
-            .await
-            .map_err(|e| format!("Failed to kill LSP server: {}", e))?;
+        child.kill().await.context("Failed to kill LSP server")?;
     }
 
     Ok(())
@@ -185,7 +778,7 @@ pub async fn lsp_request(
     _server_id: String,
     method: String,
     _params: String,
-) -> Result<String, String> {
+) -> AppResult<String> {
     // 这里应该实现LSP协议的请求/响应处理
     // 由于复杂性，这里返回一个模拟响应
     let response = match method.as_str() {
@@ -204,7 +797,7 @@ pub async fn lsp_notification(
     _server_id: String,
     _method: String,
     _params: String,
-) -> Result<(), String> {
+) -> AppResult<()> {
     // 实现LSP通知发送
     Ok(())
 }
@@ -217,12 +810,12 @@ pub async fn lsp_notification(
 pub async fn create_workspace(
     db: State<'_, Database>,
     workspace: Workspace,
-) -> Result<Workspace, String> {
+) -> AppResult<Workspace> {
     db.create_workspace(workspace).await
 }
 
 #[tauri::command]
-pub async fn get_workspaces(db: State<'_, Database>) -> Result<Vec<Workspace>, String> {
+pub async fn get_workspaces(db: State<'_, Database>) -> AppResult<Vec<Workspace>> {
     db.get_workspaces().await
 }
 
@@ -231,22 +824,22 @@ pub async fn update_workspace(
     db: State<'_, Database>,
     workspace_id: String,
     updates: WorkspaceUpdate,
-) -> Result<Workspace, String> {
+) -> AppResult<Workspace> {
     db.update_workspace(workspace_id, updates).await
 }
 
 #[tauri::command]
-pub async fn delete_workspace(db: State<'_, Database>, workspace_id: String) -> Result<(), String> {
+pub async fn delete_workspace(db: State<'_, Database>, workspace_id: String) -> AppResult<()> {
     db.delete_workspace(workspace_id).await
 }
 
 #[tauri::command]
-pub async fn create_project(db: State<'_, Database>, project: Project) -> Result<Project, String> {
+pub async fn create_project(db: State<'_, Database>, project: Project) -> AppResult<Project> {
     db.create_project(project).await
 }
 
 #[tauri::command]
-pub async fn get_projects(db: State<'_, Database>) -> Result<Vec<Project>, String> {
+pub async fn get_projects(db: State<'_, Database>) -> AppResult<Vec<Project>> {
     db.get_projects().await
 }
 
@@ -255,12 +848,12 @@ pub async fn update_project(
     db: State<'_, Database>,
     project_id: String,
     updates: ProjectUpdate,
-) -> Result<Project, String> {
+) -> AppResult<Project> {
     db.update_project(project_id, updates).await
 }
 
 #[tauri::command]
-pub async fn delete_project(db: State<'_, Database>, project_id: String) -> Result<(), String> {
+pub async fn delete_project(db: State<'_, Database>, project_id: String) -> AppResult<()> {
     db.delete_project(project_id).await
 }
 
@@ -268,7 +861,7 @@ pub async fn delete_project(db: State<'_, Database>, project_id: String) -> Resu
 pub async fn get_snippets_by_project(
     db: State<'_, Database>,
     project_id: String,
-) -> Result<Vec<CodeSnippet>, String> {
+) -> AppResult<Vec<CodeSnippet>> {
     db.get_snippets_by_project(project_id).await
 }
 
@@ -280,12 +873,12 @@ pub async fn get_snippets_by_project(
 pub async fn create_git_repository(
     db: State<'_, Database>,
     repository: GitRepository,
-) -> Result<GitRepository, String> {
+) -> AppResult<GitRepository> {
     db.create_git_repository(repository).await
 }
 
 #[tauri::command]
-pub async fn get_git_repositories(db: State<'_, Database>) -> Result<Vec<GitRepository>, String> {
+pub async fn get_git_repositories(db: State<'_, Database>) -> AppResult<Vec<GitRepository>> {
     db.get_git_repositories().await
 }
 
@@ -294,7 +887,7 @@ pub async fn update_git_repository(
     db: State<'_, Database>,
     repository_id: String,
     updates: GitRepositoryUpdate,
-) -> Result<GitRepository, String> {
+) -> AppResult<GitRepository> {
     db.update_git_repository(repository_id, updates).await
 }
 
@@ -302,7 +895,7 @@ pub async fn update_git_repository(
 pub async fn delete_git_repository(
     db: State<'_, Database>,
     repository_id: String,
-) -> Result<(), String> {
+) -> AppResult<()> {
     db.delete_git_repository(repository_id).await
 }
 
@@ -311,35 +904,35 @@ pub async fn delete_git_repository(
 // ============================================================================
 
 #[tauri::command]
-pub async fn remove_dir_all(path: String) -> Result<(), String> {
+pub async fn remove_dir_all(path: String) -> AppResult<()> {
     tokio::fs::remove_dir_all(path)
         .await
-        .map_err(|e| format!("Failed to remove directory: {}", e))
+        .context("Failed to remove directory")
 }
 
 #[tauri::command]
-pub async fn create_directory(path: String) -> Result<(), String> {
+pub async fn create_directory(path: String) -> AppResult<()> {
     tokio::fs::create_dir_all(path)
         .await
-        .map_err(|e| format!("Failed to create directory: {}", e))
+        .context("Failed to create directory")
 }
 
 #[tauri::command]
-pub async fn read_file_content(path: String) -> Result<String, String> {
+pub async fn read_file_content(path: String) -> AppResult<String> {
     tokio::fs::read_to_string(path)
         .await
-        .map_err(|e| format!("Failed to read file: {}", e))
+        .context("Failed to read file")
 }
 
 #[tauri::command]
-pub async fn write_file_content(path: String, content: String) -> Result<(), String> {
+pub async fn write_file_content(path: String, content: String) -> AppResult<()> {
     tokio::fs::write(path, content)
         .await
-        .map_err(|e| format!("Failed to write file: {}", e))
+        .context("Failed to write file")
 }
 
 #[tauri::command]
-pub async fn file_exists(path: String) -> Result<bool, String> {
+pub async fn file_exists(path: String) -> AppResult<bool> {
     Ok(tokio::fs::metadata(path).await.is_ok())
 }
 
@@ -352,7 +945,7 @@ pub async fn execute_code(
     language: String,
     code: String,
     options: ExecutionOptions,
-) -> Result<ExecutionResult, String> {
+) -> AppResult<ExecutionResult> {
     let temp_dir = std::env::temp_dir();
     let file_name = format!(
         "snippetshub_exec_{}.{}",
@@ -364,7 +957,7 @@ pub async fn execute_code(
     // 写入代码文件
     tokio::fs::write(&file_path, code)
         .await
-        .map_err(|e| format!("Failed to write code file: {}", e))?;
+        .context("Failed to write code file")?;
 
     // 执行代码
     let result = match language.as_str() {
@@ -372,7 +965,10 @@ pub async fn execute_code(
         "javascript" => execute_javascript(&file_path, &options).await,
         "rust" => execute_rust(&file_path, &options).await,
         "go" => execute_go(&file_path, &options).await,
-        _ => Err(format!("Unsupported language: {}", language)),
+        _ => Err(AppError::validation(format!(
+            "Unsupported language: {}",
+            language
+        ))),
     };
 
     // 清理临时文件
@@ -397,7 +993,7 @@ fn get_file_extension(language: &str) -> &str {
 async fn execute_python(
     file_path: &std::path::Path,
     _options: &ExecutionOptions,
-) -> Result<ExecutionResult, String> {
+) -> AppResult<ExecutionResult> {
     let mut cmd = TokioCommand::new("python3");
     cmd.arg(file_path)
         .stdin(Stdio::piped())
@@ -405,10 +1001,7 @@ async fn execute_python(
         .stderr(Stdio::piped());
 
     let start_time = std::time::Instant::now();
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute Python: {}", e))?;
+    let output = cmd.output().await.context("Failed to execute Python")?;
     let duration = start_time.elapsed();
 
     Ok(ExecutionResult {
@@ -423,7 +1016,7 @@ async fn execute_python(
 async fn execute_javascript(
     file_path: &std::path::Path,
     _options: &ExecutionOptions,
-) -> Result<ExecutionResult, String> {
+) -> AppResult<ExecutionResult> {
     let mut cmd = TokioCommand::new("node");
     cmd.arg(file_path)
         .stdin(Stdio::piped())
@@ -431,10 +1024,7 @@ async fn execute_javascript(
         .stderr(Stdio::piped());
 
     let start_time = std::time::Instant::now();
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute Node.js: {}", e))?;
+    let output = cmd.output().await.context("Failed to execute Node.js")?;
     let duration = start_time.elapsed();
 
     Ok(ExecutionResult {
@@ -449,7 +1039,7 @@ async fn execute_javascript(
 async fn execute_rust(
     file_path: &std::path::Path,
     _options: &ExecutionOptions,
-) -> Result<ExecutionResult, String> {
+) -> AppResult<ExecutionResult> {
     // 编译Rust代码
     let exe_path = file_path.with_extension("exe");
     let mut compile_cmd = TokioCommand::new("rustc");
@@ -463,7 +1053,7 @@ async fn execute_rust(
     let compile_output = compile_cmd
         .output()
         .await
-        .map_err(|e| format!("Failed to compile Rust: {}", e))?;
+        .context("Failed to compile Rust")?;
 
     if !compile_output.status.success() {
         return Ok(ExecutionResult {
@@ -486,7 +1076,7 @@ async fn execute_rust(
     let output = run_cmd
         .output()
         .await
-        .map_err(|e| format!("Failed to execute Rust binary: {}", e))?;
+        .context("Failed to execute Rust binary")?;
     let duration = start_time.elapsed();
 
     // 清理可执行文件
@@ -504,7 +1094,7 @@ async fn execute_rust(
 async fn execute_go(
     file_path: &std::path::Path,
     _options: &ExecutionOptions,
-) -> Result<ExecutionResult, String> {
+) -> AppResult<ExecutionResult> {
     let mut cmd = TokioCommand::new("go");
     cmd.arg("run")
         .arg(file_path)
@@ -513,10 +1103,7 @@ async fn execute_go(
         .stderr(Stdio::piped());
 
     let start_time = std::time::Instant::now();
-    let output = cmd
-        .output()
-        .await
-        .map_err(|e| format!("Failed to execute Go: {}", e))?;
+    let output = cmd.output().await.context("Failed to execute Go")?;
     let duration = start_time.elapsed();
 
     Ok(ExecutionResult {
@@ -528,32 +1115,44 @@ async fn execute_go(
     })
 }
 
+// ============================================================================
+// Lint Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn lint_snippet(language: String, code: String) -> AppResult<LintResult> {
+    crate::lint::lint_snippet(&language, &code).await
+}
+
 // ============================================================================
 // Todo Management Commands
 // ============================================================================
 
 #[tauri::command]
-pub async fn create_todo(db: State<'_, Database>, req: CreateTodoRequest) -> Result<Todo, String> {
+pub async fn create_todo(db: State<'_, Database>, req: CreateTodoRequest) -> AppResult<Todo> {
     db.create_todo(req).await
 }
 
 #[tauri::command]
-pub async fn get_todos(db: State<'_, Database>) -> Result<Vec<Todo>, String> {
-    db.get_todos().await
+pub async fn get_todos(
+    db: State<'_, Database>,
+    layout: Option<TodoLayout>,
+) -> AppResult<Vec<Todo>> {
+    Ok(arrange_todos(db.get_todos().await?, layout))
 }
 
 #[tauri::command]
-pub async fn get_todo(db: State<'_, Database>, todo_id: String) -> Result<Todo, String> {
+pub async fn get_todo(db: State<'_, Database>, todo_id: String) -> AppResult<Todo> {
     db.get_todo(&todo_id).await
 }
 
 #[tauri::command]
-pub async fn update_todo(db: State<'_, Database>, req: UpdateTodoRequest) -> Result<Todo, String> {
+pub async fn update_todo(db: State<'_, Database>, req: UpdateTodoRequest) -> AppResult<Todo> {
     db.update_todo(req).await
 }
 
 #[tauri::command]
-pub async fn delete_todo(db: State<'_, Database>, todo_id: String) -> Result<(), String> {
+pub async fn delete_todo(db: State<'_, Database>, todo_id: String) -> AppResult<()> {
     db.delete_todo(todo_id).await
 }
 
@@ -561,20 +1160,29 @@ pub async fn delete_todo(db: State<'_, Database>, todo_id: String) -> Result<(),
 pub async fn search_todos(
     db: State<'_, Database>,
     query: TodoSearchQuery,
-) -> Result<Vec<Todo>, String> {
-    db.search_todos(query).await
+    layout: Option<TodoLayout>,
+) -> AppResult<Vec<Todo>> {
+    Ok(arrange_todos(db.search_todos(query).await?, layout))
+}
+
+/// 按前端要求的形式返回 TODO 列表，默认平铺
+fn arrange_todos(todos: Vec<Todo>, layout: Option<TodoLayout>) -> Vec<Todo> {
+    match layout.unwrap_or_default() {
+        TodoLayout::Flat => todos,
+        TodoLayout::Tree => build_todo_tree(todos),
+    }
 }
 
 #[tauri::command]
 pub async fn batch_update_todos(
     db: State<'_, Database>,
     operation: BatchTodoOperation,
-) -> Result<Vec<Todo>, String> {
+) -> AppResult<Vec<Todo>> {
     db.batch_update_todos(operation).await
 }
 
 #[tauri::command]
-pub async fn get_todo_stats(db: State<'_, Database>) -> Result<TodoStats, String> {
+pub async fn get_todo_stats(db: State<'_, Database>) -> AppResult<TodoStats> {
     db.get_todo_stats().await
 }
 
@@ -586,12 +1194,12 @@ pub async fn get_todo_stats(db: State<'_, Database>) -> Result<TodoStats, String
 pub async fn create_todo_tag(
     db: State<'_, Database>,
     req: CreateTodoTagRequest,
-) -> Result<TodoTag, String> {
+) -> AppResult<TodoTag> {
     db.create_todo_tag(req).await
 }
 
 #[tauri::command]
-pub async fn get_todo_tags(db: State<'_, Database>) -> Result<Vec<TodoTag>, String> {
+pub async fn get_todo_tags(db: State<'_, Database>) -> AppResult<Vec<TodoTag>> {
     db.get_todo_tags_list().await
 }
 
@@ -599,11 +1207,172 @@ pub async fn get_todo_tags(db: State<'_, Database>) -> Result<Vec<TodoTag>, Stri
 pub async fn update_todo_tag(
     db: State<'_, Database>,
     req: UpdateTodoTagRequest,
-) -> Result<TodoTag, String> {
+) -> AppResult<TodoTag> {
     db.update_todo_tag(req).await
 }
 
 #[tauri::command]
-pub async fn delete_todo_tag(db: State<'_, Database>, tag_id: String) -> Result<(), String> {
+pub async fn delete_todo_tag(db: State<'_, Database>, tag_id: String) -> AppResult<()> {
     db.delete_todo_tag(tag_id).await
 }
+
+// ============================================================================
+// Todo Import & Export Commands
+// ============================================================================
+
+/// 在一个事务中导入 TODO，同一批中的父任务先于子任务写入
+async fn import_todo_drafts(
+    db: &Database,
+    items: Vec<Result<TodoDraft, EntryError>>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let known_ids: std::collections::HashSet<String> = items
+        .iter()
+        .filter_map(|item| item.as_ref().ok().and_then(|draft| draft.id.clone()))
+        .collect();
+
+    let mut results = Vec::new();
+    let mut pending = Vec::new();
+    for (index, item) in items.into_iter().enumerate() {
+        match item {
+            Ok(draft) => pending.push((index, draft)),
+            Err(error) => results.push(ImportItemResult {
+                index,
+                id: None,
+                title: error.title,
+                action: ImportAction::Error,
+                message: Some(error.message),
+            }),
+        }
+    }
+
+    let ordered = crate::formats::parents_first(pending);
+    results.extend(db.import_todos(ordered, &known_ids, dry_run).await?);
+    results.sort_by_key(|result| result.index);
+
+    let mut report = ImportReport::new(dry_run);
+    for result in results {
+        report.push(result);
+    }
+    Ok(report)
+}
+
+/// 导出 TODO 为 iCalendar (.ics) 文本
+#[tauri::command]
+pub async fn export_todos_ical(
+    db: State<'_, Database>,
+    include_archived: bool,
+) -> AppResult<String> {
+    let todos = db.get_todos_for_export(include_archived).await?;
+    let tag_names = db
+        .get_todo_tags_list()
+        .await?
+        .into_iter()
+        .map(|tag| (tag.id, tag.name))
+        .collect();
+    Ok(crate::formats::ical::render(&todos, &tag_names))
+}
+
+/// 从 iCalendar 文本导入 VTODO，按 UID 创建或更新 TODO
+#[tauri::command]
+pub async fn import_todos_ical(
+    db: State<'_, Database>,
+    content: String,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    let items = crate::formats::ical::parse(&content).map_err(AppError::InvalidData)?;
+    import_todo_drafts(&db, items, dry_run).await
+}
+
+/// 按搜索条件导出 TODO 为 Markdown 任务列表或 todo.txt
+#[tauri::command]
+pub async fn export_todos_text(
+    db: State<'_, Database>,
+    format: TodoTextFormat,
+    query: TodoSearchQuery,
+) -> AppResult<String> {
+    let todos = db.search_todos(query).await?;
+    let tag_names = db
+        .get_todo_tags_list()
+        .await?
+        .into_iter()
+        .map(|tag| (tag.id, tag.name))
+        .collect();
+
+    Ok(match format {
+        TodoTextFormat::Markdown => crate::formats::checklist::render(&todos, &tag_names),
+        TodoTextFormat::TodoTxt => {
+            let project_names = db
+                .get_projects()
+                .await?
+                .into_iter()
+                .map(|project| (project.id, project.name))
+                .collect();
+            crate::formats::todotxt::render(&todos, &tag_names, &project_names)
+        }
+    })
+}
+
+/// 从 Markdown 任务列表或 todo.txt 文本导入 TODO
+///
+/// parent_id 指定时，导入的顶层任务作为该任务的子任务
+#[tauri::command]
+pub async fn import_todos_text(
+    db: State<'_, Database>,
+    format: TodoTextFormat,
+    content: String,
+    parent_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<ImportReport> {
+    if let Some(parent_id) = &parent_id {
+        if !db.todo_exists(parent_id).await? {
+            return Err(AppError::not_found("todo", parent_id.clone()));
+        }
+    }
+
+    let mut items = match format {
+        TodoTextFormat::Markdown => crate::formats::checklist::parse(&content),
+        TodoTextFormat::TodoTxt => crate::formats::todotxt::parse(&content),
+    };
+    for draft in items.iter_mut().flatten() {
+        if draft.parent_id.is_none() {
+            draft.parent_id = parent_id.clone();
+        }
+    }
+
+    import_todo_drafts(&db, items, dry_run).await
+}
+
+/// 按搜索条件导出 TODO 及工时数据为 CSV（带 UTF-8 BOM）
+///
+/// columns 为空时使用默认列
+#[tauri::command]
+pub async fn export_todos_csv(
+    db: State<'_, Database>,
+    query: TodoSearchQuery,
+    columns: Option<Vec<TodoCsvColumn>>,
+) -> AppResult<String> {
+    let columns = columns
+        .filter(|columns| !columns.is_empty())
+        .unwrap_or_else(TodoCsvColumn::defaults);
+    let todos = db.search_todos(query).await?;
+    let tag_names = db
+        .get_todo_tags_list()
+        .await?
+        .into_iter()
+        .map(|tag| (tag.id, tag.name))
+        .collect();
+    let project_names = db
+        .get_projects()
+        .await?
+        .into_iter()
+        .map(|project| (project.id, project.name))
+        .collect();
+
+    Ok(crate::formats::csv::render(
+        &todos,
+        &columns,
+        &tag_names,
+        &project_names,
+    ))
+}
@@ -27,385 +27,238 @@
  * 使用示例:
  * ```rust
  * let db = Database::new(&app_handle).await?;
+ * // 或基于已有连接池: Database::from_pool(pool).await?
  * let snippet = db.create_snippet(snippet_data).await?;
  * let results = db.search_snippets("javascript").await?;
  * ```
  */
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::events::{ChangeAction, ChangeEntity, ChangeEvent, CHANNEL_CAPACITY};
+use crate::formats::archive::{TableRow, TableSet};
+use crate::journal::{RowChange, UndoAction, HISTORY_LIMIT};
 use crate::models::*;
+use crate::repository::{
+    FolderRepository, GitRepoRepository, ProjectRepository, SnippetRepository, TodoRepository,
+};
 // database.rs
 //
 // 后端数据库操作模块
 // 提供了与 SQLite 数据库交互的所有核心功能，包括：
-// - 数据库初始化 (Migration)
+// - 数据库初始化 (迁移见 migrations.rs)
 // - Snippets 的增删改查
 // - FTS5 全文搜索实现
 // - 文件夹管理
 
-use sqlx::{Row, SqlitePool};
-use std::collections::HashMap;
+use async_trait::async_trait;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqliteJournalMode, SqliteRow, SqliteSynchronous,
+};
+use sqlx::{Column, Row, SqlitePool, TypeInfo, ValueRef};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, PoisonError, RwLock};
+use std::time::Duration;
 
 use tauri::{AppHandle, Manager};
 use tokio::fs;
+use tokio::sync::broadcast;
 
+/// 数据库句柄
+///
+/// 克隆共享同一个资料库连接；切换资料库（switch_library）后，所有克隆
+/// （包括 Tauri 托管的状态）都会使用新的连接池。
 #[derive(Clone)]
 pub struct Database {
+    library: Arc<RwLock<Library>>,
+    /// 写入成功后发布的变更事件，见 events 模块
+    events: broadcast::Sender<ChangeEvent>,
+}
+
+/// 当前打开的资料库
+struct Library {
     pool: SqlitePool,
+    /// 数据库文件路径，内存数据库为 None
+    path: Option<PathBuf>,
 }
 
 impl Database {
-    pub async fn new(app_handle: &AppHandle) -> Result<Self, String> {
-        let app_dir = app_handle
-            .path()
-            .app_data_dir()
-            .map_err(|e| e.to_string())?;
+    /// 打开应用数据目录下的默认资料库
+    pub async fn new(app_handle: &AppHandle) -> AppResult<Self> {
+        let app_dir = app_handle.path().app_data_dir()?;
 
         if !app_dir.exists() {
-            fs::create_dir_all(&app_dir)
-                .await
-                .map_err(|e| e.to_string())?;
+            fs::create_dir_all(&app_dir).await?;
         }
 
-        let db_path = app_dir.join("snippets_hub.sqlite");
-        let db_url = format!("sqlite:{}", db_path.to_string_lossy());
+        Self::open(&app_dir.join(DEFAULT_LIBRARY_FILE), true).await
+    }
 
-        // Create database file if not exists
-        if !db_path.exists() {
-            std::fs::File::create(&db_path).map_err(|e| e.to_string())?;
-        }
+    /// 打开指定的资料库文件，create 为 true 时文件不存在则创建
+    pub async fn open(path: &Path, create: bool) -> AppResult<Self> {
+        let pool = Self::connect(path, create).await?;
+        Ok(Self::with_library(pool, Some(path.to_path_buf())))
+    }
 
-        let pool = SqlitePool::connect(&db_url)
+    /// 以只读方式打开其他资料库（如团队共享库），不执行迁移
+    ///
+    /// 由更新版本的应用创建的资料库会被拒绝；任何写入都会返回 read_only 错误。
+    /// 以 mode=ro 而不是 immutable 打开：共享盘上的文件仍可能被其他客户端修改，
+    /// 每次读取都要看到最新内容，而不是缓存的旧页
+    pub async fn open_read_only(path: &Path) -> AppResult<Self> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .read_only(true)
+            .busy_timeout(BUSY_TIMEOUT);
+        let pool = SqlitePool::connect_with(options)
             .await
-            .map_err(|e| format!("Failed to connect to database: {}", e))?;
+            .with_context(|| format!("Failed to open library {}", path.display()))?;
 
-        let db = Database { pool };
-        db.init_schema().await?;
-
-        Ok(db)
+        if let Err(e) = Self::check_readable(&pool, path).await {
+            pool.close().await;
+            return Err(e);
+        }
+        Ok(Self::with_library(pool, Some(path.to_path_buf())))
     }
 
-    async fn init_schema(&self) -> Result<(), String> {
-        // Snippets Table (updated with project_id and is_favorite)
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS snippets (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                description TEXT,
-                code TEXT NOT NULL,
-                language TEXT NOT NULL,
-                tags TEXT, -- JSON array
-                folder_id TEXT,
-                project_id TEXT,
-                is_favorite INTEGER NOT NULL DEFAULT 0,
-                usage_count INTEGER NOT NULL DEFAULT 0,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Add is_favorite column if it doesn't exist (migration)
-        sqlx::query("ALTER TABLE snippets ADD COLUMN is_favorite INTEGER NOT NULL DEFAULT 0")
-            .execute(&self.pool)
-            .await
-            .ok(); // Ignore error if column already exists
-
-        // Add usage_count column if it doesn't exist (migration)
-        sqlx::query("ALTER TABLE snippets ADD COLUMN usage_count INTEGER NOT NULL DEFAULT 0")
-            .execute(&self.pool)
-            .await
-            .ok(); // Ignore error if column already exists
+    async fn check_readable(pool: &SqlitePool, path: &Path) -> AppResult<()> {
+        let tables: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+                .fetch_all(pool)
+                .await
+                .with_context(|| format!("Failed to read library {}", path.display()))?;
+        if !tables.iter().any(|name| name == "snippets") {
+            return Err(AppError::InvalidData(format!(
+                "{} is not a SnippetsHub library",
+                path.display()
+            )));
+        }
 
-        // Update existing todos with some progress values for testing
-        sqlx::query("UPDATE todos SET progress = 25 WHERE status = 'in_progress' AND progress = 0")
-            .execute(&self.pool)
-            .await
-            .ok(); // Ignore error if update fails
+        if tables.iter().any(|name| name == "schema_migrations") {
+            let version: Option<i64> =
+                sqlx::query_scalar("SELECT MAX(version) FROM schema_migrations")
+                    .fetch_one(pool)
+                    .await?;
+            if let Some(version) = version.filter(|v| *v > SCHEMA_VERSION) {
+                return Err(AppError::UnsupportedVersion(format!(
+                    "Library schema version {} is newer than this version of SnippetsHub supports ({}); please update the application",
+                    version, SCHEMA_VERSION
+                )));
+            }
+        }
+        Ok(())
+    }
 
-        sqlx::query("UPDATE todos SET progress = 100 WHERE completed = true AND progress = 0")
-            .execute(&self.pool)
-            .await
-            .ok(); // Ignore error if update fails
+    /// 基于已有连接池创建，并把数据库迁移到最新版本
+    ///
+    /// 不依赖 AppHandle，测试中可传入内存数据库的连接池
+    pub async fn from_pool(pool: SqlitePool) -> AppResult<Self> {
+        crate::migrations::run(&pool).await?;
+        Ok(Self::with_library(pool, None))
+    }
 
-        // Add due dates to existing todos for calendar testing
-        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
-        let tomorrow = chrono::Utc::now()
-            .checked_add_signed(chrono::Duration::days(1))
-            .unwrap_or_else(chrono::Utc::now)
-            .format("%Y-%m-%d")
-            .to_string();
-        let next_week = chrono::Utc::now()
-            .checked_add_signed(chrono::Duration::days(7))
-            .unwrap_or_else(chrono::Utc::now)
-            .format("%Y-%m-%d")
-            .to_string();
+    fn with_library(pool: SqlitePool, path: Option<PathBuf>) -> Self {
+        Database {
+            library: Arc::new(RwLock::new(Library { pool, path })),
+            events: broadcast::channel(CHANNEL_CAPACITY).0,
+        }
+    }
 
-        // Set due dates for existing todos without due_date
-        sqlx::query("UPDATE todos SET due_date = ? WHERE due_date IS NULL AND status = 'in_progress' LIMIT 1")
-            .bind(&today)
-            .execute(&self.pool)
-            .await
-            .ok();
+    /// 订阅之后发生的数据变更
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.events.subscribe()
+    }
 
-        sqlx::query("UPDATE todos SET due_date = ? WHERE due_date IS NULL AND status = 'todo' LIMIT 1")
-            .bind(&tomorrow)
-            .execute(&self.pool)
-            .await
-            .ok();
+    /// 发布变更事件，没有订阅者时直接丢弃
+    fn notify(&self, event: ChangeEvent) {
+        let _ = self.events.send(event);
+    }
 
-        sqlx::query("UPDATE todos SET due_date = ? WHERE due_date IS NULL LIMIT 1")
-            .bind(&next_week)
-            .execute(&self.pool)
+    async fn connect(path: &Path, create: bool) -> AppResult<SqlitePool> {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(create)
+            .foreign_keys(true)
+            .journal_mode(SqliteJournalMode::Wal)
+            .synchronous(SqliteSynchronous::Normal)
+            .busy_timeout(BUSY_TIMEOUT);
+        let pool = SqlitePool::connect_with(options)
             .await
-            .ok();
-
-        // Folders Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS folders (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                parent_id TEXT,
-                created_at INTEGER NOT NULL
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Workspaces Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS workspaces (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                description TEXT,
-                color TEXT NOT NULL,
-                is_default INTEGER NOT NULL DEFAULT 0,
-                settings TEXT, -- JSON object
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Projects Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS projects (
-                id TEXT PRIMARY KEY,
-                workspace_id TEXT NOT NULL,
-                name TEXT NOT NULL,
-                description TEXT,
-                project_type TEXT NOT NULL,
-                template TEXT,
-                parent_id TEXT,
-                path TEXT NOT NULL,
-                color TEXT NOT NULL,
-                icon TEXT NOT NULL,
-                tags TEXT, -- JSON array
-                settings TEXT, -- JSON object
-                metadata TEXT, -- JSON object
-                is_folder INTEGER NOT NULL DEFAULT 0,
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Git Repositories Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS git_repositories (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                description TEXT,
-                path TEXT NOT NULL UNIQUE,
-                is_default INTEGER NOT NULL DEFAULT 0,
-                remotes TEXT, -- JSON array
-                created_at TEXT NOT NULL,
-                updated_at TEXT NOT NULL
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // FTS Table
-        sqlx::query(
-            "CREATE VIRTUAL TABLE IF NOT EXISTS snippets_fts USING fts5(
-                id UNINDEXED,
-                title,
-                description,
-                code,
-                tags,
-                tokenize='trigram'
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Triggers to keep FTS updated
-        sqlx::query(
-            "CREATE TRIGGER IF NOT EXISTS snippets_ai AFTER INSERT ON snippets BEGIN
-              INSERT INTO snippets_fts(id, title, description, code, tags) 
-              VALUES (new.id, new.title, new.description, new.code, new.tags);
-            END;",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        sqlx::query(
-            "CREATE TRIGGER IF NOT EXISTS snippets_ad AFTER DELETE ON snippets BEGIN
-              DELETE FROM snippets_fts WHERE id = old.id;
-            END;",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        sqlx::query(
-            "CREATE TRIGGER IF NOT EXISTS snippets_au AFTER UPDATE ON snippets BEGIN
-              UPDATE snippets_fts SET 
-                title = new.title, 
-                description = new.description, 
-                code = new.code, 
-                tags = new.tags
-              WHERE id = old.id;
-            END;",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Todos Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS todos (
-                id TEXT PRIMARY KEY,
-                title TEXT NOT NULL,
-                description TEXT,
-                status TEXT DEFAULT 'todo',
-                priority TEXT,
-                due_date TEXT,
-                estimated_hours REAL,
-                actual_hours REAL,
-                progress INTEGER DEFAULT 0,
-                assignee TEXT,
-                project_id TEXT,
-                parent_id TEXT,
-                recurring_config TEXT, -- JSON
-                dependencies TEXT, -- JSON array
-                completed BOOLEAN DEFAULT FALSE,
-                archived BOOLEAN DEFAULT FALSE,
-                created_by TEXT,
-                updated_by TEXT,
-                created_at INTEGER NOT NULL,
-                updated_at INTEGER NOT NULL,
-                archived_at INTEGER
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Todo Tags Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS todo_tags (
-                id TEXT PRIMARY KEY,
-                name TEXT NOT NULL,
-                color TEXT NOT NULL,
-                bg_color TEXT NOT NULL,
-                color_id TEXT NOT NULL,
-                created_at INTEGER NOT NULL
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Todo Tag Relations Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS todo_tag_relations (
-                todo_id TEXT,
-                tag_id TEXT,
-                PRIMARY KEY (todo_id, tag_id),
-                FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE,
-                FOREIGN KEY (tag_id) REFERENCES todo_tags(id) ON DELETE CASCADE
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Todo Comments Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS todo_comments (
-                id TEXT PRIMARY KEY,
-                todo_id TEXT NOT NULL,
-                content TEXT NOT NULL,
-                author TEXT,
-                created_at INTEGER NOT NULL,
-                FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
-
-        // Todo Attachments Table
-        sqlx::query(
-            "CREATE TABLE IF NOT EXISTS todo_attachments (
-                id TEXT PRIMARY KEY,
-                todo_id TEXT NOT NULL,
-                filename TEXT NOT NULL,
-                filepath TEXT NOT NULL,
-                size INTEGER,
-                mime_type TEXT,
-                created_at INTEGER NOT NULL,
-                FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
-            )",
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
+            .with_context(|| format!("Failed to open library {}", path.display()))?;
 
-        // Add indexes for better performance
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_snippets_project_id ON snippets(project_id)")
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+        if let Err(e) = crate::migrations::run(&pool).await {
+            pool.close().await;
+            return Err(e);
+        }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_todos_parent_id ON todos(parent_id)")
-            .execute(&self.pool)
+        let cutoff =
+            chrono::Utc::now().timestamp_millis() - ACTIVITY_RETENTION_DAYS * 24 * 60 * 60 * 1000;
+        sqlx::query("DELETE FROM activity_log WHERE created_at < ?")
+            .bind(cutoff)
+            .execute(&pool)
             .await
-            .map_err(|e| e.to_string())?;
+            .context("Failed to prune activity log")?;
+        Ok(pool)
+    }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_todos_project_id ON todos(project_id)")
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+    /// 切换到另一个资料库文件
+    ///
+    /// 新资料库打开并迁移成功后才替换当前连接，失败时保持原资料库不变；
+    /// 旧连接池在进行中的操作完成后关闭。
+    pub async fn switch_library(&self, path: &Path, create: bool) -> AppResult<()> {
+        let pool = Self::connect(path, create).await?;
+        let previous = {
+            let mut library = self.library.write().unwrap_or_else(PoisonError::into_inner);
+            std::mem::replace(
+                &mut *library,
+                Library {
+                    pool,
+                    path: Some(path.to_path_buf()),
+                },
+            )
+        };
+        close_pool(&previous.pool).await;
+        self.notify(ChangeEvent::reloaded());
+        Ok(())
+    }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_todos_due_date ON todos(due_date)")
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+    /// 关闭当前连接池，之后的操作都会失败
+    pub async fn close(&self) {
+        close_pool(&self.pool()).await;
+    }
 
-        sqlx::query("CREATE INDEX IF NOT EXISTS idx_todos_status ON todos(status)")
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+    /// 当前资料库的文件路径，内存数据库返回 None
+    pub fn path(&self) -> Option<PathBuf> {
+        self.library
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .path
+            .clone()
+    }
 
-        Ok(())
+    /// 当前资料库的连接池
+    fn pool(&self) -> SqlitePool {
+        self.library
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .pool
+            .clone()
     }
+}
 
-    pub async fn create_snippet(&self, req: CreateSnippetRequest) -> Result<CodeSnippet, String> {
+// ============================================================================
+// Snippets
+// ============================================================================
+
+#[async_trait]
+impl SnippetRepository for Database {
+    async fn create_snippet(&self, req: CreateSnippetRequest) -> AppResult<CodeSnippet> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().timestamp_millis();
         let tags_json = serde_json::to_string(&req.tags).unwrap_or("[]".to_string());
 
+        let mut action = self.begin_action("Create snippet").await?;
         sqlx::query(
             "INSERT INTO snippets (id, title, description, code, language, tags, folder_id, project_id, is_favorite, usage_count, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
@@ -421,11 +274,12 @@ impl Database {
         .bind(0) // usage_count default to 0
         .bind(now)
         .bind(now)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
+        .execute(action.conn())
+        .await?;
+        action.created("snippets", "id", &id);
+        action.commit().await?;
 
-        Ok(CodeSnippet {
+        let snippet = CodeSnippet {
             id,
             title: req.title,
             description: req.description,
@@ -438,76 +292,50 @@ impl Database {
             usage_count: 0,
             created_at: now,
             updated_at: now,
-        })
+            library_id: None,
+        };
+        self.notify(ChangeEvent::created(
+            ChangeEntity::Snippet,
+            &snippet.id,
+            &snippet,
+        ));
+        Ok(snippet)
     }
 
-    pub async fn get_all_snippets(&self) -> Result<Vec<CodeSnippet>, String> {
+    async fn get_all_snippets(&self) -> AppResult<Vec<CodeSnippet>> {
         let rows = sqlx::query("SELECT * FROM snippets ORDER BY updated_at DESC")
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
-
-        let mut snippets = Vec::new();
-        for row in rows {
-            let tags_str: String = row.get("tags");
-            let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
+            .fetch_all(&self.pool())
+            .await?;
 
-            snippets.push(CodeSnippet {
-                id: row.get("id"),
-                title: row.get("title"),
-                description: row.get("description"),
-                code: row.get("code"),
-                language: row.get("language"),
-                tags,
-                folder_id: row.get("folder_id"),
-                project_id: row.get("project_id"),
-                is_favorite: row.try_get("is_favorite").unwrap_or(0) != 0,
-                usage_count: row.try_get("usage_count").unwrap_or(0),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
-        }
-        Ok(snippets)
+        Ok(rows.iter().map(snippet_from_row).collect())
     }
 
-    pub async fn get_snippet(&self, id: &str) -> Result<Option<CodeSnippet>, String> {
+    async fn get_snippet(&self, id: &str) -> AppResult<Option<CodeSnippet>> {
         let row = sqlx::query("SELECT * FROM snippets WHERE id = ?")
             .bind(id)
-            .fetch_optional(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+            .fetch_optional(&self.pool())
+            .await?;
 
         if let Some(row) = row {
-            let tags_str: String = row.get("tags");
-            let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
-
-            Ok(Some(CodeSnippet {
-                id: row.get("id"),
-                title: row.get("title"),
-                description: row.get("description"),
-                code: row.get("code"),
-                language: row.get("language"),
-                tags,
-                folder_id: row.get("folder_id"),
-                project_id: row.get("project_id"),
-                is_favorite: row.try_get("is_favorite").unwrap_or(0) != 0,
-                usage_count: row.try_get("usage_count").unwrap_or(0),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            }))
+            Ok(Some(snippet_from_row(&row)))
         } else {
             Ok(None)
         }
     }
 
-    pub async fn update_snippet(&self, req: UpdateSnippetRequest) -> Result<CodeSnippet, String> {
+    async fn update_snippet(&self, req: UpdateSnippetRequest) -> AppResult<CodeSnippet> {
         let now = chrono::Utc::now().timestamp_millis();
 
+        let mut action = self.begin_action("Edit snippet").await?;
+        action.track("snippets", "id", &req.id).await?;
+
         // First get the current snippet to fill in missing fields
-        let current = self
-            .get_snippet(&req.id)
+        let current = sqlx::query("SELECT * FROM snippets WHERE id = ?")
+            .bind(&req.id)
+            .fetch_optional(action.conn())
             .await?
-            .ok_or_else(|| "Snippet not found".to_string())?;
+            .map(|row| snippet_from_row(&row))
+            .ok_or_else(|| AppError::not_found("snippet", &req.id))?;
 
         // Use provided values or fall back to current values
         let title = req.title.unwrap_or(current.title);
@@ -536,11 +364,11 @@ impl Database {
         .bind(usage_count)
         .bind(now)
         .bind(&req.id)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| e.to_string())?;
+        .execute(action.conn())
+        .await?;
+        action.commit().await?;
 
-        Ok(CodeSnippet {
+        let snippet = CodeSnippet {
             id: req.id,
             title,
             description,
@@ -553,20 +381,33 @@ impl Database {
             usage_count,
             created_at: current.created_at,
             updated_at: now,
-        })
+            library_id: None,
+        };
+        self.notify(ChangeEvent::updated(
+            ChangeEntity::Snippet,
+            &snippet.id,
+            &snippet,
+        ));
+        Ok(snippet)
     }
 
-    pub async fn delete_snippet(&self, id: &str) -> Result<(), String> {
+    async fn delete_snippet(&self, id: &str) -> AppResult<()> {
+        let mut action = self.begin_action("Delete snippet").await?;
+        action.track("snippets", "id", id).await?;
         sqlx::query("DELETE FROM snippets WHERE id = ?")
             .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
-
+            .execute(action.conn())
+            .await?;
+        action.commit().await?;
+
+        self.notify(ChangeEvent::deleted(
+            ChangeEntity::Snippet,
+            vec![id.to_string()],
+        ));
         Ok(())
     }
 
-    pub async fn search_snippets(&self, query: SearchQuery) -> Result<Vec<CodeSnippet>, String> {
+    async fn search_snippets(&self, query: SearchQuery) -> AppResult<Vec<CodeSnippet>> {
         let mut sql = String::from("SELECT * FROM snippets WHERE 1=1");
         let mut conditions = Vec::new();
 
@@ -594,64 +435,86 @@ impl Database {
 
         sql.push_str(" ORDER BY updated_at DESC");
 
-        let rows = sqlx::query(&sql)
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+        let rows = sqlx::query(&sql).fetch_all(&self.pool()).await?;
 
-        let mut snippets = Vec::new();
-        for row in rows {
-            let tags_str: String = row.get("tags");
-            let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
+        Ok(rows.iter().map(snippet_from_row).collect())
+    }
 
-            snippets.push(CodeSnippet {
-                id: row.get("id"),
-                title: row.get("title"),
-                description: row.get("description"),
-                code: row.get("code"),
-                language: row.get("language"),
-                tags,
-                folder_id: row.get("folder_id"),
-                project_id: row.get("project_id"),
-                is_favorite: row.try_get("is_favorite").unwrap_or(0) != 0,
-                usage_count: row.try_get("usage_count").unwrap_or(0),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
+    /// 获取文件夹及其所有子文件夹中的代码片段
+    async fn get_snippets_by_folder(&self, folder_id: &str) -> AppResult<Vec<CodeSnippet>> {
+        let folders = self.get_all_folders().await?;
+
+        // 广度优先收集所有子文件夹ID
+        let mut folder_ids = vec![folder_id.to_string()];
+        let mut index = 0;
+        while index < folder_ids.len() {
+            let current = folder_ids[index].clone();
+            for folder in &folders {
+                if folder.parent_id.as_deref() == Some(current.as_str())
+                    && !folder_ids.contains(&folder.id)
+                {
+                    folder_ids.push(folder.id.clone());
+                }
+            }
+            index += 1;
         }
-        Ok(snippets)
-    }
 
-    pub async fn create_folder(
-        &self,
-        name: String,
-        parent_id: Option<String>,
-    ) -> Result<Folder, String> {
-        let id = uuid::Uuid::new_v4().to_string();
-        let now = chrono::Utc::now().timestamp_millis();
+        let placeholders = vec!["?"; folder_ids.len()].join(", ");
+        let sql = format!(
+            "SELECT * FROM snippets WHERE folder_id IN ({}) ORDER BY updated_at DESC",
+            placeholders
+        );
+        let mut query = sqlx::query(&sql);
+        for id in &folder_ids {
+            query = query.bind(id);
+        }
 
-        sqlx::query("INSERT INTO folders (id, name, parent_id, created_at) VALUES (?, ?, ?, ?)")
-            .bind(&id)
-            .bind(&name)
-            .bind(&parent_id)
-            .bind(now)
-            .execute(&self.pool)
+        let rows = query
+            .fetch_all(&self.pool())
             .await
-            .map_err(|e| e.to_string())?;
+            .context("Failed to get snippets by folder")?;
 
-        Ok(Folder {
-            id,
-            name,
-            parent_id,
-            created_at: now,
-        })
+        Ok(rows.iter().map(snippet_from_row).collect())
+    }
+
+    /// 获取包含指定标签的代码片段
+    async fn get_snippets_by_tag(&self, tag: &str) -> AppResult<Vec<CodeSnippet>> {
+        let rows = sqlx::query(
+            "SELECT * FROM snippets WHERE EXISTS (SELECT 1 FROM json_each(snippets.tags) WHERE json_each.value = ?) ORDER BY updated_at DESC"
+        )
+        .bind(tag)
+        .fetch_all(&self.pool())
+        .await
+        .context("Failed to get snippets by tag")?;
+
+        Ok(rows.iter().map(snippet_from_row).collect())
+    }
+}
+
+// ============================================================================
+// Folders
+// ============================================================================
+
+#[async_trait]
+impl FolderRepository for Database {
+    async fn create_folder(&self, name: String, parent_id: Option<String>) -> AppResult<Folder> {
+        let mut action = self.begin_action("Create folder").await?;
+        let folder = insert_folder(action.conn(), name, parent_id).await?;
+        action.created("folders", "id", &folder.id);
+        action.commit().await?;
+
+        self.notify(ChangeEvent::created(
+            ChangeEntity::Folder,
+            &folder.id,
+            &folder,
+        ));
+        Ok(folder)
     }
 
-    pub async fn get_all_folders(&self) -> Result<Vec<Folder>, String> {
+    async fn get_all_folders(&self) -> AppResult<Vec<Folder>> {
         let rows = sqlx::query("SELECT * FROM folders ORDER BY created_at ASC")
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+            .fetch_all(&self.pool())
+            .await?;
 
         let mut folders = Vec::new();
         for row in rows {
@@ -665,30 +528,56 @@ impl Database {
         Ok(folders)
     }
 
-    pub async fn delete_folder(&self, id: &str) -> Result<(), String> {
+    async fn delete_folder(&self, id: &str) -> AppResult<()> {
+        let mut action = self.begin_action("Delete folder").await?;
+        action.track("folders", "id", id).await?;
+        let moved: Vec<String> = action
+            .track("snippets", "folder_id", id)
+            .await?
+            .iter()
+            .filter_map(|row| row.get("id").and_then(|id| id.as_str()))
+            .map(str::to_string)
+            .collect();
+
         sqlx::query("DELETE FROM folders WHERE id = ?")
             .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
+            .execute(action.conn())
+            .await?;
 
         sqlx::query("UPDATE snippets SET folder_id = NULL WHERE folder_id = ?")
             .bind(id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| e.to_string())?;
-
+            .execute(action.conn())
+            .await?;
+        action.commit().await?;
+
+        self.notify(ChangeEvent::deleted(
+            ChangeEntity::Folder,
+            vec![id.to_string()],
+        ));
+        if !moved.is_empty() {
+            // 文件夹中的代码片段移到了根目录
+            self.notify(ChangeEvent {
+                entity: ChangeEntity::Snippet,
+                action: ChangeAction::Updated,
+                ids: moved,
+                payload: None,
+            });
+        }
         Ok(())
     }
+}
 
-    // ============================================================================
-    // Workspace Management
-    // ============================================================================
+// ============================================================================
+// Workspaces & Projects
+// ============================================================================
 
-    pub async fn create_workspace(&self, workspace: Workspace) -> Result<Workspace, String> {
-        let settings_json = serde_json::to_string(&workspace.settings)
-            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+#[async_trait]
+impl ProjectRepository for Database {
+    async fn create_workspace(&self, workspace: Workspace) -> AppResult<Workspace> {
+        let settings_json =
+            serde_json::to_string(&workspace.settings).context("Failed to serialize settings")?;
 
+        let mut action = self.begin_action("Create workspace").await?;
         sqlx::query(
             "INSERT INTO workspaces (id, name, description, color, is_default, settings, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
         )
@@ -700,20 +589,27 @@ impl Database {
         .bind(&settings_json)
         .bind(&workspace.created_at)
         .bind(&workspace.updated_at)
-        .execute(&self.pool)
+        .execute(action.conn())
         .await
-        .map_err(|e| format!("Failed to create workspace: {}", e))?;
-
+        .context("Failed to create workspace")?;
+        action.created("workspaces", "id", &workspace.id);
+        action.commit().await?;
+
+        self.notify(ChangeEvent::created(
+            ChangeEntity::Workspace,
+            &workspace.id,
+            &workspace,
+        ));
         Ok(workspace)
     }
 
-    pub async fn get_workspaces(&self) -> Result<Vec<Workspace>, String> {
+    async fn get_workspaces(&self) -> AppResult<Vec<Workspace>> {
         let rows = sqlx::query(
             "SELECT id, name, description, color, is_default, settings, created_at, updated_at FROM workspaces ORDER BY created_at DESC"
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get workspaces: {}", e))?;
+        .context("Failed to get workspaces")?;
 
         let mut workspaces = Vec::new();
         for row in rows {
@@ -736,31 +632,41 @@ impl Database {
         Ok(workspaces)
     }
 
-    pub async fn update_workspace(
+    async fn update_workspace(
         &self,
         workspace_id: String,
         _updates: WorkspaceUpdate,
-    ) -> Result<Workspace, String> {
+    ) -> AppResult<Workspace> {
         let now = chrono::Utc::now().to_rfc3339();
 
+        let mut action = self.begin_action("Edit workspace").await?;
+        action.track("workspaces", "id", &workspace_id).await?;
         sqlx::query("UPDATE workspaces SET updated_at = ? WHERE id = ?")
             .bind(&now)
             .bind(&workspace_id)
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to update workspace: {}", e))?;
-
-        self.get_workspace(&workspace_id).await
+            .context("Failed to update workspace")?;
+        action.commit().await?;
+
+        let workspace = self.get_workspace(&workspace_id).await?;
+        self.notify(ChangeEvent::updated(
+            ChangeEntity::Workspace,
+            &workspace.id,
+            &workspace,
+        ));
+        Ok(workspace)
     }
 
-    pub async fn get_workspace(&self, workspace_id: &str) -> Result<Workspace, String> {
+    async fn get_workspace(&self, workspace_id: &str) -> AppResult<Workspace> {
         let row = sqlx::query(
             "SELECT id, name, description, color, is_default, settings, created_at, updated_at FROM workspaces WHERE id = ?"
         )
         .bind(workspace_id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get workspace: {}", e))?;
+        .context("Failed to get workspace")?
+        .ok_or_else(|| AppError::not_found("workspace", workspace_id))?;
 
         let settings_str: String = row.get("settings");
         let settings: std::collections::HashMap<String, serde_json::Value> =
@@ -778,28 +684,31 @@ impl Database {
         })
     }
 
-    pub async fn delete_workspace(&self, workspace_id: String) -> Result<(), String> {
+    async fn delete_workspace(&self, workspace_id: String) -> AppResult<()> {
+        let mut action = self.begin_action("Delete workspace").await?;
+        action.track("workspaces", "id", &workspace_id).await?;
         sqlx::query("DELETE FROM workspaces WHERE id = ?")
             .bind(&workspace_id)
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to delete workspace: {}", e))?;
+            .context("Failed to delete workspace")?;
+        action.commit().await?;
 
+        self.notify(ChangeEvent::deleted(
+            ChangeEntity::Workspace,
+            vec![workspace_id],
+        ));
         Ok(())
     }
 
-    // ============================================================================
-    // Project Management
-    // ============================================================================
-
-    pub async fn create_project(&self, project: Project) -> Result<Project, String> {
-        let settings_json = serde_json::to_string(&project.settings)
-            .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-        let metadata_json = serde_json::to_string(&project.metadata)
-            .map_err(|e| format!("Failed to serialize metadata: {}", e))?;
-        let tags_json = serde_json::to_string(&project.tags)
-            .map_err(|e| format!("Failed to serialize tags: {}", e))?;
+    async fn create_project(&self, project: Project) -> AppResult<Project> {
+        let settings_json =
+            serde_json::to_string(&project.settings).context("Failed to serialize settings")?;
+        let metadata_json =
+            serde_json::to_string(&project.metadata).context("Failed to serialize metadata")?;
+        let tags_json = serde_json::to_string(&project.tags).context("Failed to serialize tags")?;
 
+        let mut action = self.begin_action("Create project").await?;
         sqlx::query(
             "INSERT INTO projects (id, workspace_id, name, description, project_type, template, parent_id, path, color, icon, tags, settings, metadata, is_folder, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
@@ -819,20 +728,27 @@ impl Database {
         .bind(project.is_folder)
         .bind(&project.created_at)
         .bind(&project.updated_at)
-        .execute(&self.pool)
+        .execute(action.conn())
         .await
-        .map_err(|e| format!("Failed to create project: {}", e))?;
-
+        .context("Failed to create project")?;
+        action.created("projects", "id", &project.id);
+        action.commit().await?;
+
+        self.notify(ChangeEvent::created(
+            ChangeEntity::Project,
+            &project.id,
+            &project,
+        ));
         Ok(project)
     }
 
-    pub async fn get_projects(&self) -> Result<Vec<Project>, String> {
+    async fn get_projects(&self) -> AppResult<Vec<Project>> {
         let rows = sqlx::query(
             "SELECT id, workspace_id, name, description, project_type, template, parent_id, path, color, icon, tags, settings, metadata, is_folder, created_at, updated_at FROM projects ORDER BY created_at DESC"
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get projects: {}", e))?;
+        .context("Failed to get projects")?;
 
         let mut projects = Vec::new();
         for row in rows {
@@ -869,31 +785,41 @@ impl Database {
         Ok(projects)
     }
 
-    pub async fn update_project(
+    async fn update_project(
         &self,
         project_id: String,
         _updates: ProjectUpdate,
-    ) -> Result<Project, String> {
+    ) -> AppResult<Project> {
         let now = chrono::Utc::now().to_rfc3339();
 
+        let mut action = self.begin_action("Edit project").await?;
+        action.track("projects", "id", &project_id).await?;
         sqlx::query("UPDATE projects SET updated_at = ? WHERE id = ?")
             .bind(&now)
             .bind(&project_id)
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to update project: {}", e))?;
-
-        self.get_project(&project_id).await
+            .context("Failed to update project")?;
+        action.commit().await?;
+
+        let project = self.get_project(&project_id).await?;
+        self.notify(ChangeEvent::updated(
+            ChangeEntity::Project,
+            &project.id,
+            &project,
+        ));
+        Ok(project)
     }
 
-    pub async fn get_project(&self, project_id: &str) -> Result<Project, String> {
+    async fn get_project(&self, project_id: &str) -> AppResult<Project> {
         let row = sqlx::query(
             "SELECT id, workspace_id, name, description, project_type, template, parent_id, path, color, icon, tags, settings, metadata, is_folder, created_at, updated_at FROM projects WHERE id = ?"
         )
         .bind(project_id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get project: {}", e))?;
+        .context("Failed to get project")?
+        .ok_or_else(|| AppError::not_found("project", project_id))?;
 
         let tags_str: String = row.get("tags");
         let settings_str: String = row.get("settings");
@@ -925,63 +851,46 @@ impl Database {
         })
     }
 
-    pub async fn delete_project(&self, project_id: String) -> Result<(), String> {
+    async fn delete_project(&self, project_id: String) -> AppResult<()> {
+        let mut action = self.begin_action("Delete project").await?;
+        action.track("projects", "id", &project_id).await?;
         sqlx::query("DELETE FROM projects WHERE id = ?")
             .bind(&project_id)
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to delete project: {}", e))?;
+            .context("Failed to delete project")?;
+        action.commit().await?;
 
+        self.notify(ChangeEvent::deleted(
+            ChangeEntity::Project,
+            vec![project_id],
+        ));
         Ok(())
     }
 
-    pub async fn get_snippets_by_project(
-        &self,
-        project_id: String,
-    ) -> Result<Vec<CodeSnippet>, String> {
-        let rows = sqlx::query(
-            "SELECT id, title, description, code, language, tags, folder_id, project_id, created_at, updated_at FROM snippets WHERE project_id = ? ORDER BY updated_at DESC"
-        )
-        .bind(&project_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to get snippets by project: {}", e))?;
-
-        let mut snippets = Vec::new();
-        for row in rows {
-            let tags_str: String = row.get("tags");
-            let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
-
-            snippets.push(CodeSnippet {
-                id: row.get("id"),
-                title: row.get("title"),
-                description: row.get("description"),
-                code: row.get("code"),
-                language: row.get("language"),
-                tags,
-                folder_id: row.get("folder_id"),
-                project_id: row.get("project_id"),
-                is_favorite: row.try_get("is_favorite").unwrap_or(0) != 0,
-                usage_count: row.try_get("usage_count").unwrap_or(0),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-            });
-        }
+    async fn get_snippets_by_project(&self, project_id: String) -> AppResult<Vec<CodeSnippet>> {
+        let rows =
+            sqlx::query("SELECT * FROM snippets WHERE project_id = ? ORDER BY updated_at DESC")
+                .bind(&project_id)
+                .fetch_all(&self.pool())
+                .await
+                .context("Failed to get snippets by project")?;
 
-        Ok(snippets)
+        Ok(rows.iter().map(snippet_from_row).collect())
     }
+}
 
-    // ============================================================================
-    // Git Repository Management
-    // ============================================================================
+// ============================================================================
+// Git Repositories
+// ============================================================================
 
-    pub async fn create_git_repository(
-        &self,
-        repository: GitRepository,
-    ) -> Result<GitRepository, String> {
-        let remotes_json = serde_json::to_string(&repository.remotes)
-            .map_err(|e| format!("Failed to serialize remotes: {}", e))?;
+#[async_trait]
+impl GitRepoRepository for Database {
+    async fn create_git_repository(&self, repository: GitRepository) -> AppResult<GitRepository> {
+        let remotes_json =
+            serde_json::to_string(&repository.remotes).context("Failed to serialize remotes")?;
 
+        let mut action = self.begin_action("Create Git repository").await?;
         sqlx::query(
             "INSERT INTO git_repositories (id, name, description, path, is_default, remotes, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
         )
@@ -993,20 +902,27 @@ impl Database {
         .bind(&remotes_json)
         .bind(&repository.created_at)
         .bind(&repository.updated_at)
-        .execute(&self.pool)
+        .execute(action.conn())
         .await
-        .map_err(|e| format!("Failed to create git repository: {}", e))?;
-
+        .context("Failed to create git repository")?;
+        action.created("git_repositories", "id", &repository.id);
+        action.commit().await?;
+
+        self.notify(ChangeEvent::created(
+            ChangeEntity::GitRepository,
+            &repository.id,
+            &repository,
+        ));
         Ok(repository)
     }
 
-    pub async fn get_git_repositories(&self) -> Result<Vec<GitRepository>, String> {
+    async fn get_git_repositories(&self) -> AppResult<Vec<GitRepository>> {
         let rows = sqlx::query(
             "SELECT id, name, description, path, is_default, remotes, created_at, updated_at FROM git_repositories ORDER BY created_at DESC"
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get git repositories: {}", e))?;
+        .context("Failed to get git repositories")?;
 
         let mut repositories = Vec::new();
         for row in rows {
@@ -1028,31 +944,43 @@ impl Database {
         Ok(repositories)
     }
 
-    pub async fn update_git_repository(
+    async fn update_git_repository(
         &self,
         repository_id: String,
         _updates: GitRepositoryUpdate,
-    ) -> Result<GitRepository, String> {
+    ) -> AppResult<GitRepository> {
         let now = chrono::Utc::now().to_rfc3339();
 
+        let mut action = self.begin_action("Edit Git repository").await?;
+        action
+            .track("git_repositories", "id", &repository_id)
+            .await?;
         sqlx::query("UPDATE git_repositories SET updated_at = ? WHERE id = ?")
             .bind(&now)
             .bind(&repository_id)
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to update git repository: {}", e))?;
-
-        self.get_git_repository(&repository_id).await
+            .context("Failed to update git repository")?;
+        action.commit().await?;
+
+        let repository = self.get_git_repository(&repository_id).await?;
+        self.notify(ChangeEvent::updated(
+            ChangeEntity::GitRepository,
+            &repository.id,
+            &repository,
+        ));
+        Ok(repository)
     }
 
-    pub async fn get_git_repository(&self, repository_id: &str) -> Result<GitRepository, String> {
+    async fn get_git_repository(&self, repository_id: &str) -> AppResult<GitRepository> {
         let row = sqlx::query(
             "SELECT id, name, description, path, is_default, remotes, created_at, updated_at FROM git_repositories WHERE id = ?"
         )
         .bind(repository_id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get git repository: {}", e))?;
+        .context("Failed to get git repository")?
+        .ok_or_else(|| AppError::not_found("git_repository", repository_id))?;
 
         let remotes_str: String = row.get("remotes");
         let remotes: Vec<GitRemote> = serde_json::from_str(&remotes_str).unwrap_or_default();
@@ -1069,33 +997,48 @@ impl Database {
         })
     }
 
-    pub async fn delete_git_repository(&self, repository_id: String) -> Result<(), String> {
+    async fn delete_git_repository(&self, repository_id: String) -> AppResult<()> {
+        let mut action = self.begin_action("Delete Git repository").await?;
+        action
+            .track("git_repositories", "id", &repository_id)
+            .await?;
         sqlx::query("DELETE FROM git_repositories WHERE id = ?")
             .bind(&repository_id)
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to delete git repository: {}", e))?;
+            .context("Failed to delete git repository")?;
+        action.commit().await?;
 
+        self.notify(ChangeEvent::deleted(
+            ChangeEntity::GitRepository,
+            vec![repository_id],
+        ));
         Ok(())
     }
+}
 
-    // ============================================================================
-    // Todo Management
-    // ============================================================================
+// ============================================================================
+// Todos
+// ============================================================================
 
-    pub async fn create_todo(&self, req: CreateTodoRequest) -> Result<Todo, String> {
+#[async_trait]
+impl TodoRepository for Database {
+    async fn create_todo(&self, req: CreateTodoRequest) -> AppResult<Todo> {
         let id = uuid::Uuid::new_v4().to_string();
         let now = chrono::Utc::now().timestamp_millis(); // 使用毫秒级时间戳
         let dependencies_json = serde_json::to_string(&req.dependencies.unwrap_or_default())
-            .map_err(|e| format!("Failed to serialize dependencies: {}", e))?;
+            .context("Failed to serialize dependencies")?;
+
+        let status = req.status.as_deref().unwrap_or("todo");
 
+        let mut action = self.begin_action("Create todo").await?;
         sqlx::query(
-            "INSERT INTO todos (id, title, description, status, priority, due_date, estimated_hours, progress, assignee, project_id, parent_id, recurring_config, dependencies, completed, archived, created_by, updated_by, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO todos (id, title, description, status, priority, due_date, estimated_hours, progress, assignee, project_id, parent_id, recurring_config, dependencies, completed, archived, created_by, updated_by, created_at, updated_at, completed_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(&id)
         .bind(&req.title)
         .bind(&req.description)
-        .bind(req.status.as_deref().unwrap_or("todo"))
+        .bind(status)
         .bind(&req.priority)
         .bind(&req.due_date)
         .bind(req.estimated_hours)
@@ -1111,9 +1054,10 @@ impl Database {
         .bind(&req.assignee) // updated_by
         .bind(now)
         .bind(now)
-        .execute(&self.pool)
+        .bind((status == "completed").then_some(now))
+        .execute(action.conn())
         .await
-        .map_err(|e| format!("Failed to create todo: {}", e))?;
+        .context("Failed to create todo")?;
 
         // Add tag relations if provided
         if let Some(tags) = &req.tags {
@@ -1123,349 +1067,217 @@ impl Database {
                 )
                 .bind(&id)
                 .bind(tag_id)
-                .execute(&self.pool)
+                .execute(action.conn())
                 .await
-                .map_err(|e| format!("Failed to add tag relation: {}", e))?;
+                .context("Failed to add tag relation")?;
             }
         }
+        action.created("todos", "id", &id);
+        action.created("todo_tag_relations", "todo_id", &id);
+        action.commit().await?;
 
-        self.get_todo(&id).await
+        let todo = self.get_todo(&id).await?;
+        self.notify(ChangeEvent::created(ChangeEntity::Todo, &todo.id, &todo));
+        Ok(todo)
     }
 
-    pub async fn get_todos(&self) -> Result<Vec<Todo>, String> {
-        let rows = sqlx::query(
-            "SELECT id, title, description, status, priority, due_date, estimated_hours, actual_hours, progress, assignee, project_id, parent_id, recurring_config, dependencies, completed, archived, created_by, updated_by, created_at, updated_at, archived_at FROM todos WHERE archived = false ORDER BY created_at DESC"
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to get todos: {}", e))?;
-
-        let mut todos = Vec::new();
-        for row in rows {
-            let dependencies_str: String = row.get("dependencies");
-            let dependencies: Vec<String> =
-                serde_json::from_str(&dependencies_str).unwrap_or_default();
-
-            let todo_id: String = row.get("id");
-            let tags = self.get_todo_tags(&todo_id).await?;
-            let subtasks = self.get_subtasks(&todo_id).await?;
-
-            todos.push(Todo {
-                id: todo_id,
-                title: row.get("title"),
-                description: row.get("description"),
-                status: row.get("status"),
-                priority: row.get("priority"),
-                due_date: row.get("due_date"),
-                estimated_hours: row.get("estimated_hours"),
-                actual_hours: row.get("actual_hours"),
-                progress: row.get("progress"),
-                assignee: row.get("assignee"),
-                project_id: row.get("project_id"),
-                parent_id: row.get("parent_id"),
-                recurring_config: row.get("recurring_config"),
-                dependencies,
-                completed: row.get("completed"),
-                archived: row.get("archived"),
-                created_by: row.get("created_by"),
-                updated_by: row.get("updated_by"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                archived_at: row.get("archived_at"),
-                tags,
-                subtasks,
-            });
-        }
+    async fn get_todos(&self) -> AppResult<Vec<Todo>> {
+        let sql = format!(
+            "SELECT {} FROM todos WHERE archived = false ORDER BY created_at DESC",
+            TODO_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .fetch_all(&self.pool())
+            .await
+            .context("Failed to get todos")?;
 
+        let mut todos: Vec<Todo> = rows.iter().map(todo_from_row).collect();
+        self.load_todo_tags(&mut todos).await?;
         Ok(todos)
     }
 
-    pub async fn get_todo(&self, todo_id: &str) -> Result<Todo, String> {
-        let row = sqlx::query(
-            "SELECT id, title, description, status, priority, due_date, estimated_hours, actual_hours, progress, assignee, project_id, parent_id, recurring_config, dependencies, completed, archived, created_by, updated_by, created_at, updated_at, archived_at FROM todos WHERE id = ?"
-        )
-        .bind(todo_id)
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to get todo: {}", e))?;
+    async fn get_todo(&self, todo_id: &str) -> AppResult<Todo> {
+        // 一次查询取出该 TODO 及其所有层级的子任务；UNION 去重，parent_id 成环时也会结束
+        let sql = format!(
+            "{} SELECT {} FROM todos WHERE id IN (SELECT id FROM subtree) ORDER BY created_at ASC",
+            TODO_SUBTREE, TODO_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(todo_id)
+            .fetch_all(&self.pool())
+            .await
+            .context("Failed to get todo")?;
+
+        let mut todos: Vec<Todo> = rows.iter().map(todo_from_row).collect();
+        self.load_todo_tags(&mut todos).await?;
+        assemble_todo_tree(todos, |todo| todo.id == todo_id)
+            .into_iter()
+            .find(|todo| todo.id == todo_id)
+            .ok_or_else(|| AppError::not_found("todo", todo_id))
+    }
 
-        let dependencies_str: String = row.get("dependencies");
-        let dependencies: Vec<String> = serde_json::from_str(&dependencies_str).unwrap_or_default();
+    async fn update_todo(&self, req: UpdateTodoRequest) -> AppResult<Todo> {
+        let mut action = self.begin_action("Edit todo").await?;
+        action.track("todos", "id", &req.id).await?;
+        action
+            .track("todo_tag_relations", "todo_id", &req.id)
+            .await?;
+        update_todo_record(action.conn(), &req).await?;
+        action.commit().await?;
+
+        let todo = self.get_todo(&req.id).await?;
+        self.notify(ChangeEvent::updated(ChangeEntity::Todo, &todo.id, &todo));
+        Ok(todo)
+    }
 
-        let tags = self.get_todo_tags(todo_id).await?;
-        let subtasks = self.get_subtasks(todo_id).await?;
+    async fn delete_todo(&self, todo_id: String) -> AppResult<()> {
+        let mut action = self.begin_action("Delete todo").await?;
+        action.track_todo(&todo_id).await?;
+        // 连同所有层级的子任务一起删除（标签关联、评论和附件由外键级联删除）
+        let deleted = delete_todo_subtree(action.conn(), &todo_id).await?;
+        action.commit().await?;
 
-        Ok(Todo {
-            id: row.get("id"),
-            title: row.get("title"),
-            description: row.get("description"),
-            status: row.get("status"),
-            priority: row.get("priority"),
-            due_date: row.get("due_date"),
-            estimated_hours: row.get("estimated_hours"),
-            actual_hours: row.get("actual_hours"),
-            progress: row.get("progress"),
-            assignee: row.get("assignee"),
-            project_id: row.get("project_id"),
-            parent_id: row.get("parent_id"),
-            recurring_config: row.get("recurring_config"),
-            dependencies,
-            completed: row.get("completed"),
-            archived: row.get("archived"),
-            created_by: row.get("created_by"),
-            updated_by: row.get("updated_by"),
-            created_at: row.get("created_at"),
-            updated_at: row.get("updated_at"),
-            archived_at: row.get("archived_at"),
-            tags,
-            subtasks,
-        })
+        self.notify(ChangeEvent::deleted(ChangeEntity::Todo, deleted));
+        Ok(())
     }
 
-    pub async fn update_todo(&self, req: UpdateTodoRequest) -> Result<Todo, String> {
-        let now = chrono::Utc::now().timestamp_millis();
-
-        // Simple approach: update all fields that are provided
-        let mut sql_parts = Vec::new();
+    async fn search_todos(&self, query: TodoSearchQuery) -> AppResult<Vec<Todo>> {
+        let keyword = query
+            .keyword
+            .as_deref()
+            .filter(|keyword| !keyword.is_empty())
+            .map(like_pattern);
+        let text_filters = [
+            ("status = ?", query.status.as_deref()),
+            ("priority = ?", query.priority.as_deref()),
+            ("assignee = ?", query.assignee.as_deref()),
+            ("project_id = ?", query.project_id.as_deref()),
+            ("parent_id = ?", query.parent_id.as_deref()),
+            ("due_date >= ?", query.due_date_from.as_deref()),
+            ("due_date <= ?", query.due_date_to.as_deref()),
+        ];
+        let flag_filters = [
+            ("completed = ?", query.completed),
+            ("archived = ?", query.archived),
+        ];
+        let tags = query.tags.as_deref().filter(|tags| !tags.is_empty());
 
-        if let Some(title) = &req.title {
-            sql_parts.push(format!("title = '{}'", title.replace("'", "''")));
+        let mut conditions = Vec::new();
+        if keyword.is_some() {
+            conditions
+                .push("(title LIKE ? ESCAPE '\\' OR description LIKE ? ESCAPE '\\')".to_string());
         }
-        if let Some(description) = &req.description {
-            sql_parts.push(format!(
-                "description = '{}'",
-                description.replace("'", "''")
-            ));
+        for (condition, value) in &text_filters {
+            if value.is_some() {
+                conditions.push(condition.to_string());
+            }
         }
-        if let Some(status) = &req.status {
-            sql_parts.push(format!("status = '{}'", status.replace("'", "''")));
+        for (condition, value) in &flag_filters {
+            if value.is_some() {
+                conditions.push(condition.to_string());
+            }
         }
-        if let Some(priority) = &req.priority {
-            sql_parts.push(format!("priority = '{}'", priority.replace("'", "''")));
+        // 包含任一指定标签
+        if let Some(tags) = tags {
+            conditions.push(format!(
+                "id IN (SELECT todo_id FROM todo_tag_relations WHERE tag_id IN ({}))",
+                vec!["?"; tags.len()].join(", ")
+            ));
         }
-        if let Some(due_date) = &req.due_date {
-            sql_parts.push(format!("due_date = '{}'", due_date.replace("'", "''")));
-        }
-        if let Some(estimated_hours) = req.estimated_hours {
-            sql_parts.push(format!("estimated_hours = {}", estimated_hours));
-        }
-        if let Some(actual_hours) = req.actual_hours {
-            sql_parts.push(format!("actual_hours = {}", actual_hours));
-        }
-        if let Some(progress) = req.progress {
-            sql_parts.push(format!("progress = {}", progress));
-        }
-        if let Some(completed) = req.completed {
-            sql_parts.push(format!("completed = {}", completed));
-        }
-        if let Some(archived) = req.archived {
-            sql_parts.push(format!("archived = {}", archived));
-            if archived {
-                sql_parts.push(format!("archived_at = {}", now));
-            }
-        }
-
-        sql_parts.push(format!("updated_at = {}", now));
-
-        if !sql_parts.is_empty() {
-            let sql = format!("UPDATE todos SET {} WHERE id = ?", sql_parts.join(", "));
-            sqlx::query(&sql)
-                .bind(&req.id)
-                .execute(&self.pool)
-                .await
-                .map_err(|e| format!("Failed to update todo: {}", e))?;
-        }
-
-        // Update tags if provided
-        if let Some(tags) = &req.tags {
-            // Remove existing tag relations
-            sqlx::query("DELETE FROM todo_tag_relations WHERE todo_id = ?")
-                .bind(&req.id)
-                .execute(&self.pool)
-                .await
-                .map_err(|e| format!("Failed to remove tag relations: {}", e))?;
-
-            // Add new tag relations
-            for tag_id in tags {
-                sqlx::query("INSERT INTO todo_tag_relations (todo_id, tag_id) VALUES (?, ?)")
-                    .bind(&req.id)
-                    .bind(tag_id)
-                    .execute(&self.pool)
-                    .await
-                    .map_err(|e| format!("Failed to add tag relation: {}", e))?;
-            }
-        }
-
-        self.get_todo(&req.id).await
-    }
-
-    pub async fn delete_todo(&self, todo_id: String) -> Result<(), String> {
-        // Delete subtasks first
-        sqlx::query("DELETE FROM todos WHERE parent_id = ?")
-            .bind(&todo_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| format!("Failed to delete subtasks: {}", e))?;
-
-        // Delete the todo (tag relations will be deleted by CASCADE)
-        sqlx::query("DELETE FROM todos WHERE id = ?")
-            .bind(&todo_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| format!("Failed to delete todo: {}", e))?;
-
-        Ok(())
-    }
 
-    pub async fn search_todos(&self, query: TodoSearchQuery) -> Result<Vec<Todo>, String> {
-        let mut sql = String::from("SELECT DISTINCT t.id, t.title, t.description, t.status, t.priority, t.due_date, t.estimated_hours, t.actual_hours, t.progress, t.assignee, t.project_id, t.parent_id, t.recurring_config, t.dependencies, t.completed, t.archived, t.created_by, t.updated_by, t.created_at, t.updated_at, t.archived_at FROM todos t");
-        let mut joins = Vec::new();
-        let mut conditions = Vec::new();
-
-        if query.tags.is_some() {
-            joins.push("LEFT JOIN todo_tag_relations ttr ON t.id = ttr.todo_id");
-        }
-
-        if let Some(keyword) = &query.keyword {
-            if !keyword.is_empty() {
-                conditions.push(format!(
-                    "(t.title LIKE '%{}%' OR t.description LIKE '%{}%')",
-                    keyword, keyword
-                ));
-            }
-        }
-
-        if let Some(status) = &query.status {
-            conditions.push(format!("t.status = '{}'", status));
-        }
-
-        if let Some(priority) = &query.priority {
-            conditions.push(format!("t.priority = '{}'", priority));
-        }
-
-        if let Some(completed) = query.completed {
-            conditions.push(format!("t.completed = {}", completed));
+        let mut sql = format!("SELECT {} FROM todos", TODO_COLUMNS);
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
         }
+        sql.push_str(" ORDER BY updated_at DESC");
 
-        if let Some(archived) = query.archived {
-            conditions.push(format!("t.archived = {}", archived));
+        // 参数顺序与上面的条件一致
+        let mut statement = sqlx::query(&sql);
+        if let Some(keyword) = &keyword {
+            statement = statement.bind(keyword).bind(keyword);
         }
-
-        if let Some(tags) = &query.tags {
-            if !tags.is_empty() {
-                let tag_conditions: Vec<String> = tags
-                    .iter()
-                    .map(|tag| format!("ttr.tag_id = '{}'", tag))
-                    .collect();
-                conditions.push(format!("({})", tag_conditions.join(" OR ")));
-            }
+        for value in text_filters.iter().filter_map(|(_, value)| *value) {
+            statement = statement.bind(value);
         }
-
-        if !joins.is_empty() {
-            sql.push_str(" ");
-            sql.push_str(&joins.join(" "));
+        for value in flag_filters.iter().filter_map(|(_, value)| *value) {
+            statement = statement.bind(value);
         }
-
-        if !conditions.is_empty() {
-            sql.push_str(" WHERE ");
-            sql.push_str(&conditions.join(" AND "));
+        for tag in tags.unwrap_or_default() {
+            statement = statement.bind(tag);
         }
-
-        sql.push_str(" ORDER BY t.updated_at DESC");
-
-        let rows = sqlx::query(&sql)
-            .fetch_all(&self.pool)
+        let rows = statement
+            .fetch_all(&self.pool())
             .await
-            .map_err(|e| format!("Failed to search todos: {}", e))?;
-
-        let mut todos = Vec::new();
-        for row in rows {
-            let dependencies_str: String = row.get("dependencies");
-            let dependencies: Vec<String> =
-                serde_json::from_str(&dependencies_str).unwrap_or_default();
-
-            let todo_id: String = row.get("id");
-            let tags = self.get_todo_tags(&todo_id).await?;
-            let subtasks = self.get_subtasks(&todo_id).await?;
-
-            todos.push(Todo {
-                id: todo_id,
-                title: row.get("title"),
-                description: row.get("description"),
-                status: row.get("status"),
-                priority: row.get("priority"),
-                due_date: row.get("due_date"),
-                estimated_hours: row.get("estimated_hours"),
-                actual_hours: row.get("actual_hours"),
-                progress: row.get("progress"),
-                assignee: row.get("assignee"),
-                project_id: row.get("project_id"),
-                parent_id: row.get("parent_id"),
-                recurring_config: row.get("recurring_config"),
-                dependencies,
-                completed: row.get("completed"),
-                archived: row.get("archived"),
-                created_by: row.get("created_by"),
-                updated_by: row.get("updated_by"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                archived_at: row.get("archived_at"),
-                tags,
-                subtasks,
-            });
-        }
+            .context("Failed to search todos")?;
 
+        let mut todos: Vec<Todo> = rows.iter().map(todo_from_row).collect();
+        self.load_todo_tags(&mut todos).await?;
         Ok(todos)
     }
 
-    pub async fn batch_update_todos(
-        &self,
-        operation: BatchTodoOperation,
-    ) -> Result<Vec<Todo>, String> {
+    async fn batch_update_todos(&self, operation: BatchTodoOperation) -> AppResult<Vec<Todo>> {
         let now = chrono::Utc::now().timestamp_millis();
+        let label = match operation.operation.as_str() {
+            "complete" => "Complete todos",
+            "archive" => "Archive todos",
+            "delete" => "Delete todos",
+            "update" => "Edit todos",
+            _ => {
+                return Err(AppError::validation(format!(
+                    "Unknown batch operation: {}",
+                    operation.operation
+                )))
+            }
+        };
+
+        let mut action = self.begin_action(label).await?;
+        for todo_id in &operation.todo_ids {
+            action.track_todo(todo_id).await?;
+        }
 
-        match operation.operation.as_str() {
-            "complete" => {
-                for todo_id in &operation.todo_ids {
-                    sqlx::query("UPDATE todos SET completed = true, status = 'completed', updated_at = ? WHERE id = ?")
+        let mut deleted = Vec::new();
+        for todo_id in &operation.todo_ids {
+            match operation.operation.as_str() {
+                "complete" => {
+                    sqlx::query("UPDATE todos SET completed = true, status = 'completed', completed_at = COALESCE(completed_at, ?), updated_at = ? WHERE id = ?")
+                        .bind(now)
                         .bind(now)
                         .bind(todo_id)
-                        .execute(&self.pool)
+                        .execute(action.conn())
                         .await
-                        .map_err(|e| format!("Failed to complete todo {}: {}", todo_id, e))?;
+                        .with_context(|| format!("Failed to complete todo {}", todo_id))?;
                 }
-            }
-            "archive" => {
-                for todo_id in &operation.todo_ids {
+                "archive" => {
                     sqlx::query("UPDATE todos SET archived = true, archived_at = ?, updated_at = ? WHERE id = ?")
                         .bind(now)
                         .bind(now)
                         .bind(todo_id)
-                        .execute(&self.pool)
+                        .execute(action.conn())
                         .await
-                        .map_err(|e| format!("Failed to archive todo {}: {}", todo_id, e))?;
+                        .with_context(|| format!("Failed to archive todo {}", todo_id))?;
                 }
-            }
-            "delete" => {
-                for todo_id in &operation.todo_ids {
-                    sqlx::query("DELETE FROM todos WHERE id = ?")
-                        .bind(todo_id)
-                        .execute(&self.pool)
-                        .await
-                        .map_err(|e| format!("Failed to delete todo {}: {}", todo_id, e))?;
+                "delete" => {
+                    for id in delete_todo_subtree(action.conn(), todo_id).await? {
+                        if !deleted.contains(&id) {
+                            deleted.push(id);
+                        }
+                    }
                 }
-                return Ok(Vec::new()); // Return empty for deleted todos
-            }
-            "update" => {
-                if let Some(updates) = &operation.updates {
-                    for todo_id in &operation.todo_ids {
+                _ => {
+                    if let Some(updates) = &operation.updates {
                         let mut update_req = updates.clone();
                         update_req.id = todo_id.clone();
-                        self.update_todo(update_req).await?;
+                        update_todo_record(action.conn(), &update_req).await?;
                     }
                 }
             }
-            _ => return Err(format!("Unknown batch operation: {}", operation.operation)),
+        }
+        action.commit().await?;
+
+        if operation.operation == "delete" {
+            self.notify(ChangeEvent::deleted(ChangeEntity::Todo, deleted));
+            return Ok(Vec::new()); // Return empty for deleted todos
         }
 
         // Return updated todos (except for delete operation)
@@ -1476,22 +1288,35 @@ impl Database {
             }
         }
 
+        if operation.operation == "update" {
+            // 与单个编辑一致，逐个发布事件
+            for todo in &result {
+                self.notify(ChangeEvent::updated(ChangeEntity::Todo, &todo.id, todo));
+            }
+        } else {
+            self.notify(ChangeEvent::with_payload(
+                ChangeEntity::Todo,
+                ChangeAction::Updated,
+                result.iter().map(|todo| todo.id.clone()).collect(),
+                &result,
+            ));
+        }
         Ok(result)
     }
 
-    pub async fn get_todo_stats(&self) -> Result<TodoStats, String> {
+    async fn get_todo_stats(&self) -> AppResult<TodoStats> {
         let total_row = sqlx::query("SELECT COUNT(*) as count FROM todos WHERE archived = false")
-            .fetch_one(&self.pool)
+            .fetch_one(&self.pool())
             .await
-            .map_err(|e| format!("Failed to get total count: {}", e))?;
+            .context("Failed to get total count")?;
         let total: i64 = total_row.get("count");
 
         let completed_row = sqlx::query(
             "SELECT COUNT(*) as count FROM todos WHERE completed = true AND archived = false",
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get completed count: {}", e))?;
+        .context("Failed to get completed count")?;
         let completed: i64 = completed_row.get("count");
 
         let pending = total - completed;
@@ -1500,34 +1325,34 @@ impl Database {
         let in_progress_row = sqlx::query(
             "SELECT COUNT(*) as count FROM todos WHERE status = 'in_progress' AND archived = false",
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get in_progress count: {}", e))?;
+        .context("Failed to get in_progress count")?;
         let in_progress: i64 = in_progress_row.get("count");
 
         let blocked_row = sqlx::query(
             "SELECT COUNT(*) as count FROM todos WHERE status = 'blocked' AND archived = false",
         )
-        .fetch_one(&self.pool)
+        .fetch_one(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get blocked count: {}", e))?;
+        .context("Failed to get blocked count")?;
         let blocked: i64 = blocked_row.get("count");
 
         // Get overdue count
         let now = chrono::Utc::now().format("%Y-%m-%d").to_string();
         let overdue_row = sqlx::query("SELECT COUNT(*) as count FROM todos WHERE due_date < ? AND completed = false AND archived = false")
             .bind(&now)
-            .fetch_one(&self.pool)
+            .fetch_one(&self.pool())
             .await
-            .map_err(|e| format!("Failed to get overdue count: {}", e))?;
+            .context("Failed to get overdue count")?;
         let overdue: i64 = overdue_row.get("count");
 
         // Get due today count
         let due_today_row = sqlx::query("SELECT COUNT(*) as count FROM todos WHERE due_date = ? AND completed = false AND archived = false")
             .bind(&now)
-            .fetch_one(&self.pool)
+            .fetch_one(&self.pool())
             .await
-            .map_err(|e| format!("Failed to get due today count: {}", e))?;
+            .context("Failed to get due today count")?;
         let due_today: i64 = due_today_row.get("count");
 
         // Get due this week count
@@ -1539,17 +1364,17 @@ impl Database {
         let due_this_week_row = sqlx::query("SELECT COUNT(*) as count FROM todos WHERE due_date BETWEEN ? AND ? AND completed = false AND archived = false")
             .bind(&now)
             .bind(&week_end)
-            .fetch_one(&self.pool)
+            .fetch_one(&self.pool())
             .await
-            .map_err(|e| format!("Failed to get due this week count: {}", e))?;
+            .context("Failed to get due this week count")?;
         let due_this_week: i64 = due_this_week_row.get("count");
 
         // Get priority distribution
         let mut by_priority = HashMap::new();
         let priority_rows = sqlx::query("SELECT priority, COUNT(*) as count FROM todos WHERE archived = false GROUP BY priority")
-            .fetch_all(&self.pool)
+            .fetch_all(&self.pool())
             .await
-            .map_err(|e| format!("Failed to get priority distribution: {}", e))?;
+            .context("Failed to get priority distribution")?;
 
         for row in priority_rows {
             let priority: Option<String> = row.get("priority");
@@ -1560,9 +1385,9 @@ impl Database {
         // Get project distribution
         let mut by_project = HashMap::new();
         let project_rows = sqlx::query("SELECT project_id, COUNT(*) as count FROM todos WHERE archived = false GROUP BY project_id")
-            .fetch_all(&self.pool)
+            .fetch_all(&self.pool())
             .await
-            .map_err(|e| format!("Failed to get project distribution: {}", e))?;
+            .context("Failed to get project distribution")?;
 
         for row in project_rows {
             let project_id: Option<String> = row.get("project_id");
@@ -1573,9 +1398,9 @@ impl Database {
         // Get assignee distribution
         let mut by_assignee = HashMap::new();
         let assignee_rows = sqlx::query("SELECT assignee, COUNT(*) as count FROM todos WHERE archived = false GROUP BY assignee")
-            .fetch_all(&self.pool)
+            .fetch_all(&self.pool())
             .await
-            .map_err(|e| format!("Failed to get assignee distribution: {}", e))?;
+            .context("Failed to get assignee distribution")?;
 
         for row in assignee_rows {
             let assignee: Option<String> = row.get("assignee");
@@ -1598,110 +1423,26 @@ impl Database {
         })
     }
 
-    // Helper methods for todos
-    async fn get_todo_tags(&self, todo_id: &str) -> Result<Vec<String>, String> {
-        let rows = sqlx::query(
-            "SELECT tt.id FROM todo_tags tt 
-             JOIN todo_tag_relations ttr ON tt.id = ttr.tag_id 
-             WHERE ttr.todo_id = ?",
-        )
-        .bind(todo_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to get todo tags: {}", e))?;
-
-        Ok(rows.into_iter().map(|row| row.get("id")).collect())
-    }
-
-    async fn get_subtasks(&self, parent_id: &str) -> Result<Vec<Todo>, String> {
-        let rows = sqlx::query(
-            "SELECT id, title, description, status, priority, due_date, estimated_hours, actual_hours, progress, assignee, project_id, parent_id, recurring_config, dependencies, completed, archived, created_by, updated_by, created_at, updated_at, archived_at FROM todos WHERE parent_id = ? ORDER BY created_at ASC"
-        )
-        .bind(parent_id)
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to get subtasks: {}", e))?;
-
-        let mut subtasks = Vec::new();
-        for row in rows {
-            let dependencies_str: String = row.get("dependencies");
-            let dependencies: Vec<String> =
-                serde_json::from_str(&dependencies_str).unwrap_or_default();
-
-            let todo_id: String = row.get("id");
-            let tags = self.get_todo_tags(&todo_id).await?;
-
-            subtasks.push(Todo {
-                id: todo_id,
-                title: row.get("title"),
-                description: row.get("description"),
-                status: row.get("status"),
-                priority: row.get("priority"),
-                due_date: row.get("due_date"),
-                estimated_hours: row.get("estimated_hours"),
-                actual_hours: row.get("actual_hours"),
-                progress: row.get("progress"),
-                assignee: row.get("assignee"),
-                project_id: row.get("project_id"),
-                parent_id: row.get("parent_id"),
-                recurring_config: row.get("recurring_config"),
-                dependencies,
-                completed: row.get("completed"),
-                archived: row.get("archived"),
-                created_by: row.get("created_by"),
-                updated_by: row.get("updated_by"),
-                created_at: row.get("created_at"),
-                updated_at: row.get("updated_at"),
-                archived_at: row.get("archived_at"),
-                tags,
-                subtasks: Vec::new(), // Don't load nested subtasks to avoid infinite recursion
-            });
-        }
-
-        Ok(subtasks)
-    }
-
-    // ============================================================================
-    // Todo Tag Management
-    // ============================================================================
-
-    pub async fn create_todo_tag(&self, req: CreateTodoTagRequest) -> Result<TodoTag, String> {
-        let id = uuid::Uuid::new_v4().to_string();
-        let now = chrono::Utc::now().timestamp_millis();
-
+    async fn create_todo_tag(&self, req: CreateTodoTagRequest) -> AppResult<TodoTag> {
         // Get color info from predefined colors
         let color_info = self.get_tag_color_info(&req.color_id)?;
 
-        sqlx::query(
-            "INSERT INTO todo_tags (id, name, color, bg_color, color_id, created_at) VALUES (?, ?, ?, ?, ?, ?)"
-        )
-        .bind(&id)
-        .bind(&req.name)
-        .bind(&color_info.color)
-        .bind(&color_info.bg_color)
-        .bind(&req.color_id)
-        .bind(now)
-        .execute(&self.pool)
-        .await
-        .map_err(|e| format!("Failed to create todo tag: {}", e))?;
+        let mut action = self.begin_action("Create tag").await?;
+        let tag = insert_todo_tag(action.conn(), req, color_info).await?;
+        action.created("todo_tags", "id", &tag.id);
+        action.commit().await?;
 
-        Ok(TodoTag {
-            id,
-            name: req.name,
-            color: color_info.color,
-            bg_color: color_info.bg_color,
-            color_id: req.color_id,
-            created_at: now,
-        })
+        self.notify(ChangeEvent::created(ChangeEntity::TodoTag, &tag.id, &tag));
+        Ok(tag)
     }
 
-    pub async fn get_todo_tags_list(&self) -> Result<Vec<TodoTag>, String> {
+    async fn get_todo_tags_list(&self) -> AppResult<Vec<TodoTag>> {
         let rows = sqlx::query(
             "SELECT id, name, color, bg_color, color_id, created_at FROM todo_tags ORDER BY created_at ASC"
         )
-        .fetch_all(&self.pool)
+        .fetch_all(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get todo tags: {}", e))?;
+        .context("Failed to get todo tags")?;
 
         let mut tags = Vec::new();
         for row in rows {
@@ -1718,7 +1459,7 @@ impl Database {
         Ok(tags)
     }
 
-    pub async fn update_todo_tag(&self, req: UpdateTodoTagRequest) -> Result<TodoTag, String> {
+    async fn update_todo_tag(&self, req: UpdateTodoTagRequest) -> AppResult<TodoTag> {
         let mut updates = Vec::new();
         let mut color_info = None;
 
@@ -1734,7 +1475,7 @@ impl Database {
         }
 
         if updates.is_empty() {
-            return Err("No updates provided".to_string());
+            return Err(AppError::validation("No updates provided"));
         }
 
         let sql = format!("UPDATE todo_tags SET {} WHERE id = ?", updates.join(", "));
@@ -1753,22 +1494,28 @@ impl Database {
 
         query = query.bind(&req.id);
 
+        let mut action = self.begin_action("Edit tag").await?;
+        action.track("todo_tags", "id", &req.id).await?;
         query
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to update todo tag: {}", e))?;
+            .context("Failed to update todo tag")?;
+        action.commit().await?;
 
-        self.get_todo_tag(&req.id).await
+        let tag = self.get_todo_tag(&req.id).await?;
+        self.notify(ChangeEvent::updated(ChangeEntity::TodoTag, &tag.id, &tag));
+        Ok(tag)
     }
 
-    pub async fn get_todo_tag(&self, tag_id: &str) -> Result<TodoTag, String> {
+    async fn get_todo_tag(&self, tag_id: &str) -> AppResult<TodoTag> {
         let row = sqlx::query(
             "SELECT id, name, color, bg_color, color_id, created_at FROM todo_tags WHERE id = ?",
         )
         .bind(tag_id)
-        .fetch_one(&self.pool)
+        .fetch_optional(&self.pool())
         .await
-        .map_err(|e| format!("Failed to get todo tag: {}", e))?;
+        .context("Failed to get todo tag")?
+        .ok_or_else(|| AppError::not_found("todo_tag", tag_id))?;
 
         Ok(TodoTag {
             id: row.get("id"),
@@ -1780,18 +1527,168 @@ impl Database {
         })
     }
 
-    pub async fn delete_todo_tag(&self, tag_id: String) -> Result<(), String> {
+    async fn delete_todo_tag(&self, tag_id: String) -> AppResult<()> {
+        let mut action = self.begin_action("Delete tag").await?;
+        action.track("todo_tags", "id", &tag_id).await?;
+        action
+            .track("todo_tag_relations", "tag_id", &tag_id)
+            .await?;
         // Tag relations will be deleted by CASCADE
         sqlx::query("DELETE FROM todo_tags WHERE id = ?")
             .bind(&tag_id)
-            .execute(&self.pool)
+            .execute(action.conn())
             .await
-            .map_err(|e| format!("Failed to delete todo tag: {}", e))?;
+            .context("Failed to delete todo tag")?;
+        action.commit().await?;
+
+        self.notify(ChangeEvent::deleted(ChangeEntity::TodoTag, vec![tag_id]));
+        Ok(())
+    }
+}
+
+impl Database {
+    /// 按导出范围获取代码片段
+    pub async fn get_snippets_in_scope(&self, scope: ExportScope) -> AppResult<Vec<CodeSnippet>> {
+        match scope {
+            ExportScope::All => self.get_all_snippets().await,
+            ExportScope::Snippet { id } => self
+                .get_snippet(&id)
+                .await?
+                .map(|snippet| vec![snippet])
+                .ok_or_else(|| AppError::not_found("snippet", id)),
+            ExportScope::Folder { id } => self.get_snippets_by_folder(&id).await,
+            ExportScope::Tag { tag } => self.get_snippets_by_tag(&tag).await,
+            ExportScope::Search { query } => self.search_snippets(query).await,
+        }
+    }
+
+    // ============================================================================
+    // Snippet Import
+    // ============================================================================
+
+    /// 按冲突策略导入一批完整的代码片段记录（保留原ID和时间戳）
+    ///
+    /// 所有记录在同一个事务中写入，任何一条写入失败时整个导入回滚，
+    /// 不会留下导入了一半的资料库；整个导入记为一条可撤销的历史。
+    /// 返回每一项的结果，顺序与输入一致
+    pub async fn import_snippets(
+        &self,
+        snippets: Vec<(usize, CodeSnippet)>,
+        strategy: ImportConflictStrategy,
+        dry_run: bool,
+    ) -> AppResult<Vec<ImportItemResult>> {
+        let mut action = self.begin_action("Import snippets").await?;
+
+        let mut results = Vec::new();
+        let mut events = Vec::new();
+        for (index, snippet) in snippets {
+            action.track("snippets", "id", &snippet.id).await?;
+            let (result, event) =
+                import_snippet_record(action.conn(), index, snippet, strategy, dry_run).await?;
+            if let (ImportAction::Create, Some(id)) = (result.action, &result.id) {
+                action.created("snippets", "id", id);
+            }
+            results.push(result);
+            events.extend(event);
+        }
+
+        action.commit().await?;
+        for event in events {
+            self.notify(event);
+        }
+        Ok(results)
+    }
+
+    /// 导入一批外部格式的代码片段草稿，draft.folder_path 相对于 folder_id
+    ///
+    /// 与目标文件夹中标题、语言和代码完全相同的片段视为重复并跳过。
+    /// 与 import_snippets 相同，所有写入在一个事务中完成并记为一条历史
+    pub async fn import_drafts(
+        &self,
+        drafts: Vec<(usize, SnippetDraft)>,
+        folder_id: Option<String>,
+        dry_run: bool,
+    ) -> AppResult<Vec<ImportItemResult>> {
+        let mut action = self.begin_action("Import snippets").await?;
+
+        let mut results = Vec::new();
+        let mut events = Vec::new();
+        for (index, draft) in drafts {
+            let folder_path = draft.folder_path.join("/");
+            let (target, new_folder) = ensure_folder_path(
+                &mut action,
+                folder_id.clone(),
+                &draft.folder_path,
+                dry_run,
+                &mut events,
+            )
+            .await?;
+
+            if dry_run && new_folder {
+                // 目标文件夹尚不存在，不可能有重复项
+                results.push(ImportItemResult {
+                    index,
+                    id: None,
+                    title: Some(draft.title),
+                    action: ImportAction::Create,
+                    message: Some(format!("Folder {} will be created", folder_path)),
+                });
+                continue;
+            }
+
+            let (result, event) =
+                import_draft_record(action.conn(), index, draft, target, dry_run).await?;
+            if let (ImportAction::Create, Some(id)) = (result.action, &result.id) {
+                action.created("snippets", "id", id);
+            }
+            results.push(result);
+            events.extend(event);
+        }
+
+        action.commit().await?;
+        for event in events {
+            self.notify(event);
+        }
+        Ok(results)
+    }
+
+    // ============================================================================
+    // Todo Helpers
+    // ============================================================================
+
+    /// 用一次查询加载一组 TODO 的标签
+    async fn load_todo_tags(&self, todos: &mut [Todo]) -> AppResult<()> {
+        if todos.is_empty() {
+            return Ok(());
+        }
+
+        // ID 以 JSON 数组传入，不受 SQLite 参数个数的限制
+        let ids: Vec<&str> = todos.iter().map(|todo| todo.id.as_str()).collect();
+        let ids_json = serde_json::to_string(&ids).context("Failed to serialize todo ids")?;
+        let rows = sqlx::query(
+            "SELECT ttr.todo_id, ttr.tag_id FROM todo_tag_relations ttr
+             JOIN todo_tags tt ON tt.id = ttr.tag_id
+             WHERE ttr.todo_id IN (SELECT value FROM json_each(?))
+             ORDER BY ttr.rowid",
+        )
+        .bind(ids_json)
+        .fetch_all(&self.pool())
+        .await
+        .context("Failed to get todo tags")?;
 
+        let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+        for row in rows {
+            tags.entry(row.get("todo_id"))
+                .or_default()
+                .push(row.get("tag_id"));
+        }
+        for todo in todos {
+            todo.tags = tags.remove(&todo.id).unwrap_or_default();
+        }
         Ok(())
     }
 
-    fn get_tag_color_info(&self, color_id: &str) -> Result<TagColorInfo, String> {
+    fn get_tag_color_info(&self, color_id: &str) -> AppResult<TagColorInfo> {
         let colors = [
             ("red", "#ef4444", "#fef2f2"),
             ("orange", "#f97316", "#fff7ed"),
@@ -1822,11 +1719,1847 @@ impl Database {
             }
         }
 
-        Err(format!("Unknown color ID: {}", color_id))
+        Err(AppError::validation(format!(
+            "Unknown color ID: {}",
+            color_id
+        )))
     }
-}
 
-struct TagColorInfo {
-    color: String,
-    bg_color: String,
+    // ============================================================================
+    // Todo Import & Export
+    // ============================================================================
+
+    /// 导出用的 TODO 平铺列表，子任务通过 parent_id 关联而不嵌套
+    pub async fn get_todos_for_export(&self, include_archived: bool) -> AppResult<Vec<Todo>> {
+        let sql = format!(
+            "SELECT {} FROM todos WHERE archived = false OR ? ORDER BY created_at ASC",
+            TODO_COLUMNS
+        );
+        let rows = sqlx::query(&sql)
+            .bind(include_archived)
+            .fetch_all(&self.pool())
+            .await
+            .context("Failed to get todos")?;
+
+        let mut todos: Vec<Todo> = rows.iter().map(todo_from_row).collect();
+        self.load_todo_tags(&mut todos).await?;
+        Ok(todos)
+    }
+
+    pub async fn todo_exists(&self, todo_id: &str) -> AppResult<bool> {
+        let row = sqlx::query("SELECT COUNT(*) as count FROM todos WHERE id = ?")
+            .bind(todo_id)
+            .fetch_one(&self.pool())
+            .await
+            .context("Failed to check todo")?;
+        Ok(row.get::<i64, _>("count") > 0)
+    }
+
+    /// 导入一批 TODO 草稿，父任务应排在子任务之前（见 formats::parents_first）
+    ///
+    /// 已存在同ID的 TODO 时，仅当导入项的 updated_at 更新（或缺失）时才覆盖；
+    /// known_ids 为同一批导入中的所有ID，父任务不在其中且不存在时去掉父任务关联。
+    /// 所有写入在一个事务中完成，任何一条写入失败时整个导入回滚；整个导入记为一条历史
+    pub async fn import_todos(
+        &self,
+        drafts: Vec<(usize, TodoDraft)>,
+        known_ids: &HashSet<String>,
+        dry_run: bool,
+    ) -> AppResult<Vec<ImportItemResult>> {
+        let mut action = self.begin_action("Import todos").await?;
+
+        let mut results = Vec::new();
+        let mut events = Vec::new();
+        let mut written = Vec::new();
+        for (index, draft) in drafts {
+            let result = self
+                .import_todo_record(&mut action, index, draft, known_ids, dry_run, &mut events)
+                .await?;
+            if !dry_run {
+                if let (ImportAction::Create | ImportAction::Update, Some(id)) =
+                    (result.action, &result.id)
+                {
+                    written.push((id.clone(), result.action));
+                }
+            }
+            results.push(result);
+        }
+        action.commit().await?;
+
+        for event in events {
+            self.notify(event);
+        }
+        for (id, import_action) in written {
+            let todo = self.get_todo(&id).await?;
+            let action_kind = if import_action == ImportAction::Create {
+                ChangeAction::Created
+            } else {
+                ChangeAction::Updated
+            };
+            self.notify(ChangeEvent::with_payload(
+                ChangeEntity::Todo,
+                action_kind,
+                vec![id],
+                &todo,
+            ));
+        }
+        Ok(results)
+    }
+
+    /// 在导入事务中创建或更新一个 TODO，新建的标签事件追加到 events
+    async fn import_todo_record(
+        &self,
+        action: &mut UndoAction,
+        index: usize,
+        mut draft: TodoDraft,
+        known_ids: &HashSet<String>,
+        dry_run: bool,
+        events: &mut Vec<ChangeEvent>,
+    ) -> AppResult<ImportItemResult> {
+        let now = chrono::Utc::now().timestamp_millis();
+        let id = draft
+            .id
+            .clone()
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let mut notes = std::mem::take(&mut draft.notes);
+
+        let existing =
+            sqlx::query("SELECT updated_at, archived_at, completed_at FROM todos WHERE id = ?")
+                .bind(&id)
+                .fetch_optional(action.conn())
+                .await
+                .context("Failed to check todo")?;
+
+        let import_action = match &existing {
+            None => ImportAction::Create,
+            Some(row) => {
+                let updated_at: i64 = row.get("updated_at");
+                if draft
+                    .updated_at
+                    .is_some_and(|incoming| incoming <= updated_at)
+                {
+                    notes.push("Existing todo is newer or unchanged".to_string());
+                    ImportAction::Skip
+                } else {
+                    ImportAction::Update
+                }
+            }
+        };
+
+        let result = |import_action: ImportAction, notes: Vec<String>| ImportItemResult {
+            index,
+            id: Some(id.clone()),
+            title: Some(draft.title.clone()),
+            action: import_action,
+            message: if notes.is_empty() {
+                None
+            } else {
+                Some(notes.join("; "))
+            },
+        };
+        if import_action == ImportAction::Skip {
+            return Ok(result(import_action, notes));
+        }
+
+        if let Some(parent_id) = draft.parent_id.clone() {
+            if !known_ids.contains(&parent_id)
+                && !row_exists(action.conn(), "todos", &parent_id).await?
+            {
+                draft.parent_id = None;
+                notes.push(format!(
+                    "Parent {} not found, imported as top-level",
+                    parent_id
+                ));
+            }
+        }
+
+        let (tag_ids, new_tags) = self
+            .resolve_todo_tag_names(action, &draft.tag_names, dry_run, events)
+            .await?;
+        if !new_tags.is_empty() {
+            notes.push(format!("Tags created: {}", new_tags.join(", ")));
+        }
+
+        let project_id = match draft.project_name.as_deref() {
+            Some(name) => {
+                let project_id = find_project_by_name(action.conn(), name).await?;
+                if project_id.is_none() {
+                    notes.push(format!("Project {} not found", name));
+                }
+                project_id
+            }
+            None => None,
+        };
+
+        if dry_run {
+            return Ok(result(import_action, notes));
+        }
+
+        if import_action == ImportAction::Create {
+            action.created("todos", "id", &id);
+            action.created("todo_tag_relations", "todo_id", &id);
+        } else {
+            action.track("todos", "id", &id).await?;
+            action.track("todo_tag_relations", "todo_id", &id).await?;
+        }
+
+        let dependencies_json = serde_json::to_string(&draft.dependencies)
+            .context("Failed to serialize dependencies")?;
+        let updated_at = draft.updated_at.unwrap_or(now);
+        let archived_at = match &existing {
+            Some(row) if draft.archived => row.get::<Option<i64>, _>("archived_at").or(Some(now)),
+            _ if draft.archived => Some(now),
+            _ => None,
+        };
+        let done = draft.completed || draft.status == "completed";
+        let completed_at = match &existing {
+            Some(row) if done => row.get::<Option<i64>, _>("completed_at").or(Some(now)),
+            _ if done => Some(now),
+            _ => None,
+        };
+
+        let sql = if import_action == ImportAction::Create {
+            "INSERT INTO todos (title, description, status, priority, due_date, progress, parent_id, project_id, recurring_config, dependencies, completed, archived, archived_at, completed_at, updated_at, created_at, id) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        } else {
+            "UPDATE todos SET title = ?, description = ?, status = ?, priority = ?, due_date = ?, progress = ?, parent_id = ?, project_id = COALESCE(?, project_id), recurring_config = ?, dependencies = ?, completed = ?, archived = ?, archived_at = ?, completed_at = ?, updated_at = ? WHERE id = ?"
+        };
+        let mut query = sqlx::query(sql)
+            .bind(&draft.title)
+            .bind(&draft.description)
+            .bind(&draft.status)
+            .bind(&draft.priority)
+            .bind(&draft.due_date)
+            .bind(draft.progress)
+            .bind(&draft.parent_id)
+            .bind(&project_id)
+            .bind(&draft.recurring_config)
+            .bind(&dependencies_json)
+            .bind(draft.completed)
+            .bind(draft.archived)
+            .bind(archived_at)
+            .bind(completed_at)
+            .bind(updated_at);
+        if import_action == ImportAction::Create {
+            query = query.bind(draft.created_at.unwrap_or(updated_at));
+        }
+        query
+            .bind(&id)
+            .execute(action.conn())
+            .await
+            .context("Failed to import todo")?;
+
+        // 源数据没有标签时保留现有标签，避免不支持分类的日历工具清空标签
+        if !tag_ids.is_empty() {
+            sqlx::query("DELETE FROM todo_tag_relations WHERE todo_id = ?")
+                .bind(&id)
+                .execute(action.conn())
+                .await
+                .context("Failed to remove tag relations")?;
+            for tag_id in &tag_ids {
+                sqlx::query(
+                    "INSERT OR IGNORE INTO todo_tag_relations (todo_id, tag_id) VALUES (?, ?)",
+                )
+                .bind(&id)
+                .bind(tag_id)
+                .execute(action.conn())
+                .await
+                .context("Failed to add tag relation")?;
+            }
+        }
+
+        Ok(result(import_action, notes))
+    }
+
+    /// 按名称（比较方式见 name_key）查找标签ID，不存在的标签以灰色创建
+    ///
+    /// 返回 (标签ID, 新建的标签名称)；dry_run 时不创建，新标签不返回ID
+    async fn resolve_todo_tag_names(
+        &self,
+        action: &mut UndoAction,
+        names: &[String],
+        dry_run: bool,
+        events: &mut Vec<ChangeEvent>,
+    ) -> AppResult<(Vec<String>, Vec<String>)> {
+        let existing: Vec<(String, String)> =
+            sqlx::query_as("SELECT id, name FROM todo_tags ORDER BY created_at ASC")
+                .fetch_all(action.conn())
+                .await
+                .context("Failed to get todo tags")?;
+        let mut ids = Vec::new();
+        let mut created: Vec<String> = Vec::new();
+
+        for name in names {
+            if created.iter().any(|c| name_key(c) == name_key(name)) {
+                continue;
+            }
+            match existing
+                .iter()
+                .find(|(_, tag_name)| name_key(tag_name) == name_key(name))
+            {
+                Some((id, _)) => ids.push(id.clone()),
+                None => {
+                    created.push(name.clone());
+                    if !dry_run {
+                        let tag = insert_todo_tag(
+                            action.conn(),
+                            CreateTodoTagRequest {
+                                name: name.clone(),
+                                color_id: "gray".to_string(),
+                            },
+                            self.get_tag_color_info("gray")?,
+                        )
+                        .await?;
+                        action.created("todo_tags", "id", &tag.id);
+                        events.push(ChangeEvent::created(ChangeEntity::TodoTag, &tag.id, &tag));
+                        ids.push(tag.id);
+                    }
+                }
+            }
+        }
+
+        Ok((ids, created))
+    }
+
+    // ============================================================================
+    // Activity Log
+    // ============================================================================
+
+    /// 按实体、动作和时间范围查询活动记录，最新的在前
+    pub async fn get_activity(&self, query: ActivityQuery) -> AppResult<Vec<ActivityEntry>> {
+        let mut conditions = Vec::new();
+        if query.entity_type.is_some() {
+            conditions.push("entity_type = ?");
+        }
+        if query.entity_id.is_some() {
+            conditions.push("entity_id = ?");
+        }
+        if query.action.is_some() {
+            conditions.push("action = ?");
+        }
+        if query.from.is_some() {
+            conditions.push("created_at >= ?");
+        }
+        if query.to.is_some() {
+            conditions.push("created_at <= ?");
+        }
+
+        let mut sql = String::from(
+            "SELECT id, entity_type, entity_id, action, changes, created_at FROM activity_log",
+        );
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY id DESC LIMIT ?");
+
+        // 参数顺序与上面的条件一致
+        let mut statement = sqlx::query(&sql);
+        for value in [&query.entity_type, &query.entity_id, &query.action]
+            .into_iter()
+            .flatten()
+        {
+            statement = statement.bind(value);
+        }
+        for value in [query.from, query.to].into_iter().flatten() {
+            statement = statement.bind(value);
+        }
+        let rows = statement
+            .bind(query.limit.unwrap_or(DEFAULT_ACTIVITY_LIMIT))
+            .fetch_all(&self.pool())
+            .await
+            .context("Failed to query activity log")?;
+
+        Ok(rows
+            .iter()
+            .map(|row| ActivityEntry {
+                id: row.get("id"),
+                entity_type: row.get("entity_type"),
+                entity_id: row.get("entity_id"),
+                action: row.get("action"),
+                changes: serde_json::from_str(row.get("changes")).unwrap_or_default(),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+
+    /// 删除 before（毫秒时间戳）之前的活动记录，before 为 None 时全部删除
+    pub async fn clear_activity(&self, before: Option<i64>) -> AppResult<u64> {
+        let result = sqlx::query("DELETE FROM activity_log WHERE ? IS NULL OR created_at < ?")
+            .bind(before)
+            .bind(before)
+            .execute(&self.pool())
+            .await
+            .context("Failed to clear activity log")?;
+        Ok(result.rows_affected())
+    }
+
+    // ============================================================================
+    // Undo Journal
+    // ============================================================================
+
+    /// 开始一个可撤销的操作，修改与撤销历史在同一事务中提交
+    ///
+    /// 事务开始时即取得写锁：track 会先读后写，延迟事务在读取后升级写锁时，
+    /// 若其他连接已提交写入会返回 SQLITE_BUSY_SNAPSHOT，busy_timeout 不会重试
+    async fn begin_action(&self, label: &str) -> AppResult<UndoAction> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .context("Failed to start transaction")?;
+        // sqlx 0.7 不支持 BEGIN IMMEDIATE；事务中第一条语句为写语句时同样立即取得写锁，
+        // 其他连接持有写锁时按 busy_timeout 等待
+        sqlx::query("UPDATE undo_journal SET undone = undone WHERE 0")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to acquire write lock")?;
+        Ok(UndoAction::new(label, tx))
+    }
+
+    /// 读取 table 中 column = value 的所有行
+    pub async fn select_rows(
+        conn: &mut SqliteConnection,
+        table: &str,
+        column: &str,
+        value: &str,
+    ) -> AppResult<Vec<TableRow>> {
+        let rows = sqlx::query(&format!("SELECT * FROM {} WHERE {} = ?", table, column))
+            .bind(value)
+            .fetch_all(&mut *conn)
+            .await
+            .with_context(|| format!("Failed to read {}", table))?;
+        Ok(rows.iter().map(table_row_from_sqlite).collect())
+    }
+
+    /// TODO 及其所有层级子任务的 id，按层级由浅到深排列
+    pub async fn todo_subtree(
+        conn: &mut SqliteConnection,
+        todo_id: &str,
+    ) -> AppResult<Vec<String>> {
+        sqlx::query_scalar(&format!(
+            "{} SELECT id FROM subtree WHERE id IN (SELECT id FROM todos)",
+            TODO_SUBTREE
+        ))
+        .bind(todo_id)
+        .fetch_all(&mut *conn)
+        .await
+        .context("Failed to get subtasks")
+    }
+
+    /// 按主键读取一行
+    pub async fn select_row(
+        conn: &mut SqliteConnection,
+        table: &str,
+        key: &TableRow,
+    ) -> AppResult<Option<TableRow>> {
+        select_by_key(conn, table, key).await
+    }
+
+    /// 写入一条历史，并丢弃可重做的历史和超出 HISTORY_LIMIT 的最早历史
+    pub async fn push_undo_entry(
+        conn: &mut SqliteConnection,
+        label: &str,
+        changes: &[RowChange],
+    ) -> AppResult<()> {
+        let changes_json =
+            serde_json::to_string(changes).context("Failed to serialize undo entry")?;
+
+        sqlx::query("DELETE FROM undo_journal WHERE undone = 1")
+            .execute(&mut *conn)
+            .await
+            .context("Failed to discard redo history")?;
+        sqlx::query("INSERT INTO undo_journal (label, changes, created_at) VALUES (?, ?, ?)")
+            .bind(label)
+            .bind(&changes_json)
+            .bind(chrono::Utc::now().timestamp_millis())
+            .execute(&mut *conn)
+            .await
+            .context("Failed to write undo entry")?;
+        sqlx::query(
+            "DELETE FROM undo_journal WHERE id NOT IN (SELECT id FROM undo_journal ORDER BY id DESC LIMIT ?)",
+        )
+        .bind(HISTORY_LIMIT)
+        .execute(&mut *conn)
+        .await
+        .context("Failed to trim undo history")?;
+        Ok(())
+    }
+
+    /// 下一个可撤销和可重做的操作
+    pub async fn undo_state(&self) -> AppResult<UndoState> {
+        Ok(UndoState {
+            undo: self.next_undo_entry(false).await?.map(|(entry, _)| entry),
+            redo: self.next_undo_entry(true).await?.map(|(entry, _)| entry),
+        })
+    }
+
+    /// 撤销最近的操作，没有可撤销的操作时返回 None
+    pub async fn undo(&self) -> AppResult<Option<UndoEntry>> {
+        self.replay_undo_entry(true).await
+    }
+
+    /// 重做最近撤销的操作，没有可重做的操作时返回 None
+    pub async fn redo(&self) -> AppResult<Option<UndoEntry>> {
+        self.replay_undo_entry(false).await
+    }
+
+    /// undone 为 false 时返回最新的未撤销操作，为 true 时返回最早的已撤销操作
+    async fn next_undo_entry(
+        &self,
+        undone: bool,
+    ) -> AppResult<Option<(UndoEntry, Vec<RowChange>)>> {
+        let sql = if undone {
+            "SELECT id, label, changes, created_at FROM undo_journal WHERE undone = 1 ORDER BY id ASC LIMIT 1"
+        } else {
+            "SELECT id, label, changes, created_at FROM undo_journal WHERE undone = 0 ORDER BY id DESC LIMIT 1"
+        };
+        let Some(row) = sqlx::query(sql)
+            .fetch_optional(&self.pool())
+            .await
+            .context("Failed to read undo history")?
+        else {
+            return Ok(None);
+        };
+
+        let changes: Vec<RowChange> = serde_json::from_str(row.get("changes"))
+            .map_err(|e| AppError::InvalidData(format!("Corrupted undo entry: {}", e)))?;
+        let entry = UndoEntry {
+            id: row.get("id"),
+            label: row.get("label"),
+            created_at: row.get("created_at"),
+        };
+        Ok(Some((entry, changes)))
+    }
+
+    /// 把一条历史涉及的行写回操作前（undo）或操作后的内容
+    ///
+    /// 任何一行与记录不一致时拒绝执行，整个操作在一个事务中完成
+    async fn replay_undo_entry(&self, undo: bool) -> AppResult<Option<UndoEntry>> {
+        let Some((entry, changes)) = self.next_undo_entry(!undo).await? else {
+            return Ok(None);
+        };
+        let verb = if undo { "undone" } else { "redone" };
+
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .context("Failed to start undo transaction")?;
+        // 行按记录顺序的逆序写回，父记录可能晚于子记录插入
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to defer foreign key checks")?;
+
+        for change in &changes {
+            let expected = if undo { &change.after } else { &change.before };
+            let current = select_by_key(&mut tx, &change.table, &change.key).await?;
+            if current != *expected {
+                return Err(AppError::Conflict {
+                    details: format!(
+                        "\"{}\" can't be {} because the data has changed since",
+                        entry.label, verb
+                    ),
+                });
+            }
+        }
+
+        let ordered: Vec<&RowChange> = if undo {
+            changes.iter().rev().collect()
+        } else {
+            changes.iter().collect()
+        };
+        for change in ordered {
+            let target = if undo { &change.before } else { &change.after };
+            write_row(&mut tx, &change.table, &change.key, target.as_ref())
+                .await
+                .with_context(|| format!("Failed to restore a row of {}", change.table))?;
+        }
+
+        sqlx::query("UPDATE undo_journal SET undone = ? WHERE id = ?")
+            .bind(undo)
+            .bind(entry.id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update undo history")?;
+        tx.commit()
+            .await
+            .with_context(|| format!("Failed to commit {} operation", verb))?;
+
+        self.notify(ChangeEvent::reloaded());
+        Ok(Some(entry))
+    }
+
+    // ============================================================================
+    // Maintenance
+    // ============================================================================
+
+    /// 运行 integrity_check 与 foreign_key_check，并统计孤立的引用
+    ///
+    /// repair 为 true 时在一个事务中修复孤立记录（删除或清空引用列）；
+    /// integrity_check 报告的文件损坏无法在此修复，需要从备份恢复。
+    pub async fn check_integrity(&self, repair: bool) -> AppResult<IntegrityReport> {
+        let pool = self.pool();
+
+        let integrity: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check")
+            .fetch_all(&pool)
+            .await
+            .context("Failed to run integrity check")?;
+        let integrity_errors = integrity.into_iter().filter(|line| line != "ok").collect();
+
+        let foreign_key_violations = sqlx::query("PRAGMA foreign_key_check")
+            .fetch_all(&pool)
+            .await
+            .context("Failed to run foreign key check")?
+            .iter()
+            .map(|row| ForeignKeyViolation {
+                table: row.get(0),
+                row_id: row.get(1),
+                parent: row.get(2),
+            })
+            .collect();
+
+        let mut tx = pool
+            .begin()
+            .await
+            .context("Failed to start integrity repair")?;
+        let orphans = find_orphans(&mut tx, repair).await?;
+        tx.commit()
+            .await
+            .context("Failed to commit integrity repair")?;
+        if repair && !orphans.is_empty() {
+            self.notify(ChangeEvent::reloaded());
+        }
+
+        Ok(IntegrityReport {
+            integrity_errors,
+            foreign_key_violations,
+            repaired: repair && !orphans.is_empty(),
+            orphans,
+        })
+    }
+
+    /// 统计各表（含活动日志与撤销历史）的行数与占用空间，以及全文索引的大小
+    ///
+    /// 占用空间来自 dbstat 虚拟表（包含表上的索引）；SQLite 未启用 dbstat 时为 None
+    pub async fn database_stats(&self) -> AppResult<DatabaseStats> {
+        let pool = self.pool();
+
+        let page_size: i64 = sqlx::query_scalar("PRAGMA page_size")
+            .fetch_one(&pool)
+            .await
+            .context("Failed to read page size")?;
+        let page_count: i64 = sqlx::query_scalar("PRAGMA page_count")
+            .fetch_one(&pool)
+            .await
+            .context("Failed to read page count")?;
+        let freelist_count: i64 = sqlx::query_scalar("PRAGMA freelist_count")
+            .fetch_one(&pool)
+            .await
+            .context("Failed to read freelist count")?;
+
+        let sizes: Option<HashMap<String, i64>> = sqlx::query_as(
+            "SELECT m.tbl_name, SUM(s.pgsize) FROM dbstat s
+             JOIN sqlite_master m ON m.name = s.name
+             GROUP BY m.tbl_name",
+        )
+        .fetch_all(&pool)
+        .await
+        .ok()
+        .map(|rows| rows.into_iter().collect());
+
+        let mut tables = Vec::new();
+        for table in BACKUP_TABLES
+            .iter()
+            .chain(HISTORY_TABLES)
+            .chain([&FTS_TABLE])
+        {
+            let row_count: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {}", table))
+                .fetch_one(&pool)
+                .await
+                .with_context(|| format!("Failed to count rows of {}", table))?;
+            tables.push(TableStats {
+                name: table.to_string(),
+                row_count,
+                size_bytes: sizes
+                    .as_ref()
+                    .map(|sizes| sizes.get(*table).copied().unwrap_or(0)),
+            });
+        }
+
+        // FTS5 的索引数据保存在 snippets_fts_data 等影子表中
+        let fts_index_bytes = sizes.as_ref().map(|sizes| {
+            sizes
+                .iter()
+                .filter(|(name, _)| name.starts_with(&format!("{}_", FTS_TABLE)))
+                .map(|(_, size)| size)
+                .sum()
+        });
+
+        Ok(DatabaseStats {
+            size_bytes: page_count * page_size,
+            free_bytes: freelist_count * page_size,
+            tables,
+            fts_index_bytes,
+        })
+    }
+
+    /// 重建数据库文件并回收空闲页
+    pub async fn vacuum(&self) -> AppResult<()> {
+        sqlx::query("VACUUM")
+            .execute(&self.pool())
+            .await
+            .context("Failed to vacuum database")?;
+        Ok(())
+    }
+
+    /// 更新查询优化器使用的统计信息
+    pub async fn analyze(&self) -> AppResult<()> {
+        sqlx::query("ANALYZE")
+            .execute(&self.pool())
+            .await
+            .context("Failed to analyze database")?;
+        Ok(())
+    }
+
+    /// 合并全文索引的 b-tree 段，提升搜索速度
+    pub async fn optimize_fts(&self) -> AppResult<()> {
+        sqlx::query("INSERT INTO snippets_fts(snippets_fts) VALUES('optimize')")
+            .execute(&self.pool())
+            .await
+            .context("Failed to optimize search index")?;
+        Ok(())
+    }
+
+    /// 根据 snippets 表重新生成全文索引的全部内容
+    pub async fn rebuild_fts(&self) -> AppResult<()> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .context("Failed to start search index rebuild")?;
+        sqlx::query("DELETE FROM snippets_fts")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear search index")?;
+        sqlx::query(
+            "INSERT INTO snippets_fts(id, title, description, code, tags)
+             SELECT id, title, description, code, tags FROM snippets",
+        )
+        .execute(&mut *tx)
+        .await
+        .context("Failed to fill search index")?;
+        sqlx::query("INSERT INTO snippets_fts(snippets_fts) VALUES('rebuild')")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to rebuild search index")?;
+        tx.commit()
+            .await
+            .context("Failed to commit search index rebuild")?;
+        Ok(())
+    }
+
+    /// 比较全文索引与 snippets 表，repair 为 true 时重新索引有偏差的代码片段
+    ///
+    /// 偏差来自触发器之外的写入（如旧版本导入或手工修改数据库文件）。
+    pub async fn check_fts_sync(&self, repair: bool) -> AppResult<FtsSyncReport> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .context("Failed to start search index check")?;
+
+        let missing: Vec<String> = sqlx::query_scalar(
+            "SELECT id FROM snippets WHERE id NOT IN (SELECT id FROM snippets_fts)",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to find unindexed snippets")?;
+        let stale: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM snippets_fts WHERE id NOT IN (SELECT id FROM snippets)",
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to find stale index rows")?;
+        // 内容不一致或同一 ID 有多行索引
+        let outdated: Vec<String> = sqlx::query_scalar(
+            "SELECT s.id FROM snippets s JOIN snippets_fts f ON f.id = s.id
+             GROUP BY s.id
+             HAVING COUNT(*) > 1
+                OR MAX(f.title IS NOT s.title OR f.description IS NOT s.description
+                       OR f.code IS NOT s.code OR f.tags IS NOT s.tags)",
+        )
+        .fetch_all(&mut *tx)
+        .await
+        .context("Failed to compare index rows")?;
+
+        let drifted = !missing.is_empty() || stale > 0 || !outdated.is_empty();
+        if repair && drifted {
+            sqlx::query("DELETE FROM snippets_fts WHERE id NOT IN (SELECT id FROM snippets)")
+                .execute(&mut *tx)
+                .await
+                .context("Failed to remove stale index rows")?;
+            for id in missing.iter().chain(&outdated) {
+                sqlx::query("DELETE FROM snippets_fts WHERE id = ?")
+                    .bind(id)
+                    .execute(&mut *tx)
+                    .await
+                    .context("Failed to remove outdated index rows")?;
+                sqlx::query(
+                    "INSERT INTO snippets_fts(id, title, description, code, tags)
+                     SELECT id, title, description, code, tags FROM snippets WHERE id = ?",
+                )
+                .bind(id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to reindex snippet")?;
+            }
+        }
+        tx.commit()
+            .await
+            .context("Failed to commit search index check")?;
+
+        Ok(FtsSyncReport {
+            missing: missing.len() as i64,
+            stale,
+            outdated: outdated.len() as i64,
+            repaired: repair && drifted,
+        })
+    }
+
+    // ============================================================================
+    // Backup & Restore
+    // ============================================================================
+
+    /// 导出备份归档包含的所有表
+    pub async fn dump_tables(&self) -> AppResult<Vec<(String, Vec<TableRow>)>> {
+        let mut tables = Vec::new();
+        for table in BACKUP_TABLES {
+            let rows = sqlx::query(&format!("SELECT * FROM {}", table))
+                .fetch_all(&self.pool())
+                .await
+                .with_context(|| format!("Failed to dump table {}", table))?;
+
+            tables.push((
+                table.to_string(),
+                rows.iter().map(table_row_from_sqlite).collect(),
+            ));
+        }
+        Ok(tables)
+    }
+
+    /// 用归档数据替换对应的表
+    ///
+    /// 仅替换归档中存在的表；在单个事务中执行，任何错误都会回滚。
+    /// 归档中多余的列会被忽略，缺少的列使用表的默认值。
+    /// 恢复期间暂停逐行的活动记录，完成后写入一条 library/restored 汇总记录。
+    pub async fn restore_tables(&self, tables: &TableSet) -> AppResult<HashMap<String, usize>> {
+        let mut tx = self
+            .pool()
+            .begin()
+            .await
+            .context("Failed to start restore transaction")?;
+        // 外键检查推迟到提交时，表可以按任意顺序清空和插入
+        sqlx::query("PRAGMA defer_foreign_keys = ON")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to defer foreign key checks")?;
+        sqlx::query("INSERT INTO activity_pause (id) VALUES (1)")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to pause activity log")?;
+
+        // 先按依赖的逆序清空，再按顺序插入
+        for table in BACKUP_TABLES.iter().rev() {
+            if tables.contains_key(*table) {
+                sqlx::query(&format!("DELETE FROM {}", table))
+                    .execute(&mut *tx)
+                    .await
+                    .with_context(|| format!("Failed to clear table {}", table))?;
+            }
+        }
+
+        let mut restored = HashMap::new();
+        for table in BACKUP_TABLES {
+            let Some(rows) = tables.get(*table) else {
+                continue;
+            };
+
+            let column_rows = sqlx::query(&format!("PRAGMA table_info({})", table))
+                .fetch_all(&mut *tx)
+                .await
+                .with_context(|| format!("Failed to read columns of {}", table))?;
+            let table_columns: Vec<String> =
+                column_rows.iter().map(|row| row.get("name")).collect();
+
+            for row in rows {
+                let columns: Vec<&String> = table_columns
+                    .iter()
+                    .filter(|column| row.contains_key(*column))
+                    .collect();
+                if columns.is_empty() {
+                    continue;
+                }
+
+                let sql = format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    table,
+                    columns
+                        .iter()
+                        .map(|c| c.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    vec!["?"; columns.len()].join(", ")
+                );
+
+                let mut query = sqlx::query(&sql);
+                for column in &columns {
+                    query = bind_json_value(query, &row[*column]);
+                }
+
+                query
+                    .execute(&mut *tx)
+                    .await
+                    .with_context(|| format!("Failed to restore row into {}", table))?;
+            }
+
+            restored.insert(table.to_string(), rows.len());
+        }
+
+        // 备份可能来自未启用外键的旧版本，其中的孤立记录在提交前一并清理
+        find_orphans(&mut tx, true).await?;
+
+        sqlx::query("DELETE FROM activity_pause")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to resume activity log")?;
+        sqlx::query(
+            "INSERT INTO activity_log (entity_type, entity_id, action, changes, created_at)
+             VALUES ('library', '', 'restored', ?, ?)",
+        )
+        .bind(serde_json::to_string(&restored).context("Failed to serialize restore summary")?)
+        .bind(chrono::Utc::now().timestamp_millis())
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record restore activity")?;
+        // 撤销历史记录的是恢复前的数据，已无法套用
+        sqlx::query("DELETE FROM undo_journal")
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear undo history")?;
+        tx.commit().await.context("Failed to commit restore")?;
+        self.notify(ChangeEvent::reloaded());
+
+        Ok(restored)
+    }
+
+    /// 将当前数据库写入一个一致的快照文件（VACUUM INTO），可在应用运行时执行
+    pub async fn snapshot_into(&self, path: &Path) -> AppResult<()> {
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool())
+            .await
+            .context("Failed to write snapshot")?;
+        Ok(())
+    }
+
+    /// 以只读方式打开快照文件，读取其中存在的备份表
+    pub async fn read_snapshot_tables(path: &Path) -> AppResult<TableSet> {
+        let options = SqliteConnectOptions::new().filename(path).read_only(true);
+        let pool = SqlitePool::connect_with(options)
+            .await
+            .context("Failed to open snapshot")?;
+
+        let existing: Vec<String> =
+            sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+                .fetch_all(&pool)
+                .await
+                .context("Invalid snapshot")?;
+
+        let mut tables = TableSet::new();
+        for table in BACKUP_TABLES {
+            if !existing.iter().any(|name| name == table) {
+                continue;
+            }
+            let rows = sqlx::query(&format!("SELECT * FROM {}", table))
+                .fetch_all(&pool)
+                .await
+                .with_context(|| format!("Failed to read table {} from snapshot", table))?;
+            tables.insert(
+                table.to_string(),
+                rows.iter().map(table_row_from_sqlite).collect(),
+            );
+        }
+
+        pool.close().await;
+        Ok(tables)
+    }
+}
+
+/// 当前数据库结构版本，即最新迁移的版本号
+pub const SCHEMA_VERSION: i64 = crate::migrations::LATEST_VERSION;
+
+/// 默认资料库在应用数据目录中的文件名
+pub const DEFAULT_LIBRARY_FILE: &str = "snippets_hub.sqlite";
+
+/// 数据库被其他连接锁定时的最长等待时间，超时后才返回 SQLITE_BUSY
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 如何修复孤立记录
+#[derive(Clone, Copy)]
+enum OrphanFix {
+    /// 删除记录（关联表、评论、附件）
+    Delete,
+    /// 把引用列置为 NULL（如文件夹被删除后，代码片段回到根目录）
+    ClearReference,
+}
+
+/// 完整性检查涵盖的引用关系：(表, 列, 被引用的表, 修复方式)
+///
+/// 前四项在表结构中声明了 ON DELETE CASCADE，外键启用之前删除父记录会留下孤立行；
+/// 其余引用没有外键约束，由应用代码维护。
+const REFERENCES: &[(&str, &str, &str, OrphanFix)] = &[
+    ("todo_tag_relations", "todo_id", "todos", OrphanFix::Delete),
+    (
+        "todo_tag_relations",
+        "tag_id",
+        "todo_tags",
+        OrphanFix::Delete,
+    ),
+    ("todo_comments", "todo_id", "todos", OrphanFix::Delete),
+    ("todo_attachments", "todo_id", "todos", OrphanFix::Delete),
+    (
+        "snippets",
+        "folder_id",
+        "folders",
+        OrphanFix::ClearReference,
+    ),
+    (
+        "snippets",
+        "project_id",
+        "projects",
+        OrphanFix::ClearReference,
+    ),
+    ("folders", "parent_id", "folders", OrphanFix::ClearReference),
+    (
+        "projects",
+        "parent_id",
+        "projects",
+        OrphanFix::ClearReference,
+    ),
+    ("todos", "parent_id", "todos", OrphanFix::ClearReference),
+    ("todos", "project_id", "projects", OrphanFix::ClearReference),
+];
+
+/// 活动记录的保留天数，打开资料库时删除更早的记录
+const ACTIVITY_RETENTION_DAYS: i64 = 180;
+
+/// 未指定 limit 时返回的活动记录条数
+const DEFAULT_ACTIVITY_LIMIT: i64 = 100;
+
+/// 代码片段的全文索引表
+const FTS_TABLE: &str = "snippets_fts";
+
+/// 活动日志与撤销历史，不参与备份但计入数据库统计
+const HISTORY_TABLES: &[&str] = &["activity_log", "undo_journal"];
+
+/// 备份归档包含的表，按外键依赖顺序排列（恢复时按此顺序插入）
+/// snippets_fts 由触发器维护，无需备份
+pub const BACKUP_TABLES: &[&str] = &[
+    "folders",
+    "workspaces",
+    "projects",
+    "git_repositories",
+    "snippets",
+    "todo_tags",
+    "todos",
+    "todo_tag_relations",
+    "todo_comments",
+    "todo_attachments",
+];
+
+struct TagColorInfo {
+    color: String,
+    bg_color: String,
+}
+
+/// LIKE 子串匹配的模式，转义 keyword 中的通配符（配合 ESCAPE '\'）
+fn like_pattern(keyword: &str) -> String {
+    let escaped = keyword
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    format!("%{}%", escaped)
+}
+
+/// 名称比较键：不区分大小写，空白与 - 视为相同
+///
+/// 文本格式中的标签、项目名不能包含空白，导出时以 - 代替
+fn name_key(value: &str) -> String {
+    value
+        .split(|c: char| c.is_whitespace() || c == '-')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+/// TODO 及其所有层级子任务的 id（subtree 表），参数为根 TODO 的 id；UNION 去重，
+/// parent_id 成环时也会结束
+const TODO_SUBTREE: &str = "WITH RECURSIVE subtree(id) AS (
+    SELECT ?
+    UNION
+    SELECT t.id FROM todos t JOIN subtree s ON t.parent_id = s.id
+)";
+
+const TODO_COLUMNS: &str = "id, title, description, status, priority, due_date, estimated_hours, actual_hours, progress, assignee, project_id, parent_id, recurring_config, dependencies, completed, archived, created_by, updated_by, created_at, updated_at, archived_at, completed_at";
+
+/// 把平铺的 TODO 列表组装成子任务树，返回顶层 TODO
+///
+/// 父任务不在列表中的 TODO 作为顶层（如搜索结果中只匹配了子任务），
+/// 每一层保持列表中的顺序
+pub fn build_todo_tree(todos: Vec<Todo>) -> Vec<Todo> {
+    let ids: HashSet<String> = todos.iter().map(|todo| todo.id.clone()).collect();
+    assemble_todo_tree(todos, |todo| {
+        todo.parent_id
+            .as_ref()
+            .is_none_or(|parent_id| !ids.contains(parent_id))
+    })
+}
+
+/// 以满足 is_root 的 TODO 为根组装子任务树
+///
+/// 每个 TODO 只出现一次；parent_id 成环导致无法从根到达的 TODO 同样作为根返回，不会丢失
+fn assemble_todo_tree(todos: Vec<Todo>, is_root: impl Fn(&Todo) -> bool) -> Vec<Todo> {
+    let roots: Vec<usize> = (0..todos.len()).filter(|&i| is_root(&todos[i])).collect();
+    let mut children: HashMap<String, Vec<usize>> = HashMap::new();
+    for (index, todo) in todos.iter().enumerate() {
+        if let Some(parent_id) = &todo.parent_id {
+            children.entry(parent_id.clone()).or_default().push(index);
+        }
+    }
+
+    fn take(
+        index: usize,
+        slots: &mut [Option<Todo>],
+        children: &HashMap<String, Vec<usize>>,
+    ) -> Option<Todo> {
+        let mut todo = slots[index].take()?;
+        for &child in children.get(&todo.id).into_iter().flatten() {
+            if let Some(subtask) = take(child, slots, children) {
+                todo.subtasks.push(subtask);
+            }
+        }
+        Some(todo)
+    }
+
+    let mut slots: Vec<Option<Todo>> = todos.into_iter().map(Some).collect();
+    let mut tree = Vec::new();
+    for index in roots.into_iter().chain(0..slots.len()) {
+        if let Some(todo) = take(index, &mut slots, &children) {
+            tree.push(todo);
+        }
+    }
+    tree
+}
+
+/// todos 表的一行转换为 Todo，标签和子任务需另行加载
+fn todo_from_row(row: &SqliteRow) -> Todo {
+    let dependencies_str: String = row.try_get("dependencies").unwrap_or_default();
+
+    Todo {
+        id: row.get("id"),
+        title: row.get("title"),
+        description: row.get("description"),
+        status: row.get("status"),
+        priority: row.get("priority"),
+        due_date: row.get("due_date"),
+        estimated_hours: row.get("estimated_hours"),
+        actual_hours: row.get("actual_hours"),
+        progress: row.get("progress"),
+        assignee: row.get("assignee"),
+        project_id: row.get("project_id"),
+        parent_id: row.get("parent_id"),
+        recurring_config: row.get("recurring_config"),
+        dependencies: serde_json::from_str(&dependencies_str).unwrap_or_default(),
+        completed: row.get("completed"),
+        archived: row.get("archived"),
+        created_by: row.get("created_by"),
+        updated_by: row.get("updated_by"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        archived_at: row.get("archived_at"),
+        completed_at: row.get("completed_at"),
+        tags: Vec::new(),
+        subtasks: Vec::new(),
+    }
+}
+
+/// 导入一个代码片段草稿，返回结果及写入后应发布的事件
+async fn import_draft_record(
+    conn: &mut SqliteConnection,
+    index: usize,
+    draft: SnippetDraft,
+    folder_id: Option<String>,
+    dry_run: bool,
+) -> AppResult<(ImportItemResult, Option<ChangeEvent>)> {
+    let duplicate = sqlx::query(
+        "SELECT id FROM snippets WHERE title = ? AND language = ? AND code = ? AND folder_id IS ? LIMIT 1",
+    )
+    .bind(&draft.title)
+    .bind(&draft.language)
+    .bind(&draft.code)
+    .bind(&folder_id)
+    .fetch_optional(&mut *conn)
+    .await
+    .context("Failed to check duplicate snippet")?;
+
+    if let Some(row) = duplicate {
+        let result = ImportItemResult {
+            index,
+            id: Some(row.get("id")),
+            title: Some(draft.title),
+            action: ImportAction::Skip,
+            message: Some("Identical snippet already exists".to_string()),
+        };
+        return Ok((result, None));
+    }
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let created_at = draft.created_at.unwrap_or(now);
+    let notes = draft.notes;
+    let snippet = CodeSnippet {
+        id: uuid::Uuid::new_v4().to_string(),
+        title: draft.title,
+        description: draft.description,
+        code: draft.code,
+        language: draft.language,
+        tags: draft.tags,
+        folder_id,
+        project_id: None,
+        is_favorite: draft.is_favorite,
+        usage_count: 0,
+        created_at,
+        updated_at: draft.updated_at.unwrap_or(created_at),
+        library_id: None,
+    };
+
+    let mut event = None;
+    if !dry_run {
+        insert_snippet_record(conn, &snippet).await?;
+        event = Some(ChangeEvent::created(
+            ChangeEntity::Snippet,
+            &snippet.id,
+            &snippet,
+        ));
+    }
+
+    let result = ImportItemResult {
+        index,
+        id: Some(snippet.id),
+        title: Some(snippet.title),
+        action: ImportAction::Create,
+        message: if notes.is_empty() {
+            None
+        } else {
+            Some(notes.join("; "))
+        },
+    };
+    Ok((result, event))
+}
+
+/// 在 parent_id 下按名称逐级查找文件夹，不存在时创建（新建的文件夹记入 action）
+///
+/// dry_run 时不创建文件夹；返回值的第二项表示是否有（将要）新建的文件夹
+async fn ensure_folder_path(
+    action: &mut UndoAction,
+    parent_id: Option<String>,
+    path: &[String],
+    dry_run: bool,
+    events: &mut Vec<ChangeEvent>,
+) -> AppResult<(Option<String>, bool)> {
+    let mut current = parent_id;
+    let mut created = false;
+    for name in path {
+        let existing = sqlx::query(
+            "SELECT id FROM folders WHERE name = ? AND parent_id IS ? ORDER BY created_at ASC LIMIT 1",
+        )
+        .bind(name)
+        .bind(&current)
+        .fetch_optional(action.conn())
+        .await
+        .context("Failed to find folder")?;
+
+        current = match existing {
+            Some(row) => Some(row.get("id")),
+            // 预览模式下不创建，后续层级也必然不存在
+            None if dry_run => return Ok((None, true)),
+            None => {
+                created = true;
+                let folder = insert_folder(action.conn(), name.clone(), current).await?;
+                action.created("folders", "id", &folder.id);
+                events.push(ChangeEvent::created(
+                    ChangeEntity::Folder,
+                    &folder.id,
+                    &folder,
+                ));
+                Some(folder.id)
+            }
+        };
+    }
+    Ok((current, created))
+}
+
+/// 按冲突策略导入一个代码片段记录，返回结果及写入后应发布的事件
+///
+/// 文件夹或项目不存在时清除对应的引用，避免违反外键约束
+async fn import_snippet_record(
+    conn: &mut SqliteConnection,
+    index: usize,
+    mut snippet: CodeSnippet,
+    strategy: ImportConflictStrategy,
+    dry_run: bool,
+) -> AppResult<(ImportItemResult, Option<ChangeEvent>)> {
+    let existing = sqlx::query("SELECT * FROM snippets WHERE id = ?")
+        .bind(&snippet.id)
+        .fetch_optional(&mut *conn)
+        .await
+        .context("Failed to check existing snippet")?
+        .map(|row| snippet_from_row(&row));
+
+    let (action, mut notes) = match existing {
+        None => (ImportAction::Create, Vec::new()),
+        Some(existing) => match strategy {
+            ImportConflictStrategy::Skip => (
+                ImportAction::Skip,
+                vec!["Snippet already exists".to_string()],
+            ),
+            ImportConflictStrategy::Overwrite => (ImportAction::Update, Vec::new()),
+            ImportConflictStrategy::KeepBoth => {
+                snippet.id = uuid::Uuid::new_v4().to_string();
+                (
+                    ImportAction::Create,
+                    vec![format!("Imported as a copy of {}", existing.id)],
+                )
+            }
+            ImportConflictStrategy::MergeNewer => {
+                if snippet.updated_at > existing.updated_at {
+                    (ImportAction::Update, Vec::new())
+                } else {
+                    (
+                        ImportAction::Skip,
+                        vec!["Existing snippet is newer or unchanged".to_string()],
+                    )
+                }
+            }
+        },
+    };
+
+    if action != ImportAction::Skip {
+        if let Some(folder_id) = snippet.folder_id.clone() {
+            if !row_exists(conn, "folders", &folder_id).await? {
+                snippet.folder_id = None;
+                notes.push(format!(
+                    "Folder {} not found, imported without folder",
+                    folder_id
+                ));
+            }
+        }
+        if let Some(project_id) = snippet.project_id.clone() {
+            if !row_exists(conn, "projects", &project_id).await? {
+                snippet.project_id = None;
+                notes.push(format!(
+                    "Project {} not found, imported without project",
+                    project_id
+                ));
+            }
+        }
+    }
+
+    let mut event = None;
+    if !dry_run {
+        match action {
+            ImportAction::Create => {
+                insert_snippet_record(conn, &snippet).await?;
+                event = Some(ChangeEvent::created(
+                    ChangeEntity::Snippet,
+                    &snippet.id,
+                    &snippet,
+                ));
+            }
+            ImportAction::Update => {
+                replace_snippet_record(conn, &snippet).await?;
+                event = Some(ChangeEvent::updated(
+                    ChangeEntity::Snippet,
+                    &snippet.id,
+                    &snippet,
+                ));
+            }
+            ImportAction::Skip | ImportAction::Error => {}
+        }
+    }
+
+    let result = ImportItemResult {
+        index,
+        id: Some(snippet.id),
+        title: Some(snippet.title),
+        action,
+        message: if notes.is_empty() {
+            None
+        } else {
+            Some(notes.join("; "))
+        },
+    };
+    Ok((result, event))
+}
+
+/// 按名称查找项目，比较方式见 name_key
+async fn find_project_by_name(
+    conn: &mut SqliteConnection,
+    name: &str,
+) -> AppResult<Option<String>> {
+    let rows = sqlx::query("SELECT id, name FROM projects ORDER BY created_at ASC")
+        .fetch_all(&mut *conn)
+        .await
+        .context("Failed to get projects")?;
+
+    let wanted = name_key(name);
+    Ok(rows
+        .iter()
+        .find(|row| name_key(&row.get::<String, _>("name")) == wanted)
+        .map(|row| row.get("id")))
+}
+
+async fn row_exists(conn: &mut SqliteConnection, table: &str, id: &str) -> AppResult<bool> {
+    let sql = format!("SELECT EXISTS(SELECT 1 FROM {} WHERE id = ?)", table);
+    let exists: bool = sqlx::query_scalar(&sql)
+        .bind(id)
+        .fetch_one(conn)
+        .await
+        .with_context(|| format!("Failed to check {}", table))?;
+    Ok(exists)
+}
+
+/// 按原样插入代码片段记录，保留ID、时间戳和统计字段
+async fn insert_snippet_record(
+    conn: &mut SqliteConnection,
+    snippet: &CodeSnippet,
+) -> AppResult<()> {
+    let tags_json = serde_json::to_string(&snippet.tags).unwrap_or("[]".to_string());
+
+    sqlx::query(
+        "INSERT INTO snippets (id, title, description, code, language, tags, folder_id, project_id, is_favorite, usage_count, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&snippet.id)
+    .bind(&snippet.title)
+    .bind(&snippet.description)
+    .bind(&snippet.code)
+    .bind(&snippet.language)
+    .bind(&tags_json)
+    .bind(&snippet.folder_id)
+    .bind(&snippet.project_id)
+    .bind(if snippet.is_favorite { 1 } else { 0 })
+    .bind(snippet.usage_count)
+    .bind(snippet.created_at)
+    .bind(snippet.updated_at)
+    .execute(conn)
+    .await
+    .context("Failed to insert snippet")?;
+    Ok(())
+}
+
+/// 用导入的记录整体替换现有代码片段
+async fn replace_snippet_record(
+    conn: &mut SqliteConnection,
+    snippet: &CodeSnippet,
+) -> AppResult<()> {
+    let tags_json = serde_json::to_string(&snippet.tags).unwrap_or("[]".to_string());
+
+    sqlx::query(
+        "UPDATE snippets SET title = ?, description = ?, code = ?, language = ?, tags = ?, folder_id = ?, project_id = ?, is_favorite = ?, usage_count = ?, created_at = ?, updated_at = ? WHERE id = ?"
+    )
+    .bind(&snippet.title)
+    .bind(&snippet.description)
+    .bind(&snippet.code)
+    .bind(&snippet.language)
+    .bind(&tags_json)
+    .bind(&snippet.folder_id)
+    .bind(&snippet.project_id)
+    .bind(if snippet.is_favorite { 1 } else { 0 })
+    .bind(snippet.usage_count)
+    .bind(snippet.created_at)
+    .bind(snippet.updated_at)
+    .bind(&snippet.id)
+    .execute(conn)
+    .await
+    .context("Failed to replace snippet")?;
+    Ok(())
+}
+
+/// 插入一个文件夹，不记录撤销历史也不发布事件
+async fn insert_folder(
+    conn: &mut SqliteConnection,
+    name: String,
+    parent_id: Option<String>,
+) -> AppResult<Folder> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp_millis();
+
+    sqlx::query("INSERT INTO folders (id, name, parent_id, created_at) VALUES (?, ?, ?, ?)")
+        .bind(&id)
+        .bind(&name)
+        .bind(&parent_id)
+        .bind(now)
+        .execute(&mut *conn)
+        .await?;
+
+    Ok(Folder {
+        id,
+        name,
+        parent_id,
+        created_at: now,
+    })
+}
+
+/// 插入一个 TODO 标签，不记录撤销历史也不发布事件
+async fn insert_todo_tag(
+    conn: &mut SqliteConnection,
+    req: CreateTodoTagRequest,
+    color_info: TagColorInfo,
+) -> AppResult<TodoTag> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().timestamp_millis();
+
+    sqlx::query(
+        "INSERT INTO todo_tags (id, name, color, bg_color, color_id, created_at) VALUES (?, ?, ?, ?, ?, ?)"
+    )
+    .bind(&id)
+    .bind(&req.name)
+    .bind(&color_info.color)
+    .bind(&color_info.bg_color)
+    .bind(&req.color_id)
+    .bind(now)
+    .execute(&mut *conn)
+    .await
+    .context("Failed to create todo tag")?;
+
+    Ok(TodoTag {
+        id,
+        name: req.name,
+        color: color_info.color,
+        bg_color: color_info.bg_color,
+        color_id: req.color_id,
+        created_at: now,
+    })
+}
+
+/// 按请求中提供的字段更新 TODO，tags 提供时替换全部标签关联
+async fn update_todo_record(conn: &mut SqliteConnection, req: &UpdateTodoRequest) -> AppResult<()> {
+    let now = chrono::Utc::now().timestamp_millis();
+
+    // Simple approach: update all fields that are provided
+    let mut sql_parts = Vec::new();
+
+    if let Some(title) = &req.title {
+        sql_parts.push(format!("title = '{}'", title.replace("'", "''")));
+    }
+    if let Some(description) = &req.description {
+        sql_parts.push(format!(
+            "description = '{}'",
+            description.replace("'", "''")
+        ));
+    }
+    if let Some(status) = &req.status {
+        sql_parts.push(format!("status = '{}'", status.replace("'", "''")));
+    }
+    if let Some(priority) = &req.priority {
+        sql_parts.push(format!("priority = '{}'", priority.replace("'", "''")));
+    }
+    if let Some(due_date) = &req.due_date {
+        sql_parts.push(format!("due_date = '{}'", due_date.replace("'", "''")));
+    }
+    if let Some(estimated_hours) = req.estimated_hours {
+        sql_parts.push(format!("estimated_hours = {}", estimated_hours));
+    }
+    if let Some(actual_hours) = req.actual_hours {
+        sql_parts.push(format!("actual_hours = {}", actual_hours));
+    }
+    if let Some(progress) = req.progress {
+        sql_parts.push(format!("progress = {}", progress));
+    }
+    if let Some(completed) = req.completed {
+        sql_parts.push(format!("completed = {}", completed));
+    }
+    if let Some(archived) = req.archived {
+        sql_parts.push(format!("archived = {}", archived));
+        if archived {
+            sql_parts.push(format!("archived_at = {}", now));
+        }
+    }
+    // 变为完成时记录完成时间（已完成的保留原时间），重新打开时清空
+    if req.status.is_some() || req.completed.is_some() {
+        let completed = match req.completed {
+            Some(completed) => completed.to_string(),
+            None => "completed".to_string(),
+        };
+        let status = match &req.status {
+            Some(status) => format!("'{}'", status.replace("'", "''")),
+            None => "status".to_string(),
+        };
+        sql_parts.push(format!(
+            "completed_at = CASE WHEN {} OR {} = 'completed' THEN COALESCE(completed_at, {}) ELSE NULL END",
+            completed, status, now
+        ));
+    }
+
+    sql_parts.push(format!("updated_at = {}", now));
+
+    if !sql_parts.is_empty() {
+        let sql = format!("UPDATE todos SET {} WHERE id = ?", sql_parts.join(", "));
+        sqlx::query(&sql)
+            .bind(&req.id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to update todo")?;
+    }
+
+    // Update tags if provided
+    if let Some(tags) = &req.tags {
+        // Remove existing tag relations
+        sqlx::query("DELETE FROM todo_tag_relations WHERE todo_id = ?")
+            .bind(&req.id)
+            .execute(&mut *conn)
+            .await
+            .context("Failed to remove tag relations")?;
+
+        // Add new tag relations
+        for tag_id in tags {
+            sqlx::query("INSERT INTO todo_tag_relations (todo_id, tag_id) VALUES (?, ?)")
+                .bind(&req.id)
+                .bind(tag_id)
+                .execute(&mut *conn)
+                .await
+                .context("Failed to add tag relation")?;
+        }
+    }
+    Ok(())
+}
+
+/// 合并 WAL 后关闭连接池，使只读挂载方能以 immutable 方式读到全部数据
+///
+/// 只读连接池上的合并会失败，此时直接关闭
+async fn close_pool(pool: &SqlitePool) {
+    let _ = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .execute(pool)
+        .await;
+    pool.close().await;
+}
+
+fn snippet_from_row(row: &SqliteRow) -> CodeSnippet {
+    let tags_str: String = row.try_get("tags").unwrap_or_default();
+    let tags: Vec<String> = serde_json::from_str(&tags_str).unwrap_or_default();
+
+    CodeSnippet {
+        id: row.get("id"),
+        title: row.get("title"),
+        description: row.try_get("description").unwrap_or_default(),
+        code: row.get("code"),
+        language: row.get("language"),
+        tags,
+        folder_id: row.get("folder_id"),
+        project_id: row.get("project_id"),
+        is_favorite: row.try_get("is_favorite").unwrap_or(0) != 0,
+        usage_count: row.try_get("usage_count").unwrap_or(0),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+        library_id: None,
+    }
+}
+
+/// WHERE 子句及其参数：主键各列相等
+fn key_condition(key: &TableRow) -> String {
+    key.keys()
+        .map(|column| format!("{} = ?", column))
+        .collect::<Vec<_>>()
+        .join(" AND ")
+}
+
+async fn select_by_key(
+    conn: &mut SqliteConnection,
+    table: &str,
+    key: &TableRow,
+) -> AppResult<Option<TableRow>> {
+    let sql = format!("SELECT * FROM {} WHERE {}", table, key_condition(key));
+    let mut query = sqlx::query(&sql);
+    for value in key.values() {
+        query = bind_json_value(query, value);
+    }
+    let row = query
+        .fetch_optional(&mut *conn)
+        .await
+        .with_context(|| format!("Failed to read {}", table))?;
+    Ok(row.as_ref().map(table_row_from_sqlite))
+}
+
+/// 把主键为 key 的行写成 row 的内容，row 为 None 时删除该行
+async fn write_row(
+    conn: &mut SqliteConnection,
+    table: &str,
+    key: &TableRow,
+    row: Option<&TableRow>,
+) -> AppResult<()> {
+    let Some(row) = row else {
+        let sql = format!("DELETE FROM {} WHERE {}", table, key_condition(key));
+        let mut query = sqlx::query(&sql);
+        for value in key.values() {
+            query = bind_json_value(query, value);
+        }
+        query.execute(&mut *conn).await?;
+        return Ok(());
+    };
+
+    // 先尝试更新，不存在时再插入；REPLACE 会删除旧行并触发外键级联删除
+    let assignments: Vec<String> = row.keys().map(|column| format!("{} = ?", column)).collect();
+    let sql = format!(
+        "UPDATE {} SET {} WHERE {}",
+        table,
+        assignments.join(", "),
+        key_condition(key)
+    );
+    let mut query = sqlx::query(&sql);
+    for value in row.values().chain(key.values()) {
+        query = bind_json_value(query, value);
+    }
+    if query.execute(&mut *conn).await?.rows_affected() > 0 {
+        return Ok(());
+    }
+
+    let columns: Vec<&str> = row.keys().map(String::as_str).collect();
+    let sql = format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        columns.join(", "),
+        vec!["?"; columns.len()].join(", ")
+    );
+    let mut query = sqlx::query(&sql);
+    for value in row.values() {
+        query = bind_json_value(query, value);
+    }
+    query.execute(&mut *conn).await?;
+    Ok(())
+}
+
+/// 统计 REFERENCES 中每个引用关系的孤立记录，repair 为 true 时同时修复
+async fn find_orphans(
+    conn: &mut SqliteConnection,
+    repair: bool,
+) -> AppResult<Vec<OrphanedReference>> {
+    let mut orphans = Vec::new();
+    for (table, column, parent, fix) in REFERENCES {
+        let condition = format!(
+            "{column} IS NOT NULL AND {column} != '' AND {column} NOT IN (SELECT id FROM {parent})"
+        );
+        let count: i64 =
+            sqlx::query_scalar(&format!("SELECT COUNT(*) FROM {table} WHERE {condition}"))
+                .fetch_one(&mut *conn)
+                .await
+                .with_context(|| format!("Failed to check {}.{}", table, column))?;
+        if count == 0 {
+            continue;
+        }
+
+        let (sql, action) = match fix {
+            OrphanFix::Delete => (format!("DELETE FROM {table} WHERE {condition}"), "delete"),
+            OrphanFix::ClearReference => (
+                format!("UPDATE {table} SET {column} = NULL WHERE {condition}"),
+                "clear_reference",
+            ),
+        };
+        if repair {
+            sqlx::query(&sql)
+                .execute(&mut *conn)
+                .await
+                .with_context(|| format!("Failed to repair {}.{}", table, column))?;
+        }
+        orphans.push(OrphanedReference {
+            table: table.to_string(),
+            column: column.to_string(),
+            parent_table: parent.to_string(),
+            count,
+            repair: action.to_string(),
+        });
+    }
+    Ok(orphans)
+}
+
+/// 将任意表的一行转换为 JSON 对象，BLOB 以 {"$blob": base64} 表示
+fn table_row_from_sqlite(row: &SqliteRow) -> TableRow {
+    let mut map = TableRow::new();
+    for (index, column) in row.columns().iter().enumerate() {
+        let value = match row.try_get_raw(index) {
+            Ok(raw) if !raw.is_null() => {
+                let type_name = raw.type_info().name().to_string();
+                match type_name.as_str() {
+                    "INTEGER" => row
+                        .try_get_unchecked::<i64, _>(index)
+                        .map(serde_json::Value::from)
+                        .unwrap_or_default(),
+                    "REAL" => row
+                        .try_get_unchecked::<f64, _>(index)
+                        .map(serde_json::Value::from)
+                        .unwrap_or_default(),
+                    "BLOB" => row
+                        .try_get_unchecked::<Vec<u8>, _>(index)
+                        .map(|bytes| serde_json::json!({ "$blob": BASE64.encode(bytes) }))
+                        .unwrap_or_default(),
+                    _ => row
+                        .try_get_unchecked::<String, _>(index)
+                        .map(serde_json::Value::from)
+                        .unwrap_or_default(),
+                }
+            }
+            _ => serde_json::Value::Null,
+        };
+        map.insert(column.name().to_string(), value);
+    }
+    map
+}
+
+fn bind_json_value<'q>(
+    query: sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>>,
+    value: &serde_json::Value,
+) -> sqlx::query::Query<'q, sqlx::Sqlite, sqlx::sqlite::SqliteArguments<'q>> {
+    match value {
+        serde_json::Value::Null => query.bind(None::<String>),
+        serde_json::Value::Bool(b) => query.bind(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => query.bind(i),
+            None => query.bind(n.as_f64().unwrap_or_default()),
+        },
+        serde_json::Value::String(s) => query.bind(s.clone()),
+        serde_json::Value::Object(obj) if obj.contains_key("$blob") => {
+            let bytes = obj
+                .get("$blob")
+                .and_then(|v| v.as_str())
+                .and_then(|encoded| BASE64.decode(encoded).ok())
+                .unwrap_or_default();
+            query.bind(bytes)
+        }
+        other => query.bind(other.to_string()),
+    }
+}
+
+/// 删除 TODO 及其所有层级的子任务，返回被删除的 id（子任务在前）
+async fn delete_todo_subtree(conn: &mut SqliteConnection, todo_id: &str) -> AppResult<Vec<String>> {
+    let mut ids = Database::todo_subtree(conn, todo_id).await?;
+    ids.reverse();
+    sqlx::query(&format!(
+        "{} DELETE FROM todos WHERE id IN (SELECT id FROM subtree)",
+        TODO_SUBTREE
+    ))
+    .bind(todo_id)
+    .execute(&mut *conn)
+    .await
+    .with_context(|| format!("Failed to delete todo {}", todo_id))?;
+    Ok(ids)
 }
@@ -0,0 +1,241 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file error.rs - 统一错误类型
+ * @author Noah
+ * @description 后端命令返回给前端的结构化错误
+ * @created 2026-02-19
+ * @version 1.0.0
+ *
+ * 序列化格式:
+ * { "code": "not_found", "message": "Todo not found", "details": "..." }
+ *
+ * - code: 稳定的错误代码，前端据此区分处理方式
+ * - message: 可直接展示给用户的说明，不包含 SQL 或文件系统细节
+ * - details: 排查问题用的技术细节（原始错误及上下文），可能为 null
+ *
+ * 架构说明:
+ * database.rs 与 commands.rs 统一返回 AppResult。sqlx、IO、JSON 错误通过 From
+ * 自动归类（约束冲突 -> conflict，写入只读资料库 -> read_only，连接池关闭/超时
+ * -> database_not_ready），调用处用 ResultExt::context 补充"正在做什么"。
+ * formats 模块只负责格式转换，仍返回 String，由命令层转换为 invalid_data。
+ */
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::fmt;
+
+#[derive(Debug, Clone)]
+pub enum AppError {
+    /// 记录不存在，entity 为记录类型（如 "todo"）
+    NotFound { entity: &'static str, id: String },
+    /// 请求参数不合法，消息可直接展示
+    Validation(String),
+    /// 导入或恢复的数据无法解析，消息可直接展示
+    InvalidData(String),
+    /// 数据库或归档由更新版本的应用创建，消息可直接展示
+    UnsupportedVersion(String),
+    /// 违反唯一、外键等约束
+    Conflict { details: String },
+    /// 试图写入只读资料库
+    ReadOnly { details: String },
+    /// 数据库尚未初始化完成或连接已关闭
+    DatabaseNotReady,
+    /// 其他数据库错误
+    Database { details: String },
+    /// 文件读写错误
+    Io { details: String },
+    /// 不应发生的内部错误
+    Internal { details: String },
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn not_found(entity: &'static str, id: impl Into<String>) -> Self {
+        AppError::NotFound {
+            entity,
+            id: id.into(),
+        }
+    }
+
+    pub fn validation(message: impl Into<String>) -> Self {
+        AppError::Validation(message.into())
+    }
+
+    pub fn internal(details: impl Into<String>) -> Self {
+        AppError::Internal {
+            details: details.into(),
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::NotFound { .. } => "not_found",
+            AppError::Validation(_) => "validation",
+            AppError::InvalidData(_) => "invalid_data",
+            AppError::UnsupportedVersion(_) => "unsupported_version",
+            AppError::Conflict { .. } => "conflict",
+            AppError::ReadOnly { .. } => "read_only",
+            AppError::DatabaseNotReady => "database_not_ready",
+            AppError::Database { .. } => "database",
+            AppError::Io { .. } => "io",
+            AppError::Internal { .. } => "internal",
+        }
+    }
+
+    /// 面向用户的说明
+    pub fn message(&self) -> String {
+        match self {
+            AppError::NotFound { entity, .. } => {
+                let mut entity = entity.replace('_', " ");
+                if let Some(first) = entity.get_mut(..1) {
+                    first.make_ascii_uppercase();
+                }
+                format!("{} not found", entity)
+            }
+            AppError::Validation(message)
+            | AppError::InvalidData(message)
+            | AppError::UnsupportedVersion(message) => message.clone(),
+            AppError::Conflict { .. } => "The change conflicts with existing data".to_string(),
+            AppError::ReadOnly { .. } => "This library is read-only".to_string(),
+            AppError::DatabaseNotReady => "The database is not ready yet".to_string(),
+            AppError::Database { .. } => "A database error occurred".to_string(),
+            AppError::Io { .. } => "A file could not be read or written".to_string(),
+            AppError::Internal { .. } => "An unexpected error occurred".to_string(),
+        }
+    }
+
+    pub fn details(&self) -> Option<String> {
+        match self {
+            AppError::NotFound { id, .. } if !id.is_empty() => Some(format!("id: {}", id)),
+            AppError::Conflict { details }
+            | AppError::ReadOnly { details }
+            | AppError::Database { details }
+            | AppError::Io { details }
+            | AppError::Internal { details } => Some(details.clone()),
+            _ => None,
+        }
+    }
+
+    /// 在技术细节前加上上下文；面向用户的消息不变
+    pub fn context(self, context: &str) -> Self {
+        let prefix = |details: String| format!("{}: {}", context, details);
+        match self {
+            AppError::Conflict { details } => AppError::Conflict {
+                details: prefix(details),
+            },
+            AppError::ReadOnly { details } => AppError::ReadOnly {
+                details: prefix(details),
+            },
+            AppError::Database { details } => AppError::Database {
+                details: prefix(details),
+            },
+            AppError::Io { details } => AppError::Io {
+                details: prefix(details),
+            },
+            AppError::Internal { details } => AppError::Internal {
+                details: prefix(details),
+            },
+            other => other,
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.details() {
+            Some(details) => write!(f, "{} ({})", self.message(), details),
+            None => write!(f, "{}", self.message()),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AppError", 3)?;
+        state.serialize_field("code", self.code())?;
+        state.serialize_field("message", &self.message())?;
+        state.serialize_field("details", &self.details())?;
+        state.end()
+    }
+}
+
+impl From<sqlx::Error> for AppError {
+    fn from(error: sqlx::Error) -> Self {
+        match &error {
+            sqlx::Error::RowNotFound => AppError::not_found("record", ""),
+            sqlx::Error::PoolClosed | sqlx::Error::PoolTimedOut => AppError::DatabaseNotReady,
+            sqlx::Error::Database(db_error)
+                if matches!(
+                    db_error.kind(),
+                    sqlx::error::ErrorKind::UniqueViolation
+                        | sqlx::error::ErrorKind::ForeignKeyViolation
+                        | sqlx::error::ErrorKind::NotNullViolation
+                        | sqlx::error::ErrorKind::CheckViolation
+                ) =>
+            {
+                AppError::Conflict {
+                    details: error.to_string(),
+                }
+            }
+            // SQLITE_READONLY 及其扩展错误码
+            sqlx::Error::Database(db_error)
+                if db_error
+                    .code()
+                    .and_then(|code| code.parse::<i32>().ok())
+                    .is_some_and(|code| code & 0xff == 8) =>
+            {
+                AppError::ReadOnly {
+                    details: error.to_string(),
+                }
+            }
+            _ => AppError::Database {
+                details: error.to_string(),
+            },
+        }
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        AppError::Io {
+            details: error.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(error: serde_json::Error) -> Self {
+        AppError::internal(error.to_string())
+    }
+}
+
+impl From<tokio::task::JoinError> for AppError {
+    fn from(error: tokio::task::JoinError) -> Self {
+        AppError::internal(error.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(error: tauri::Error) -> Self {
+        AppError::internal(error.to_string())
+    }
+}
+
+/// 为 Result 的错误补充上下文
+pub trait ResultExt<T> {
+    fn context(self, context: &str) -> AppResult<T>;
+    fn with_context(self, context: impl FnOnce() -> String) -> AppResult<T>;
+}
+
+impl<T, E: Into<AppError>> ResultExt<T> for Result<T, E> {
+    fn context(self, context: &str) -> AppResult<T> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context(self, context: impl FnOnce() -> String) -> AppResult<T> {
+        self.map_err(|e| e.into().context(&context()))
+    }
+}
@@ -0,0 +1,155 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file events.rs - 数据变更事件
+ * @author Noah
+ * @description 数据库写入后发布类型化的变更事件，并转发给所有窗口
+ * @created 2026-02-24
+ * @version 1.0.0
+ *
+ * 功能特性:
+ * - 事件名为 "<实体>:<动作>"，如 snippet:created、todo:updated、folder:deleted
+ * - 载荷包含受影响的实体ID，创建和更新事件同时带有最新的实体数据
+ * - 导入备份、修复数据库、切换资料库等批量变更发送 library:reloaded，
+ *   收到后应重新加载全部数据
+ *
+ * 架构说明:
+ * Database 持有一个 broadcast 通道，写入方法成功后发布事件，不依赖 AppHandle，
+ * 因此测试中可以直接订阅。forward 把通道中的事件转发为 Tauri 事件，
+ * 多个窗口、托盘菜单等都能据此更新，而无需轮询。
+ */
+use crate::database::Database;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::broadcast::error::RecvError;
+
+/// 事件通道容量，订阅者处理过慢时最旧的事件会被丢弃
+pub const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeEntity {
+    Snippet,
+    Folder,
+    Workspace,
+    Project,
+    GitRepository,
+    Todo,
+    TodoTag,
+    /// 整个资料库，仅用于 reloaded
+    Library,
+}
+
+impl ChangeEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeEntity::Snippet => "snippet",
+            ChangeEntity::Folder => "folder",
+            ChangeEntity::Workspace => "workspace",
+            ChangeEntity::Project => "project",
+            ChangeEntity::GitRepository => "git_repository",
+            ChangeEntity::Todo => "todo",
+            ChangeEntity::TodoTag => "todo_tag",
+            ChangeEntity::Library => "library",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeAction {
+    Created,
+    Updated,
+    Deleted,
+    Reloaded,
+}
+
+impl ChangeAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeAction::Created => "created",
+            ChangeAction::Updated => "updated",
+            ChangeAction::Deleted => "deleted",
+            ChangeAction::Reloaded => "reloaded",
+        }
+    }
+}
+
+/// 一次数据变更
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangeEvent {
+    pub entity: ChangeEntity,
+    pub action: ChangeAction,
+    /// 受影响的实体ID
+    pub ids: Vec<String>,
+    /// 变更后的实体（批量更新时为数组）；删除事件和级联引起的更新
+    /// （如删除文件夹后代码片段移到根目录）没有载荷
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payload: Option<serde_json::Value>,
+}
+
+impl ChangeEvent {
+    pub fn created(entity: ChangeEntity, id: &str, payload: &impl Serialize) -> Self {
+        Self::with_payload(entity, ChangeAction::Created, vec![id.to_string()], payload)
+    }
+
+    pub fn updated(entity: ChangeEntity, id: &str, payload: &impl Serialize) -> Self {
+        Self::with_payload(entity, ChangeAction::Updated, vec![id.to_string()], payload)
+    }
+
+    pub fn deleted(entity: ChangeEntity, ids: Vec<String>) -> Self {
+        ChangeEvent {
+            entity,
+            action: ChangeAction::Deleted,
+            ids,
+            payload: None,
+        }
+    }
+
+    /// 资料库被整体替换或批量修改
+    pub fn reloaded() -> Self {
+        ChangeEvent {
+            entity: ChangeEntity::Library,
+            action: ChangeAction::Reloaded,
+            ids: Vec::new(),
+            payload: None,
+        }
+    }
+
+    pub fn with_payload(
+        entity: ChangeEntity,
+        action: ChangeAction,
+        ids: Vec<String>,
+        payload: &impl Serialize,
+    ) -> Self {
+        ChangeEvent {
+            entity,
+            action,
+            ids,
+            payload: serde_json::to_value(payload).ok(),
+        }
+    }
+
+    /// Tauri 事件名，如 snippet:created
+    pub fn name(&self) -> String {
+        format!("{}:{}", self.entity.as_str(), self.action.as_str())
+    }
+}
+
+/// 把数据库的变更事件转发为 Tauri 事件，发送给所有窗口
+///
+/// 转发跟不上导致事件丢失时改为发送 library:reloaded，让前端整体刷新
+pub fn forward(app: &AppHandle, db: &Database) {
+    let mut receiver = db.subscribe();
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let event = match receiver.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(_)) => ChangeEvent::reloaded(),
+                Err(RecvError::Closed) => break,
+            };
+            let _ = app.emit(&event.name(), &event);
+        }
+    });
+}
@@ -0,0 +1,266 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/archive.rs - 全库备份归档
+ * @author Noah
+ * @description 带版本号、清单和校验和的 zip 备份归档的读写与升级
+ * @created 2026-02-08
+ * @version 1.0.0
+ *
+ * 归档结构:
+ * - manifest.json: 格式标识、归档版本、数据库结构版本、各表行数和 SHA-256
+ * - tables/<table>.json: 每张表一个 JSON 数组，每行为 { 列名: 值 }
+ *
+ * 版本说明:
+ * - 版本 0: 旧版 export_to_json 导出的代码片段数组（非 zip）
+ * - 版本 1: 当前的 zip 归档格式
+ *
+ * 归档记录的数据库结构版本低于当前版本时，读取后按 SCHEMA_UPGRADES 逐版本转换
+ * 表数据（如结构版本 4 新增的 todos.completed_at），再交给恢复流程
+ */
+use crate::models::{BackupManifest, BackupTableEntry};
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::io::{Cursor, Read, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+pub const ARCHIVE_FORMAT: &str = "snippetshub-backup";
+pub const ARCHIVE_VERSION: i64 = 1;
+const MANIFEST_FILE: &str = "manifest.json";
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 表中的一行数据
+pub type TableRow = Map<String, Value>;
+/// 表名到行数据的映射
+pub type TableSet = BTreeMap<String, Vec<TableRow>>;
+
+/// 把表数据从上一个结构版本转换到某个结构版本
+type SchemaUpgrade = fn(&mut TableSet);
+
+/// 数据库结构升级对应的归档数据转换，(引入变化的结构版本, 转换函数)，按版本升序排列
+///
+/// 只有改变已有数据含义的迁移需要转换；新增列缺省即可的迁移无需登记
+const SCHEMA_UPGRADES: &[(i64, SchemaUpgrade)] = &[(4, fill_completed_at)];
+
+/// 解析后的归档内容
+pub struct Archive {
+    pub manifest: BackupManifest,
+    pub tables: TableSet,
+    /// 若归档由旧版本升级而来，记录原版本号
+    pub upgraded_from: Option<i64>,
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+/// 生成备份归档
+///
+/// tables 中表的顺序即恢复时的插入顺序
+pub fn write(
+    tables: &[(String, Vec<TableRow>)],
+    schema_version: i64,
+) -> Result<(Vec<u8>, BackupManifest), String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    let mut entries = Vec::new();
+    for (name, rows) in tables {
+        let file = format!("tables/{}.json", name);
+        let bytes = serde_json::to_vec(rows)
+            .map_err(|e| format!("Failed to serialize table {}: {}", name, e))?;
+
+        zip.start_file(file.as_str(), options)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+        zip.write_all(&bytes)
+            .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+        entries.push(BackupTableEntry {
+            name: name.clone(),
+            file,
+            rows: rows.len(),
+            sha256: sha256_hex(&bytes),
+        });
+    }
+
+    let manifest = BackupManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        archive_version: ARCHIVE_VERSION,
+        schema_version,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+        tables: entries,
+    };
+
+    let manifest_bytes = serde_json::to_vec_pretty(&manifest)
+        .map_err(|e| format!("Failed to serialize manifest: {}", e))?;
+    zip.start_file(MANIFEST_FILE, options)
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    zip.write_all(&manifest_bytes)
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+
+    let cursor = zip
+        .finish()
+        .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    Ok((cursor.into_inner(), manifest))
+}
+
+/// 读取并校验备份归档，必要时升级到当前归档版本
+///
+/// max_schema_version 为当前应用支持的数据库结构版本，更新版本的归档将被拒绝
+pub fn read(bytes: &[u8], max_schema_version: i64) -> Result<Archive, String> {
+    // 旧版 JSON 导出文件（版本 0），可能带有 UTF-8 BOM 或前导空白
+    let text = bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes);
+    let text = text.trim_ascii_start();
+    if text.first() == Some(&b'[') {
+        let mut archive = upgrade_legacy_export(text)?;
+        upgrade_schema(&mut archive.tables, 0, max_schema_version);
+        return Ok(archive);
+    }
+
+    let mut zip = ZipArchive::new(Cursor::new(bytes))
+        .map_err(|e| format!("Not a valid backup archive: {}", e))?;
+
+    let manifest_text = read_entry(&mut zip, MANIFEST_FILE)?;
+    let manifest: BackupManifest = serde_json::from_slice(&manifest_text)
+        .map_err(|e| format!("Invalid backup manifest: {}", e))?;
+
+    if manifest.format != ARCHIVE_FORMAT {
+        return Err(format!("Unknown archive format: {}", manifest.format));
+    }
+    if manifest.archive_version > ARCHIVE_VERSION {
+        return Err(format!(
+            "Backup archive version {} is newer than supported version {}",
+            manifest.archive_version, ARCHIVE_VERSION
+        ));
+    }
+    if manifest.schema_version > max_schema_version {
+        return Err(format!(
+            "Backup was created by a newer app (schema version {}, supported {})",
+            manifest.schema_version, max_schema_version
+        ));
+    }
+
+    let mut tables = TableSet::new();
+    for entry in &manifest.tables {
+        let data = read_entry(&mut zip, &entry.file)?;
+        let checksum = sha256_hex(&data);
+        if checksum != entry.sha256 {
+            return Err(format!(
+                "Checksum mismatch for table {}: archive may be corrupted",
+                entry.name
+            ));
+        }
+
+        let rows: Vec<TableRow> = serde_json::from_slice(&data)
+            .map_err(|e| format!("Invalid data for table {}: {}", entry.name, e))?;
+        if rows.len() != entry.rows {
+            return Err(format!(
+                "Row count mismatch for table {}: manifest {}, archive {}",
+                entry.name,
+                entry.rows,
+                rows.len()
+            ));
+        }
+        tables.insert(entry.name.clone(), rows);
+    }
+    upgrade_schema(&mut tables, manifest.schema_version, max_schema_version);
+
+    Ok(Archive {
+        manifest,
+        tables,
+        upgraded_from: None,
+    })
+}
+
+fn read_entry(zip: &mut ZipArchive<Cursor<&[u8]>>, name: &str) -> Result<Vec<u8>, String> {
+    let mut file = zip
+        .by_name(name)
+        .map_err(|e| format!("Missing {} in backup archive: {}", name, e))?;
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)
+        .map_err(|e| format!("Failed to read {}: {}", name, e))?;
+    Ok(data)
+}
+
+/// 版本 0 -> 1: 将 CodeSnippet 数组转换为 snippets 表的行
+fn upgrade_legacy_export(bytes: &[u8]) -> Result<Archive, String> {
+    let items: Vec<TableRow> =
+        serde_json::from_slice(bytes).map_err(|e| format!("Invalid legacy JSON export: {}", e))?;
+
+    let rows: Vec<TableRow> = items
+        .into_iter()
+        .map(|mut item| {
+            // 旧格式中 tags 为数组、is_favorite 为布尔值，表中分别存储为 JSON 文本和整数
+            let tags = item.remove("tags").unwrap_or(Value::Array(Vec::new()));
+            item.insert("tags".to_string(), Value::String(tags.to_string()));
+            let is_favorite = item
+                .remove("is_favorite")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            item.insert("is_favorite".to_string(), Value::from(is_favorite as i64));
+            item
+        })
+        .collect();
+
+    let manifest = BackupManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        archive_version: 0,
+        schema_version: 0,
+        app_version: String::new(),
+        created_at: 0,
+        tables: vec![BackupTableEntry {
+            name: "snippets".to_string(),
+            file: String::new(),
+            rows: rows.len(),
+            sha256: String::new(),
+        }],
+    };
+
+    let mut tables = TableSet::new();
+    tables.insert("snippets".to_string(), rows);
+
+    Ok(Archive {
+        manifest,
+        tables,
+        upgraded_from: Some(0),
+    })
+}
+
+/// 依次执行 from 之后、直到 to（含）的结构版本对应的数据转换
+fn upgrade_schema(tables: &mut TableSet, from: i64, to: i64) {
+    for (version, upgrade) in SCHEMA_UPGRADES {
+        if *version > from && *version <= to {
+            upgrade(tables);
+        }
+    }
+}
+
+/// 结构版本 4: 已完成的 TODO 没有完成时间，与迁移一样以最后更新时间近似
+fn fill_completed_at(tables: &mut TableSet) {
+    let Some(todos) = tables.get_mut("todos") else {
+        return;
+    };
+    for todo in todos {
+        if todo.get("completed_at").is_some_and(|v| !v.is_null()) {
+            continue;
+        }
+        let completed = match todo.get("completed") {
+            Some(Value::Bool(completed)) => *completed,
+            Some(value) => value.as_i64().is_some_and(|v| v != 0),
+            None => false,
+        } || todo.get("status").and_then(Value::as_str) == Some("completed");
+        let completed_at = if completed {
+            todo.get("updated_at").cloned().unwrap_or(Value::Null)
+        } else {
+            Value::Null
+        };
+        todo.insert("completed_at".to_string(), completed_at);
+    }
+}
@@ -0,0 +1,240 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/checklist.rs - Markdown 任务列表
+ * @author Noah
+ * @description TODO 与 Markdown 任务列表（GitHub 风格 checklist）之间的转换
+ * @created 2026-02-15
+ * @version 1.0.0
+ *
+ * 格式示例:
+ * - [ ] 发布 2.0 !high #release due:2026-03-01
+ *   - [x] 更新变更日志
+ *
+ * 字段映射:
+ * - 列表缩进 <-> 父子任务
+ * - [x] <-> 已完成；其他状态导出为 [ ]
+ * - !high / !medium / !low <-> 优先级
+ * - #标签 <-> 标签名称（标签名中的空白替换为 -，#123 这类编号保留在标题中）
+ * - due:YYYY-MM-DD <-> 截止日期
+ * - 描述、进度、依赖等字段不导出；导入时忽略非任务列表项和其他文本
+ */
+use super::EntryError;
+use crate::models::{Todo, TodoDraft};
+use std::collections::{HashMap, HashSet};
+
+// ============================================================================
+// 写入
+// ============================================================================
+
+/// 标签名称写成单个 #word
+pub fn tag_word(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join("-")
+}
+
+fn render_item(
+    output: &mut String,
+    todo: &Todo,
+    depth: usize,
+    children: &HashMap<&str, Vec<&Todo>>,
+    tag_names: &HashMap<String, String>,
+    visited: &mut HashSet<String>,
+) {
+    if !visited.insert(todo.id.clone()) {
+        return;
+    }
+
+    let mut line = format!(
+        "{}- [{}] {}",
+        "  ".repeat(depth),
+        if todo.completed { "x" } else { " " },
+        todo.title.split_whitespace().collect::<Vec<_>>().join(" ")
+    );
+    if let Some(priority) = todo.priority.as_deref().filter(|p| !p.is_empty()) {
+        line.push_str(&format!(" !{}", priority));
+    }
+    for tag_id in &todo.tags {
+        if let Some(name) = tag_names.get(tag_id) {
+            line.push_str(&format!(" #{}", tag_word(name)));
+        }
+    }
+    if let Some(due_date) = todo.due_date.as_deref().filter(|d| !d.is_empty()) {
+        line.push_str(&format!(" due:{}", due_date));
+    }
+    output.push_str(&line);
+    output.push('\n');
+
+    for child in children.get(todo.id.as_str()).into_iter().flatten() {
+        render_item(output, child, depth + 1, children, tag_names, visited);
+    }
+}
+
+/// 生成 Markdown 任务列表
+///
+/// todos 中的任务及其已加载的子任务按 parent_id 组织为嵌套列表，
+/// 父任务不在列表中的任务作为顶层项
+pub fn render(todos: &[Todo], tag_names: &HashMap<String, String>) -> String {
+    let mut nodes: Vec<&Todo> = Vec::new();
+    let mut seen = HashSet::new();
+    for todo in todos
+        .iter()
+        .chain(todos.iter().flat_map(|t| t.subtasks.iter()))
+    {
+        if seen.insert(todo.id.as_str()) {
+            nodes.push(todo);
+        }
+    }
+
+    let mut children: HashMap<&str, Vec<&Todo>> = HashMap::new();
+    for todo in &nodes {
+        if let Some(parent_id) = todo.parent_id.as_deref().filter(|p| seen.contains(p)) {
+            children.entry(parent_id).or_default().push(todo);
+        }
+    }
+
+    let mut output = String::new();
+    let mut visited = HashSet::new();
+    for todo in &nodes {
+        let is_root = todo
+            .parent_id
+            .as_deref()
+            .is_none_or(|parent_id| !seen.contains(parent_id));
+        if is_root {
+            render_item(&mut output, todo, 0, &children, tag_names, &mut visited);
+        }
+    }
+    // 父级循环引用的任务不会从顶层到达，附加在末尾
+    for todo in &nodes {
+        render_item(&mut output, todo, 0, &children, tag_names, &mut visited);
+    }
+
+    output
+}
+
+// ============================================================================
+// 读取
+// ============================================================================
+
+/// 解析列表项标记后的任务框，返回 (缩进宽度, 是否完成, 正文)
+fn parse_task_line(line: &str) -> Option<(usize, bool, &str)> {
+    let content = line.trim_start();
+    let indent: usize = line[..line.len() - content.len()]
+        .chars()
+        .map(|c| if c == '\t' { 4 } else { 1 })
+        .sum();
+
+    let rest = if let Some(rest) = content
+        .strip_prefix("- ")
+        .or_else(|| content.strip_prefix("* "))
+        .or_else(|| content.strip_prefix("+ "))
+    {
+        rest
+    } else {
+        // 有序列表：1. 或 1)
+        let digits = content.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        content[digits..]
+            .strip_prefix(". ")
+            .or_else(|| content[digits..].strip_prefix(") "))?
+    };
+
+    let rest = rest.trim_start();
+    let checked = match rest.get(..3)? {
+        "[ ]" => false,
+        "[x]" | "[X]" => true,
+        _ => return None,
+    };
+    let text = &rest[3..];
+    if !text.is_empty() && !text.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some((indent, checked, text.trim()))
+}
+
+/// 正文 -> 草稿，提取其中的 !优先级、#标签 和 due:日期
+fn draft_from_text(text: &str, checked: bool, line_number: usize) -> TodoDraft {
+    let mut words = Vec::new();
+    let mut draft = TodoDraft::new(String::new());
+
+    for word in text.split_whitespace() {
+        if let Some(priority) = word.strip_prefix('!') {
+            if matches!(priority, "high" | "medium" | "low") {
+                draft.priority = Some(priority.to_string());
+                continue;
+            }
+        }
+        if let Some(tag) = word.strip_prefix('#') {
+            // #123 通常是 issue / PR 编号
+            if !tag.is_empty() && !tag.chars().all(|c| c.is_ascii_digit()) {
+                draft.tag_names.push(tag.to_string());
+                continue;
+            }
+        }
+        if let Some(date) = word.strip_prefix("due:") {
+            if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_ok() {
+                draft.due_date = Some(date.to_string());
+            } else {
+                draft
+                    .notes
+                    .push(format!("Line {}: invalid due date {}", line_number, date));
+            }
+            continue;
+        }
+        words.push(word);
+    }
+
+    draft.title = words.join(" ");
+    if checked {
+        draft.status = "completed".to_string();
+        draft.completed = true;
+        draft.progress = 100;
+    }
+    draft
+}
+
+/// 解析 Markdown 任务列表
+///
+/// 每个任务分配新ID，子任务的 parent_id 指向缩进更少的上一个任务
+pub fn parse(content: &str) -> Vec<Result<TodoDraft, EntryError>> {
+    let mut items = Vec::new();
+    // (缩进宽度, 任务ID)；解析失败的任务不入栈，其子任务挂到更上一级
+    let mut stack: Vec<(usize, String)> = Vec::new();
+    let mut in_fence = false;
+
+    for (number, line) in content.lines().enumerate() {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+        let Some((indent, checked, text)) = parse_task_line(line) else {
+            continue;
+        };
+
+        while stack.last().is_some_and(|(level, _)| *level >= indent) {
+            stack.pop();
+        }
+
+        let mut draft = draft_from_text(text, checked, number + 1);
+        if draft.title.is_empty() {
+            items.push(Err(EntryError {
+                title: None,
+                message: format!("Line {}: task has no title", number + 1),
+            }));
+            continue;
+        }
+
+        let id = uuid::Uuid::new_v4().to_string();
+        draft.id = Some(id.clone());
+        draft.parent_id = stack.last().map(|(_, parent_id)| parent_id.clone());
+        stack.push((indent, id));
+        items.push(Ok(draft));
+    }
+
+    items
+}
@@ -0,0 +1,139 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/csv.rs - CSV 导出
+ * @author Noah
+ * @description 将 TODO 及工时数据导出为电子表格可直接打开的 CSV
+ * @created 2026-02-16
+ * @version 1.0.0
+ *
+ * 输出约定 (RFC 4180):
+ * - 以 UTF-8 BOM 开头，Excel 据此识别编码，中文不会乱码
+ * - 行以 CRLF 结尾，首行为列标题
+ * - 以 = + - @ 制表符或回车开头的字段前加 '，避免被电子表格当作公式执行；
+ *   以 + - 开头的数字除外
+ * - 含逗号、引号或换行的字段用双引号包裹，内部引号写为 ""
+ * - 时间为 UTC，格式 YYYY-MM-DD HH:MM:SS；工时保留原始小数
+ */
+use crate::models::{Todo, TodoCsvColumn};
+use std::collections::HashMap;
+
+const BOM: char = '\u{feff}';
+
+fn header(column: TodoCsvColumn) -> &'static str {
+    match column {
+        TodoCsvColumn::Id => "ID",
+        TodoCsvColumn::Title => "Title",
+        TodoCsvColumn::Description => "Description",
+        TodoCsvColumn::Status => "Status",
+        TodoCsvColumn::Priority => "Priority",
+        TodoCsvColumn::DueDate => "Due Date",
+        TodoCsvColumn::EstimatedHours => "Estimated Hours",
+        TodoCsvColumn::ActualHours => "Actual Hours",
+        TodoCsvColumn::Progress => "Progress (%)",
+        TodoCsvColumn::Assignee => "Assignee",
+        TodoCsvColumn::Project => "Project",
+        TodoCsvColumn::Tags => "Tags",
+        TodoCsvColumn::ParentId => "Parent ID",
+        TodoCsvColumn::Completed => "Completed",
+        TodoCsvColumn::CompletedAt => "Completed At",
+        TodoCsvColumn::CreatedAt => "Created At",
+        TodoCsvColumn::UpdatedAt => "Updated At",
+        TodoCsvColumn::ArchivedAt => "Archived At",
+    }
+}
+
+/// 按 RFC 4180 转义单个字段，公式前缀字符先加 ' 中和（OWASP CSV Injection）
+///
+/// 以 + 或 - 开头的数字（如 -5）原样保留
+fn escape_field(value: &str) -> String {
+    let formula = value.starts_with(['=', '@', '\t', '\r'])
+        || (value.starts_with(['+', '-']) && value.parse::<f64>().is_err());
+    let value = if formula {
+        format!("'{}", value)
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
+}
+
+fn format_timestamp(timestamp_millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_millis)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_default()
+}
+
+fn cell(
+    todo: &Todo,
+    column: TodoCsvColumn,
+    tag_names: &HashMap<String, String>,
+    project_names: &HashMap<String, String>,
+) -> String {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
+    let hours = |value: Option<f64>| value.map(|h| h.to_string()).unwrap_or_default();
+
+    match column {
+        TodoCsvColumn::Id => todo.id.clone(),
+        TodoCsvColumn::Title => todo.title.clone(),
+        TodoCsvColumn::Description => text(&todo.description),
+        TodoCsvColumn::Status => todo.status.clone(),
+        TodoCsvColumn::Priority => text(&todo.priority),
+        TodoCsvColumn::DueDate => text(&todo.due_date),
+        TodoCsvColumn::EstimatedHours => hours(todo.estimated_hours),
+        TodoCsvColumn::ActualHours => hours(todo.actual_hours),
+        TodoCsvColumn::Progress => todo.progress.to_string(),
+        TodoCsvColumn::Assignee => text(&todo.assignee),
+        TodoCsvColumn::Project => todo
+            .project_id
+            .as_ref()
+            .and_then(|id| project_names.get(id))
+            .cloned()
+            .unwrap_or_default(),
+        TodoCsvColumn::Tags => todo
+            .tags
+            .iter()
+            .filter_map(|id| tag_names.get(id))
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", "),
+        TodoCsvColumn::ParentId => text(&todo.parent_id),
+        TodoCsvColumn::Completed => todo.completed.to_string(),
+        TodoCsvColumn::CompletedAt => todo.completed_at.map(format_timestamp).unwrap_or_default(),
+        TodoCsvColumn::CreatedAt => format_timestamp(todo.created_at),
+        TodoCsvColumn::UpdatedAt => format_timestamp(todo.updated_at),
+        TodoCsvColumn::ArchivedAt => todo.archived_at.map(format_timestamp).unwrap_or_default(),
+    }
+}
+
+/// 生成 CSV 内容，每个任务一行，列顺序与 columns 一致
+pub fn render(
+    todos: &[Todo],
+    columns: &[TodoCsvColumn],
+    tag_names: &HashMap<String, String>,
+    project_names: &HashMap<String, String>,
+) -> String {
+    let mut output = String::new();
+    output.push(BOM);
+
+    let headers: Vec<String> = columns
+        .iter()
+        .map(|column| escape_field(header(*column)))
+        .collect();
+    output.push_str(&headers.join(","));
+    output.push_str("\r\n");
+
+    for todo in todos {
+        let fields: Vec<String> = columns
+            .iter()
+            .map(|column| escape_field(&cell(todo, *column, tag_names, project_names)))
+            .collect();
+        output.push_str(&fields.join(","));
+        output.push_str("\r\n");
+    }
+
+    output
+}
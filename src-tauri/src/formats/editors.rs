@@ -0,0 +1,158 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/editors.rs - 编辑器代码片段集合导入
+ * @author Noah
+ * @description 从文件或目录中收集 Sublime、UltiSnips、SnipMate、yasnippet 片段
+ * @created 2026-02-11
+ * @version 1.0.0
+ *
+ * 目录约定:
+ * - Sublime: 任意层级下的 *.sublime-snippet 文件
+ * - UltiSnips / SnipMate: <filetype>.snippets、<filetype>_*.snippets、
+ *   <filetype>-*.snippets 或 <filetype> 目录下的 .snippets，filetype 决定语言
+ * - yasnippet: <mode>-mode/ 目录下的片段文件，其中的子目录作为分组
+ *
+ * 单个文件读取或解析失败时记录为一条错误，不影响其他文件。
+ */
+use super::{
+    from_vim_filetype, list_files, relative_dirs, snipmate, sublime, ultisnips, yasnippet,
+    EntryError,
+};
+use crate::models::{EditorSnippetFormat, SnippetDraft};
+use std::path::Path;
+
+fn extension_is(path: &Path, extension: &str) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some(extension)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Vim .snippets 文件的 filetype：优先使用所在子目录，否则取文件名中第一个 _ 或 - 之前的部分
+fn vim_filetype(root: &Path, path: &Path) -> String {
+    if let Some(dir) = relative_dirs(root, path).last() {
+        // 插件默认的片段目录本身不是 filetype
+        if dir != "UltiSnips" && dir != "snippets" {
+            return dir.clone();
+        }
+    }
+    let stem = file_stem(path);
+    stem.split(['_', '-'])
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// 判断文件是否属于所选格式
+fn is_snippet_file(format: EditorSnippetFormat, path: &Path) -> bool {
+    match format {
+        EditorSnippetFormat::Sublime => extension_is(path, "sublime-snippet"),
+        EditorSnippetFormat::UltiSnips | EditorSnippetFormat::SnipMate => {
+            extension_is(path, "snippets")
+        }
+        // yasnippet 目录中的 .el 为辅助脚本，~ 结尾为编辑器备份文件
+        EditorSnippetFormat::Yasnippet => {
+            !extension_is(path, "el") && !path.to_string_lossy().ends_with('~')
+        }
+    }
+}
+
+/// 解析单个文件，返回其中的片段
+fn parse_file(
+    format: EditorSnippetFormat,
+    root: &Path,
+    path: &Path,
+    content: &str,
+) -> Result<Vec<Result<SnippetDraft, EntryError>>, String> {
+    match format {
+        EditorSnippetFormat::Sublime => Ok(vec![Ok(sublime::parse(content, &file_stem(path))?)]),
+        EditorSnippetFormat::UltiSnips => Ok(ultisnips::parse(
+            content,
+            &from_vim_filetype(&vim_filetype(root, path)),
+        )),
+        EditorSnippetFormat::SnipMate => Ok(snipmate::parse(
+            content,
+            &from_vim_filetype(&vim_filetype(root, path)),
+        )),
+        EditorSnippetFormat::Yasnippet => {
+            // 取最近的 <mode>-mode 祖先目录，导入单个文件时也能确定语言和分组
+            let dirs: Vec<String> = path
+                .parent()
+                .map(|parent| {
+                    parent
+                        .components()
+                        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default();
+            let Some(mode_index) = dirs.iter().rposition(|d| d.ends_with("-mode")) else {
+                return Err("File is not inside a <mode>-mode directory".to_string());
+            };
+            let file_name = path
+                .file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            Ok(vec![Ok(yasnippet::parse(
+                content,
+                &dirs[mode_index],
+                &dirs[mode_index + 1..],
+                &file_name,
+            )?)])
+        }
+    }
+}
+
+/// 从文件或目录收集片段
+///
+/// 只有路径本身无法访问时才返回错误；单个文件的错误以 EntryError 返回，
+/// title 为相对路径
+pub fn collect(
+    format: EditorSnippetFormat,
+    path: &Path,
+) -> Result<Vec<Result<SnippetDraft, EntryError>>, String> {
+    let metadata =
+        std::fs::metadata(path).map_err(|e| format!("Cannot access {}: {}", path.display(), e))?;
+
+    let (root, files) = if metadata.is_dir() {
+        let mut files = Vec::new();
+        list_files(path, &mut files)?;
+        files.retain(|file| is_snippet_file(format, file));
+        (path.to_path_buf(), files)
+    } else {
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        (root, vec![path.to_path_buf()])
+    };
+
+    let mut results = Vec::new();
+    for file in files {
+        let display = file
+            .strip_prefix(&root)
+            .unwrap_or(&file)
+            .to_string_lossy()
+            .into_owned();
+
+        let parsed = std::fs::read_to_string(&file)
+            .map_err(|e| format!("Failed to read file: {}", e))
+            .and_then(|content| parse_file(format, &root, &file, &content));
+
+        match parsed {
+            Ok(items) => results.extend(items.into_iter().map(|item| {
+                item.map_err(|error| EntryError {
+                    title: error.title,
+                    message: format!("{}: {}", display, error.message),
+                })
+            })),
+            Err(message) => results.push(Err(EntryError {
+                title: Some(display),
+                message,
+            })),
+        }
+    }
+
+    Ok(results)
+}
@@ -0,0 +1,151 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/gist.rs - GitHub Gist 导出导入
+ * @author Noah
+ * @description 解析 GitHub Gist API 格式的 JSON（单个 gist 或 gist 数组）
+ * @created 2026-02-13
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - 单文件 gist: 描述 -> 标题与描述（缺省时使用文件名）
+ * - 多文件 gist: 以描述（或 gist ID）作为文件夹，每个文件一个代码片段
+ * - files[].language -> 语言
+ * - created_at / updated_at (ISO 8601) -> 保留原始时间戳
+ * - files[].content 缺失（列表接口或被截断的文件）时无法导入，在报告中说明
+ */
+use super::EntryError;
+use crate::models::SnippetDraft;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize)]
+struct GistFile {
+    #[serde(default)]
+    filename: Option<String>,
+    #[serde(default)]
+    language: Option<String>,
+    #[serde(default)]
+    content: Option<String>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct Gist {
+    id: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    public: Option<bool>,
+    files: BTreeMap<String, GistFile>,
+    #[serde(default)]
+    created_at: Option<String>,
+    #[serde(default)]
+    updated_at: Option<String>,
+}
+
+/// GitHub Linguist 语言名 -> 应用语言标识
+fn from_linguist_language(language: &str) -> String {
+    match language.to_lowercase().as_str() {
+        "c++" => "cpp".to_string(),
+        "c#" => "csharp".to_string(),
+        "shell" | "bash" => "shell".to_string(),
+        "objective-c" => "objective-c".to_string(),
+        "text" | "" => "text".to_string(),
+        other => other.replace(' ', "-"),
+    }
+}
+
+fn timestamp(value: Option<&str>) -> Option<i64> {
+    value
+        .and_then(|v| chrono::DateTime::parse_from_rfc3339(v).ok())
+        .map(|t| t.timestamp_millis())
+}
+
+/// 解析 Gist JSON，顶层可以是单个 gist 对象或 gist 数组
+pub fn parse(content: &str) -> Result<Vec<Result<SnippetDraft, EntryError>>, String> {
+    let value: Value =
+        serde_json::from_str(content).map_err(|e| format!("Invalid Gist export: {}", e))?;
+    let values = match value {
+        Value::Array(items) => items,
+        Value::Object(_) => vec![value],
+        _ => return Err("Invalid Gist export: expected an object or an array".to_string()),
+    };
+
+    let mut items = Vec::new();
+    for value in values {
+        let id = value.get("id").and_then(Value::as_str).map(str::to_string);
+        let gist: Gist = match serde_json::from_value(value) {
+            Ok(gist) => gist,
+            Err(e) => {
+                items.push(Err(EntryError {
+                    title: id,
+                    message: format!("Invalid gist: {}", e),
+                }));
+                continue;
+            }
+        };
+
+        let description = gist.description.clone().unwrap_or_default();
+        let description = description.trim();
+        let created_at = timestamp(gist.created_at.as_deref());
+        let updated_at = timestamp(gist.updated_at.as_deref());
+        let single = gist.files.len() == 1;
+        let folder_path = if single {
+            Vec::new()
+        } else if description.is_empty() {
+            vec![format!("gist-{}", gist.id)]
+        } else {
+            vec![description.to_string()]
+        };
+
+        if gist.files.is_empty() {
+            items.push(Err(EntryError {
+                title: Some(gist.id.clone()),
+                message: "Gist has no files".to_string(),
+            }));
+        }
+
+        for (key, file) in gist.files {
+            let filename = file.filename.unwrap_or(key);
+            let Some(code) = file.content.filter(|_| !file.truncated) else {
+                items.push(Err(EntryError {
+                    title: Some(filename),
+                    message: format!(
+                        "Gist {}: file content is missing or truncated; export each gist individually",
+                        gist.id
+                    ),
+                }));
+                continue;
+            };
+
+            let title = if single && !description.is_empty() {
+                description.to_string()
+            } else {
+                filename.clone()
+            };
+            let language = from_linguist_language(file.language.as_deref().unwrap_or(""));
+
+            let mut draft = SnippetDraft::new(title, code, language);
+            draft.description = description.to_string();
+            draft.folder_path = folder_path.clone();
+            draft.created_at = created_at;
+            draft.updated_at = updated_at;
+            if single && !description.is_empty() {
+                draft
+                    .notes
+                    .push(format!("File name {} not preserved", filename));
+            }
+            if gist.public == Some(false) {
+                draft
+                    .notes
+                    .push("Secret gist visibility not preserved".to_string());
+            }
+            items.push(Ok(draft));
+        }
+    }
+
+    Ok(items)
+}
@@ -0,0 +1,152 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/html.rs - HTML 导出
+ * @author Noah
+ * @description 将代码片段渲染为单个自包含、带语法高亮的 HTML 页面
+ * @created 2026-02-06
+ * @version 1.0.0
+ *
+ * 功能特性:
+ * - 使用 syntect 在 Rust 端完成语法高亮（内联样式，无外部依赖）
+ * - 包含标题、描述、标签和语言等元数据
+ * - 可选目录 (Table of Contents)
+ */
+use super::escape_html;
+use crate::models::{CodeSnippet, HtmlExportOptions};
+use std::sync::OnceLock;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::html::highlighted_html_for_string;
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+const DEFAULT_TITLE: &str = "SnippetsHub Export";
+const DEFAULT_THEME: &str = "InspiredGitHub";
+
+static SYNTAX_SET: OnceLock<SyntaxSet> = OnceLock::new();
+static THEME_SET: OnceLock<ThemeSet> = OnceLock::new();
+
+fn syntax_set() -> &'static SyntaxSet {
+    SYNTAX_SET.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn theme_set() -> &'static ThemeSet {
+    THEME_SET.get_or_init(ThemeSet::load_defaults)
+}
+
+/// 可用的高亮主题名称
+pub fn available_themes() -> Vec<String> {
+    theme_set().themes.keys().cloned().collect()
+}
+
+/// 将应用内的语言标识映射为 syntect 的语法定义
+fn find_syntax(language: &str) -> &'static SyntaxReference {
+    let ss = syntax_set();
+    let token = match language {
+        // 前端使用的语言名与 syntect 的标识不同；没有对应语法的语言按纯文本显示
+        "typescript" | "tsx" | "jsx" => "js",
+        "vue" | "svelte" => "html",
+        "shell" | "bash" | "zsh" => "sh",
+        "csharp" => "cs",
+        "c++" => "cpp",
+        other => other,
+    };
+
+    ss.find_syntax_by_token(token)
+        .unwrap_or_else(|| ss.find_syntax_plain_text())
+}
+
+fn highlight(code: &str, language: &str, theme: &Theme) -> String {
+    highlighted_html_for_string(code, syntax_set(), find_syntax(language), theme)
+        .unwrap_or_else(|_| format!("<pre><code>{}</code></pre>", escape_html(code)))
+}
+
+fn format_timestamp(millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(millis)
+        .map(|dt| dt.format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// 渲染 HTML 页面
+pub fn render(snippets: &[CodeSnippet], options: &HtmlExportOptions) -> Result<String, String> {
+    let theme_name = options.theme.as_deref().unwrap_or(DEFAULT_THEME);
+    let theme = theme_set()
+        .themes
+        .get(theme_name)
+        .ok_or_else(|| format!("Unknown highlight theme: {}", theme_name))?;
+    let title = options.title.as_deref().unwrap_or(DEFAULT_TITLE);
+
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(title)));
+    html.push_str("<style>\n");
+    html.push_str(STYLE);
+    html.push_str("</style>\n</head>\n<body>\n<main>\n");
+
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(title)));
+    html.push_str(&format!(
+        "<p class=\"meta\">{} snippets &middot; exported {}</p>\n",
+        snippets.len(),
+        chrono::Utc::now().format("%Y-%m-%d %H:%M UTC")
+    ));
+
+    if options.include_toc && !snippets.is_empty() {
+        html.push_str("<nav class=\"toc\">\n<h2>Contents</h2>\n<ol>\n");
+        for snippet in snippets {
+            html.push_str(&format!(
+                "<li><a href=\"#snippet-{}\">{}</a> <span class=\"lang\">{}</span></li>\n",
+                escape_html(&snippet.id),
+                escape_html(&snippet.title),
+                escape_html(&snippet.language)
+            ));
+        }
+        html.push_str("</ol>\n</nav>\n");
+    }
+
+    for snippet in snippets {
+        html.push_str(&format!(
+            "<section class=\"snippet\" id=\"snippet-{}\">\n",
+            escape_html(&snippet.id)
+        ));
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&snippet.title)));
+        html.push_str(&format!(
+            "<p class=\"meta\"><span class=\"lang\">{}</span> &middot; updated {}</p>\n",
+            escape_html(&snippet.language),
+            format_timestamp(snippet.updated_at)
+        ));
+
+        if !snippet.description.is_empty() {
+            html.push_str(&format!(
+                "<p class=\"description\">{}</p>\n",
+                escape_html(&snippet.description)
+            ));
+        }
+
+        if !snippet.tags.is_empty() {
+            html.push_str("<ul class=\"tags\">");
+            for tag in &snippet.tags {
+                html.push_str(&format!("<li>{}</li>", escape_html(tag)));
+            }
+            html.push_str("</ul>\n");
+        }
+
+        html.push_str(&highlight(&snippet.code, &snippet.language, theme));
+        html.push_str("</section>\n");
+    }
+
+    html.push_str("</main>\n</body>\n</html>\n");
+    Ok(html)
+}
+
+const STYLE: &str = "body { margin: 0; background: #f6f7f9; color: #1f2328; font: 15px/1.6 -apple-system, BlinkMacSystemFont, \"Segoe UI\", \"PingFang SC\", \"Microsoft YaHei\", sans-serif; }
+main { max-width: 960px; margin: 0 auto; padding: 32px 24px; }
+h1 { margin-bottom: 4px; }
+.meta { color: #656d76; font-size: 13px; margin-top: 0; }
+.toc { background: #fff; border: 1px solid #d0d7de; border-radius: 8px; padding: 8px 24px; margin: 24px 0; }
+.toc h2 { font-size: 16px; }
+.snippet { background: #fff; border: 1px solid #d0d7de; border-radius: 8px; padding: 4px 24px 16px; margin: 24px 0; }
+.lang { display: inline-block; background: #eef1f4; border-radius: 4px; padding: 0 6px; font-size: 12px; }
+.tags { list-style: none; padding: 0; margin: 8px 0; }
+.tags li { display: inline-block; background: #ddf4ff; color: #0969da; border-radius: 12px; padding: 0 10px; margin-right: 6px; font-size: 12px; }
+pre { padding: 12px 16px; border-radius: 6px; overflow-x: auto; font: 13px/1.5 \"JetBrains Mono\", Consolas, Menlo, monospace; }
+";
@@ -0,0 +1,550 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/ical.rs - iCalendar VTODO 格式
+ * @author Noah
+ * @description TODO 任务与 iCalendar (RFC 5545) VTODO 组件之间的转换
+ * @created 2026-02-14
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - id <-> UID
+ * - title / description <-> SUMMARY / DESCRIPTION
+ * - status <-> STATUS (todo: NEEDS-ACTION, in_progress: IN-PROCESS, completed: COMPLETED)，
+ *   blocked 没有对应值，额外写入 X-SNIPPETSHUB-STATUS
+ * - archived <-> STATUS:CANCELLED（仅导入）
+ * - priority <-> PRIORITY (high: 1, medium: 5, low: 9)
+ * - due_date <-> DUE;VALUE=DATE；导入带时间的 DUE 时按 TZID（或 UTC、本地浮动时间）
+ *   换算到本地时区后取日期
+ * - progress <-> PERCENT-COMPLETE
+ * - recurring_config {"rrule": "..."} <-> RRULE
+ * - parent_id <-> RELATED-TO;RELTYPE=PARENT
+ * - dependencies <-> RELATED-TO;RELTYPE=DEPENDS-ON (RFC 9253)
+ * - 标签名称 <-> CATEGORIES
+ * - created_at / updated_at <-> CREATED / LAST-MODIFIED
+ */
+use super::EntryError;
+use crate::models::{Todo, TodoDraft};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use std::collections::HashMap;
+
+const PRODUCT_ID: &str = "-//SnippetsHub//Todos//EN";
+const STATUS_EXTENSION: &str = "X-SNIPPETSHUB-STATUS";
+/// 单行最大长度（字节），超出部分折行
+const LINE_LIMIT: usize = 75;
+
+// ============================================================================
+// 写入
+// ============================================================================
+
+/// 转义 TEXT 类型的属性值
+fn escape_text(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 按 75 字节折行，不截断 UTF-8 字符，行尾使用 CRLF
+fn push_line(output: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > LINE_LIMIT {
+            output.push_str("\r\n ");
+            // 续行开头的空格计入长度
+            width = 1;
+        }
+        output.push(c);
+        width += c.len_utf8();
+    }
+    output.push_str("\r\n");
+}
+
+fn format_timestamp(timestamp_millis: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp_millis(timestamp_millis)
+        .map(|t| t.format("%Y%m%dT%H%M%SZ").to_string())
+}
+
+/// 从 recurring_config 中提取 RRULE
+///
+/// 支持 {"rrule": "FREQ=..."} 以及 {"frequency": "weekly", "interval": 2, "count": 5, "until": "2026-12-31"}
+fn recurrence_rule(config: &str) -> Option<String> {
+    let value: serde_json::Value = serde_json::from_str(config).ok()?;
+    if let Some(rule) = value.get("rrule").and_then(|v| v.as_str()) {
+        return Some(rule.trim_start_matches("RRULE:").to_string());
+    }
+
+    let frequency = value
+        .get("frequency")
+        .or_else(|| value.get("freq"))
+        .and_then(|v| v.as_str())?
+        .to_uppercase();
+    if !["DAILY", "WEEKLY", "MONTHLY", "YEARLY"].contains(&frequency.as_str()) {
+        return None;
+    }
+    let mut rule = format!("FREQ={}", frequency);
+    if let Some(interval) = value.get("interval").and_then(|v| v.as_u64()) {
+        rule.push_str(&format!(";INTERVAL={}", interval));
+    }
+    if let Some(count) = value.get("count").and_then(|v| v.as_u64()) {
+        rule.push_str(&format!(";COUNT={}", count));
+    }
+    if let Some(until) = value.get("until").and_then(|v| v.as_str()) {
+        rule.push_str(&format!(";UNTIL={}", until.replace('-', "")));
+    }
+    Some(rule)
+}
+
+fn to_ical_priority(priority: &str) -> Option<u8> {
+    match priority {
+        "high" => Some(1),
+        "medium" => Some(5),
+        "low" => Some(9),
+        _ => None,
+    }
+}
+
+/// 生成包含所有 TODO 的 VCALENDAR
+///
+/// tag_names 为标签ID到名称的映射，用于生成 CATEGORIES
+pub fn render(todos: &[Todo], tag_names: &HashMap<String, String>) -> String {
+    let mut output = String::new();
+    push_line(&mut output, "BEGIN:VCALENDAR");
+    push_line(&mut output, "VERSION:2.0");
+    push_line(&mut output, &format!("PRODID:{}", PRODUCT_ID));
+    let now = format_timestamp(chrono::Utc::now().timestamp_millis()).unwrap_or_default();
+
+    for todo in todos {
+        push_line(&mut output, "BEGIN:VTODO");
+        push_line(&mut output, &format!("UID:{}", escape_text(&todo.id)));
+        push_line(&mut output, &format!("DTSTAMP:{}", now));
+        push_line(
+            &mut output,
+            &format!("SUMMARY:{}", escape_text(&todo.title)),
+        );
+        if let Some(description) = todo.description.as_deref().filter(|d| !d.is_empty()) {
+            push_line(
+                &mut output,
+                &format!("DESCRIPTION:{}", escape_text(description)),
+            );
+        }
+
+        let status = match todo.status.as_str() {
+            _ if todo.completed => "COMPLETED",
+            "in_progress" => "IN-PROCESS",
+            "completed" => "COMPLETED",
+            _ => "NEEDS-ACTION",
+        };
+        push_line(&mut output, &format!("STATUS:{}", status));
+        if todo.status == "blocked" {
+            push_line(&mut output, &format!("{}:blocked", STATUS_EXTENSION));
+        }
+
+        if let Some(priority) = todo.priority.as_deref().and_then(to_ical_priority) {
+            push_line(&mut output, &format!("PRIORITY:{}", priority));
+        }
+        if let Some(due) = todo.due_date.as_deref().filter(|d| !d.is_empty()) {
+            let date: String = due.chars().filter(|c| c.is_ascii_digit()).take(8).collect();
+            if date.len() == 8 {
+                push_line(&mut output, &format!("DUE;VALUE=DATE:{}", date));
+            }
+        }
+        if todo.progress > 0 {
+            push_line(
+                &mut output,
+                &format!("PERCENT-COMPLETE:{}", todo.progress.clamp(0, 100)),
+            );
+        }
+        if let Some(rule) = todo.recurring_config.as_deref().and_then(recurrence_rule) {
+            push_line(&mut output, &format!("RRULE:{}", rule));
+        }
+        if let Some(parent) = &todo.parent_id {
+            push_line(
+                &mut output,
+                &format!("RELATED-TO;RELTYPE=PARENT:{}", escape_text(parent)),
+            );
+        }
+        for dependency in &todo.dependencies {
+            push_line(
+                &mut output,
+                &format!("RELATED-TO;RELTYPE=DEPENDS-ON:{}", escape_text(dependency)),
+            );
+        }
+
+        let categories: Vec<String> = todo
+            .tags
+            .iter()
+            .filter_map(|id| tag_names.get(id))
+            .map(|name| escape_text(name))
+            .collect();
+        if !categories.is_empty() {
+            push_line(&mut output, &format!("CATEGORIES:{}", categories.join(",")));
+        }
+
+        if let Some(created) = format_timestamp(todo.created_at) {
+            push_line(&mut output, &format!("CREATED:{}", created));
+        }
+        if let Some(modified) = format_timestamp(todo.updated_at) {
+            push_line(&mut output, &format!("LAST-MODIFIED:{}", modified));
+        }
+        push_line(&mut output, "END:VTODO");
+    }
+
+    push_line(&mut output, "END:VCALENDAR");
+    output
+}
+
+// ============================================================================
+// 读取
+// ============================================================================
+
+/// 内容行：名称、参数和值
+struct ContentLine {
+    name: String,
+    params: Vec<(String, String)>,
+    value: String,
+}
+
+impl ContentLine {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// 展开折行（CRLF 或 LF 后跟空格/制表符）
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in content.split('\n') {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        match (raw.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(rest), Some(last)) => last.push_str(rest),
+            _ if raw.is_empty() => {}
+            _ => lines.push(raw.to_string()),
+        }
+    }
+    lines
+}
+
+/// 解析内容行，参数值中的引号内可以包含 : ; ,
+fn parse_line(line: &str) -> Option<ContentLine> {
+    let mut in_quotes = false;
+    let mut separators = Vec::new();
+    let mut colon = None;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => separators.push(i),
+            ':' if !in_quotes => {
+                colon = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let colon = colon?;
+    let head = &line[..colon];
+    let name_end = separators.first().copied().unwrap_or(colon);
+
+    let mut params = Vec::new();
+    let mut bounds = separators.clone();
+    bounds.push(colon);
+    for window in bounds.windows(2) {
+        let param = &head[window[0] + 1..window[1]];
+        if let Some((key, value)) = param.split_once('=') {
+            params.push((key.to_string(), value.trim_matches('"').to_string()));
+        }
+    }
+
+    Some(ContentLine {
+        name: head[..name_end].to_uppercase(),
+        params,
+        value: line[colon + 1..].to_string(),
+    })
+}
+
+fn unescape_text(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') | Some('N') => output.push('\n'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+    output
+}
+
+/// 拆分以逗号分隔的 TEXT 列表（如 CATEGORIES），转义的逗号不拆分
+fn split_text_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut escaped = false;
+    for c in value.chars() {
+        if escaped {
+            current.push('\\');
+            current.push(c);
+            escaped = false;
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == ',' {
+            items.push(unescape_text(&current));
+            current.clear();
+        } else {
+            current.push(c);
+        }
+    }
+    items.push(unescape_text(&current));
+    items
+        .into_iter()
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+/// 解析 DATE-TIME 为时刻：Z 结尾为 UTC，带 TZID 参数时按该时区，否则为本地浮动时间
+///
+/// 无法识别的 TZID（如 Outlook 使用的 Windows 时区名）按本地时间处理并记入 notes
+fn parse_date_time(line: &ContentLine, notes: &mut Vec<String>) -> Option<DateTime<Utc>> {
+    let value = line.value.trim();
+    if let Some(utc) = value.strip_suffix('Z') {
+        let time = NaiveDateTime::parse_from_str(utc, "%Y%m%dT%H%M%S").ok()?;
+        return Some(time.and_utc());
+    }
+
+    let time = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S").ok()?;
+    // 部分客户端在 TZID 前加 / 表示全局唯一的时区名
+    let zone = line.param("TZID").map(|tzid| tzid.trim_start_matches('/'));
+    let local = match zone.map(|zone| zone.parse::<Tz>()) {
+        Some(Ok(tz)) => tz
+            .from_local_datetime(&time)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc)),
+        Some(Err(_)) => {
+            notes.push(format!(
+                "Unknown time zone {} in {}, treated as local time",
+                zone.unwrap_or_default(),
+                line.name
+            ));
+            None
+        }
+        None => None,
+    };
+    local.or_else(|| {
+        Local
+            .from_local_datetime(&time)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    })
+}
+
+/// 解析 DATE 或 DATE-TIME，返回本地时区的 YYYY-MM-DD
+fn parse_date(line: &ContentLine, notes: &mut Vec<String>) -> Option<String> {
+    let value = line.value.trim();
+    let is_date = line
+        .param("VALUE")
+        .is_some_and(|v| v.eq_ignore_ascii_case("DATE"))
+        || !value.contains('T');
+    let date = if is_date {
+        chrono::NaiveDate::parse_from_str(value, "%Y%m%d").ok()?
+    } else {
+        parse_date_time(line, notes)?
+            .with_timezone(&Local)
+            .date_naive()
+    };
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// 解析 DATE-TIME，返回毫秒时间戳
+fn parse_timestamp(line: &ContentLine, notes: &mut Vec<String>) -> Option<i64> {
+    parse_date_time(line, notes).map(|t| t.timestamp_millis())
+}
+
+fn from_ical_priority(value: &str) -> Option<String> {
+    match value.trim().parse::<u8>().ok()? {
+        1..=4 => Some("high".to_string()),
+        5 => Some("medium".to_string()),
+        6..=9 => Some("low".to_string()),
+        _ => None,
+    }
+}
+
+/// 单个 VTODO 的内容行 -> TODO 草稿
+fn todo_from_lines(lines: &[ContentLine]) -> Result<TodoDraft, EntryError> {
+    let summary = lines
+        .iter()
+        .find(|l| l.name == "SUMMARY")
+        .map(|l| unescape_text(&l.value))
+        .filter(|s| !s.trim().is_empty());
+    let uid = lines
+        .iter()
+        .find(|l| l.name == "UID")
+        .map(|l| unescape_text(&l.value))
+        .filter(|s| !s.trim().is_empty());
+
+    let Some(title) = summary else {
+        return Err(EntryError {
+            title: uid,
+            message: "VTODO has no SUMMARY".to_string(),
+        });
+    };
+
+    let mut draft = TodoDraft::new(title);
+    draft.id = uid;
+    let mut extended_status = None;
+
+    for line in lines {
+        match line.name.as_str() {
+            "DESCRIPTION" => draft.description = Some(unescape_text(&line.value)),
+            "STATUS" => match line.value.trim().to_uppercase().as_str() {
+                "IN-PROCESS" => draft.status = "in_progress".to_string(),
+                "COMPLETED" => {
+                    draft.status = "completed".to_string();
+                    draft.completed = true;
+                }
+                "CANCELLED" => {
+                    draft.archived = true;
+                    draft
+                        .notes
+                        .push("Cancelled task imported as archived".to_string());
+                }
+                _ => draft.status = "todo".to_string(),
+            },
+            name if name == STATUS_EXTENSION => {
+                extended_status = Some(line.value.trim().to_string())
+            }
+            "PRIORITY" => draft.priority = from_ical_priority(&line.value),
+            "DUE" => {
+                draft.due_date = parse_date(line, &mut draft.notes);
+                if draft.due_date.is_none() {
+                    draft
+                        .notes
+                        .push(format!("Invalid DUE value {}", line.value));
+                }
+            }
+            "PERCENT-COMPLETE" => {
+                draft.progress = line.value.trim().parse::<i32>().unwrap_or(0).clamp(0, 100)
+            }
+            "RRULE" => {
+                draft.recurring_config =
+                    Some(serde_json::json!({ "rrule": line.value.trim() }).to_string())
+            }
+            "RELATED-TO" => {
+                let related = unescape_text(&line.value);
+                match line
+                    .param("RELTYPE")
+                    .unwrap_or("PARENT")
+                    .to_uppercase()
+                    .as_str()
+                {
+                    "PARENT" => draft.parent_id = Some(related),
+                    "DEPENDS-ON" | "FINISHTOSTART" => draft.dependencies.push(related),
+                    other => draft
+                        .notes
+                        .push(format!("RELATED-TO with RELTYPE {} not preserved", other)),
+                }
+            }
+            "CATEGORIES" => draft.tag_names.extend(split_text_list(&line.value)),
+            "CREATED" => draft.created_at = parse_timestamp(line, &mut draft.notes),
+            "LAST-MODIFIED" => draft.updated_at = parse_timestamp(line, &mut draft.notes),
+            "DTSTART" | "COMPLETED" | "DURATION" | "ATTENDEE" | "VALARM" => {
+                draft.notes.push(format!("{} not preserved", line.name))
+            }
+            _ => {}
+        }
+    }
+
+    if extended_status.as_deref() == Some("blocked") && !draft.completed {
+        draft.status = "blocked".to_string();
+    }
+
+    Ok(draft)
+}
+
+/// 解析 .ics 内容中的所有 VTODO，忽略 VEVENT 等其他组件
+pub fn parse(content: &str) -> Result<Vec<Result<TodoDraft, EntryError>>, String> {
+    let lines = unfold(content);
+    if !lines
+        .first()
+        .is_some_and(|l| l.trim().eq_ignore_ascii_case("BEGIN:VCALENDAR"))
+    {
+        return Err("Invalid iCalendar file: missing BEGIN:VCALENDAR".to_string());
+    }
+
+    let mut items = Vec::new();
+    // 当前 VTODO 的内容行；嵌套组件（如 VALARM）的内容行不计入
+    let mut current: Option<Vec<ContentLine>> = None;
+    let mut depth = 0;
+
+    for (number, raw) in lines.iter().enumerate() {
+        let Some(line) = parse_line(raw) else {
+            if current.is_some() {
+                items.push(Err(EntryError {
+                    title: None,
+                    message: format!("Line {}: malformed content line", number + 1),
+                }));
+                current = None;
+            }
+            continue;
+        };
+
+        match (
+            line.name.as_str(),
+            line.value.trim().to_uppercase().as_str(),
+        ) {
+            ("BEGIN", "VTODO") if current.is_none() => {
+                current = Some(Vec::new());
+                depth = 0;
+            }
+            ("BEGIN", component) if current.is_some() => {
+                depth += 1;
+                if depth == 1 {
+                    if let Some(lines) = current.as_mut() {
+                        lines.push(ContentLine {
+                            name: component.to_string(),
+                            params: Vec::new(),
+                            value: String::new(),
+                        });
+                    }
+                }
+            }
+            ("END", "VTODO") if current.is_some() && depth == 0 => {
+                if let Some(lines) = current.take() {
+                    items.push(todo_from_lines(&lines));
+                }
+            }
+            ("END", _) if current.is_some() => depth -= 1,
+            _ if depth == 0 => {
+                if let Some(lines) = current.as_mut() {
+                    lines.push(line);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if current.is_some() {
+        items.push(Err(EntryError {
+            title: None,
+            message: "VTODO is missing END:VTODO".to_string(),
+        }));
+    }
+
+    Ok(items)
+}
@@ -0,0 +1,378 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/jetbrains.rs - JetBrains Live Templates 格式
+ * @author Noah
+ * @description IntelliJ / RustRover 等 IDE 的 live template XML 的解析与生成
+ * @created 2026-02-10
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - templateSet@group <-> 文件夹
+ * - template@name (缩写) <-> trigger:<name> 标签
+ * - template@snippetsHubTitle <-> 标题（IDE 忽略此属性；其他来源的模板以描述或缩写作为标题）
+ * - template@description <-> 描述（缺省时写入标题，便于在 IDE 中识别）
+ * - template@value <-> 代码，变量 ($NAME$, $END$, $SELECTION$) 原样保留，其余 $ 转义为 $$
+ * - context/option[value=true] <-> 语言（多个上下文时取第一个可识别的）
+ * - variable 的 expression/defaultValue 无对应字段，导入时在报告中说明
+ */
+use super::{escape_xml_attr, slugify, trigger_tag, triggers, EntryError};
+use crate::models::{CodeSnippet, SnippetDraft};
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::collections::HashSet;
+
+/// 保存标题的自定义属性，IDE 会忽略未知属性
+const TITLE_ATTRIBUTE: &str = "snippetsHubTitle";
+
+/// 变量名为预定义变量，不需要生成 variable 元素
+const PREDEFINED_VARIABLES: &[&str] = &["END", "SELECTION"];
+
+/// 上下文选项名前缀 -> 应用语言标识，按顺序匹配（JAVA_SCRIPT 需在 JAVA 之前）
+const CONTEXT_LANGUAGES: &[(&str, &str)] = &[
+    ("RUST", "rust"),
+    ("JAVA_SCRIPT", "javascript"),
+    ("JAVA", "java"),
+    ("KOTLIN", "kotlin"),
+    ("PYTHON", "python"),
+    ("Python", "python"),
+    ("GO", "go"),
+    ("TypeScript", "typescript"),
+    ("TS_", "typescript"),
+    ("JS_", "javascript"),
+    ("JSX_HTML", "javascript"),
+    ("SHELL_SCRIPT", "shell"),
+    ("Bash", "shell"),
+    ("PHP", "php"),
+    ("RUBY", "ruby"),
+    ("SQL", "sql"),
+    ("HTML", "html"),
+    ("XML", "xml"),
+    ("CSS", "css"),
+    ("SCSS", "scss"),
+    ("JSON", "json"),
+    ("YAML", "yaml"),
+    ("MARKDOWN", "markdown"),
+    ("SWIFT", "swift"),
+    ("OC_", "cpp"),
+    ("C_", "c"),
+    ("CPP", "cpp"),
+    ("CSHARP", "csharp"),
+    ("DART", "dart"),
+    ("SCALA", "scala"),
+];
+
+/// 应用语言标识 -> 导出时使用的上下文选项名
+fn to_context(language: &str) -> &'static str {
+    match language {
+        "rust" => "RUST_FILE",
+        "java" => "JAVA_CODE",
+        "kotlin" => "KOTLIN",
+        "python" => "Python",
+        "go" => "GO_FILE",
+        "typescript" => "TS_STATEMENT",
+        "javascript" => "JS_STATEMENT",
+        "shell" | "bash" => "SHELL_SCRIPT",
+        "php" => "PHP",
+        "ruby" => "RUBY",
+        "sql" => "SQL",
+        "html" => "HTML",
+        "xml" => "XML",
+        "css" => "CSS",
+        "scss" => "SCSS",
+        "json" => "JSON",
+        "yaml" => "YAML",
+        "markdown" => "MARKDOWN",
+        "swift" => "SWIFT",
+        "c" | "cpp" => "OC_SOURCE",
+        "csharp" => "CSHARP",
+        "dart" => "DART",
+        "scala" => "SCALA",
+        _ => "OTHER",
+    }
+}
+
+fn from_context(option: &str) -> Option<&'static str> {
+    CONTEXT_LANGUAGES
+        .iter()
+        .find(|(prefix, _)| option.starts_with(prefix))
+        .map(|(_, language)| *language)
+}
+
+/// 解析过程中的模板
+struct TemplateState {
+    name: Option<String>,
+    value: Option<String>,
+    title: Option<String>,
+    description: String,
+    contexts: Vec<String>,
+    notes: Vec<String>,
+}
+
+fn attribute(element: &BytesStart, name: &str) -> Result<Option<String>, String> {
+    for attr in element.attributes() {
+        let attr = attr.map_err(|e| format!("Invalid attribute: {}", e))?;
+        if attr.key.as_ref() == name.as_bytes() {
+            let value = attr
+                .unescape_value()
+                .map_err(|e| format!("Invalid attribute value: {}", e))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn template_state(element: &BytesStart) -> Result<TemplateState, String> {
+    Ok(TemplateState {
+        name: attribute(element, "name")?,
+        value: attribute(element, "value")?,
+        title: attribute(element, TITLE_ATTRIBUTE)?.filter(|t| !t.trim().is_empty()),
+        description: attribute(element, "description")?.unwrap_or_default(),
+        contexts: Vec::new(),
+        notes: Vec::new(),
+    })
+}
+
+fn finish_template(
+    state: TemplateState,
+    group: &Option<String>,
+) -> Result<SnippetDraft, EntryError> {
+    let error = |message: &str| EntryError {
+        title: state.name.clone(),
+        message: message.to_string(),
+    };
+
+    let name = state
+        .name
+        .clone()
+        .filter(|n| !n.trim().is_empty())
+        .ok_or_else(|| error("Template has no name"))?;
+    let value = state
+        .value
+        .clone()
+        .ok_or_else(|| error("Template has no value"))?;
+
+    let language = state
+        .contexts
+        .iter()
+        .find_map(|c| from_context(c))
+        .unwrap_or("text");
+    let (title, description) = match state.title {
+        // 导出时描述为空会以标题代替，这里还原为空描述
+        Some(title) if title == state.description => (title, String::new()),
+        Some(title) => (title, state.description),
+        None if state.description.trim().is_empty() => (name.clone(), String::new()),
+        None => (state.description.clone(), state.description),
+    };
+
+    let mut draft = SnippetDraft::new(title, unescape_template(&value), language.to_string());
+    draft.description = description;
+    draft.tags = vec![trigger_tag(&name)];
+    draft.folder_path = group.iter().cloned().collect();
+    draft.notes = state.notes;
+    Ok(draft)
+}
+
+/// 解析 live template XML 文件
+///
+/// 支持单个 templateSet 或直接以 template 为根的片段；XML 语法错误时整个文件失败
+pub fn parse(content: &str) -> Result<Vec<Result<SnippetDraft, EntryError>>, String> {
+    let mut reader = Reader::from_str(content);
+    reader.config_mut().trim_text(true);
+
+    let mut results = Vec::new();
+    let mut group: Option<String> = None;
+    let mut current: Option<TemplateState> = None;
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            format!(
+                "Invalid live template XML at position {}: {}",
+                reader.error_position(),
+                e
+            )
+        })?;
+        let (element, is_empty) = match &event {
+            Event::Start(e) => (e, false),
+            Event::Empty(e) => (e, true),
+            Event::End(e) => {
+                if e.name().as_ref() == b"template" {
+                    if let Some(state) = current.take() {
+                        results.push(finish_template(state, &group));
+                    }
+                }
+                continue;
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        match element.name().as_ref() {
+            b"templateSet" => {
+                group = attribute(element, "group")?.filter(|g| !g.trim().is_empty());
+            }
+            b"template" => {
+                let state = template_state(element)?;
+                if is_empty {
+                    results.push(finish_template(state, &group));
+                } else {
+                    current = Some(state);
+                }
+            }
+            b"variable" => {
+                if let Some(state) = current.as_mut() {
+                    let name = attribute(element, "name")?.unwrap_or_default();
+                    let expression = attribute(element, "expression")?.unwrap_or_default();
+                    let default_value = attribute(element, "defaultValue")?.unwrap_or_default();
+                    if !expression.is_empty() || !default_value.is_empty() {
+                        state.notes.push(format!(
+                            "Variable ${}$ expression/default value not preserved",
+                            name
+                        ));
+                    }
+                }
+            }
+            b"option" => {
+                if let Some(state) = current.as_mut() {
+                    if attribute(element, "value")?.as_deref() == Some("true") {
+                        if let Some(name) = attribute(element, "name")? {
+                            state.contexts.push(name);
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(results)
+}
+
+/// text 以 $NAME$ 形式的变量开头时返回变量名
+fn variable_at(text: &str) -> Option<&str> {
+    let rest = text.strip_prefix('$')?;
+    let name = &rest[..rest.find('$')?];
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    valid.then_some(name)
+}
+
+/// 提取代码中使用的自定义变量名，按首次出现顺序
+fn variables(code: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut rest = code;
+    while let Some(start) = rest.find('$') {
+        rest = &rest[start..];
+        match variable_at(rest) {
+            Some(name) => {
+                if !PREDEFINED_VARIABLES.contains(&name) && !names.iter().any(|n| n == name) {
+                    names.push(name.to_string());
+                }
+                rest = &rest[name.len() + 2..];
+            }
+            None => rest = &rest[1..],
+        }
+    }
+    names
+}
+
+/// 代码 -> 模板文本：变量原样保留，其余的 $（如 shell、PHP 变量）转义为 $$
+fn escape_template(code: &str) -> String {
+    let mut escaped = String::with_capacity(code.len());
+    let mut rest = code;
+    while let Some(start) = rest.find('$') {
+        escaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match variable_at(rest) {
+            Some(name) => {
+                escaped.push_str(&rest[..name.len() + 2]);
+                rest = &rest[name.len() + 2..];
+            }
+            None => {
+                escaped.push_str("$$");
+                rest = &rest[1..];
+            }
+        }
+    }
+    escaped.push_str(rest);
+    escaped
+}
+
+/// 模板文本 -> 代码：$$ 还原为 $，$NAME$ 变量原样保留
+fn unescape_template(value: &str) -> String {
+    let mut code = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('$') {
+        code.push_str(&rest[..start]);
+        rest = &rest[start + 1..];
+        if let Some(after) = rest.strip_prefix('$') {
+            code.push('$');
+            rest = after;
+            continue;
+        }
+        code.push('$');
+        if let Some(end) = rest.find('$') {
+            code.push_str(&rest[..=end]);
+            rest = &rest[end + 1..];
+        }
+    }
+    code.push_str(rest);
+    code
+}
+
+/// 生成 live template XML，所有片段放在同一个 templateSet 中
+pub fn render(snippets: &[CodeSnippet], group: &str) -> String {
+    let mut used_names = HashSet::new();
+    let mut xml = format!("<templateSet group=\"{}\">\n", escape_xml_attr(group));
+
+    for snippet in snippets {
+        // 同一模板组中缩写必须唯一，重复时追加序号
+        let base = triggers(&snippet.tags)
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| slugify(&snippet.title));
+        let base = if base.is_empty() {
+            snippet.id.clone()
+        } else {
+            base
+        };
+        let mut name = base.clone();
+        let mut counter = 2;
+        while !used_names.insert(name.clone()) {
+            name = format!("{}{}", base, counter);
+            counter += 1;
+        }
+
+        let description = if snippet.description.trim().is_empty() {
+            &snippet.title
+        } else {
+            &snippet.description
+        };
+
+        xml.push_str(&format!(
+            "  <template name=\"{}\" value=\"{}\" description=\"{}\" {}=\"{}\" toReformat=\"false\" toShortenFQNames=\"true\">\n",
+            escape_xml_attr(&name),
+            escape_xml_attr(&escape_template(&snippet.code)),
+            escape_xml_attr(description),
+            TITLE_ATTRIBUTE,
+            escape_xml_attr(&snippet.title)
+        ));
+        for variable in variables(&snippet.code) {
+            xml.push_str(&format!(
+                "    <variable name=\"{}\" expression=\"\" defaultValue=\"\" alwaysStopAt=\"true\" />\n",
+                escape_xml_attr(&variable)
+            ));
+        }
+        xml.push_str("    <context>\n");
+        xml.push_str(&format!(
+            "      <option name=\"{}\" value=\"true\" />\n",
+            to_context(&snippet.language)
+        ));
+        xml.push_str("    </context>\n");
+        xml.push_str("  </template>\n");
+    }
+
+    xml.push_str("</templateSet>\n");
+    xml
+}
@@ -0,0 +1,111 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/json.rs - JSON 导入
+ * @author Noah
+ * @description 解析并校验 export_to_json 导出的代码片段数组
+ * @created 2026-02-07
+ * @version 1.0.0
+ *
+ * 功能特性:
+ * - 逐项解析，单项格式错误不影响其他项
+ * - 缺失的可选字段使用默认值
+ * - 检测文件内重复的ID
+ */
+use crate::models::CodeSnippet;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// export_to_json 输出中的单个条目，除必填字段外均可缺省
+#[derive(Debug, Deserialize)]
+struct SnippetRecord {
+    id: Option<String>,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    code: String,
+    language: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde(default)]
+    project_id: Option<String>,
+    #[serde(default)]
+    is_favorite: bool,
+    #[serde(default)]
+    usage_count: i64,
+    created_at: Option<i64>,
+    updated_at: Option<i64>,
+}
+
+/// 无法导入的条目，保留可识别的ID和标题以便报告
+#[derive(Debug)]
+pub struct InvalidItem {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub message: String,
+}
+
+/// 解析 JSON 导出内容
+///
+/// 顶层必须是数组；返回每一项的解析结果，顺序与源数据一致
+pub fn parse_snippets(content: &str) -> Result<Vec<Result<CodeSnippet, InvalidItem>>, String> {
+    let items: Vec<serde_json::Value> = serde_json::from_str(content)
+        .map_err(|e| format!("Invalid snippet export: expected a JSON array ({})", e))?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut seen_ids = HashSet::new();
+
+    Ok(items
+        .into_iter()
+        .map(|item| {
+            let invalid = |message: String| InvalidItem {
+                id: item.get("id").and_then(|v| v.as_str()).map(str::to_string),
+                title: item
+                    .get("title")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                message,
+            };
+
+            let record: SnippetRecord = serde_json::from_value(item.clone())
+                .map_err(|e| invalid(format!("Invalid snippet: {}", e)))?;
+
+            if record.title.trim().is_empty() {
+                return Err(invalid("Title must not be empty".to_string()));
+            }
+            if record.language.trim().is_empty() {
+                return Err(invalid("Language must not be empty".to_string()));
+            }
+
+            let id = record
+                .id
+                .filter(|id| !id.trim().is_empty())
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            if !seen_ids.insert(id.clone()) {
+                return Err(invalid(format!(
+                    "Duplicate snippet id in import file: {}",
+                    id
+                )));
+            }
+
+            let created_at = record.created_at.unwrap_or(now);
+            Ok(CodeSnippet {
+                id,
+                title: record.title,
+                description: record.description.unwrap_or_default(),
+                code: record.code,
+                language: record.language,
+                tags: record.tags,
+                folder_id: record.folder_id,
+                project_id: record.project_id,
+                is_favorite: record.is_favorite,
+                usage_count: record.usage_count.max(0),
+                created_at,
+                updated_at: record.updated_at.unwrap_or(created_at),
+                library_id: None,
+            })
+        })
+        .collect())
+}
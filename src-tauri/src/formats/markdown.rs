@@ -0,0 +1,629 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/markdown.rs - Markdown 代码块提取与目录树导出
+ * @author Noah
+ * @description 从 Markdown 笔记中提取围栏代码块作为代码片段，以及将代码片段库导出为 Markdown 目录树
+ * @created 2026-02-12
+ * @version 1.0.0
+ *
+ * 提取规则:
+ * - ``` 或 ~~~ 围栏代码块，信息字符串的第一个词作为语言
+ * - 标题取代码块之前最近的标题，同一标题下的多个代码块追加序号
+ * - 描述取代码块前紧邻的段落，没有时取其后紧邻的段落
+ * - 文件开头的 YAML front matter 会被跳过
+ * - 目录导入时，Markdown 文件所在的相对目录作为文件夹
+ *
+ * 导出规则:
+ * - 每个代码片段一个文件，front matter 记录ID、语言、标签和时间戳
+ * - 目录结构与文件夹层级一致，每个目录生成 README.md 索引
+ * - 目标目录中的 .snippetshub-export.json 记录上次导出的文件，
+ *   重新导出时只写入内容有变化的文件，并删除已不存在的片段对应的文件
+ */
+use super::{list_files, relative_dirs, slugify, EntryError};
+use crate::models::{CodeSnippet, Folder, MarkdownCodeBlock, MarkdownExportReport, SnippetDraft};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+/// 导出清单文件名，以 . 开头，导入时会被忽略
+const EXPORT_MANIFEST: &str = ".snippetshub-export.json";
+
+/// 文档结构中与代码块提取相关的元素
+enum Element {
+    Heading(String),
+    Paragraph(String),
+    Code {
+        line: usize,
+        info: String,
+        code: String,
+    },
+}
+
+/// 信息字符串 -> 应用语言标识，如 "rust,ignore"、"{.python}"、"sh title=x"
+fn normalize_language(info: &str) -> String {
+    let word = info
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('{')
+        .trim_start_matches('.')
+        .trim_end_matches('}')
+        .to_lowercase();
+
+    match word.as_str() {
+        "" | "txt" | "plain" | "plaintext" => "text",
+        "sh" | "bash" | "zsh" | "console" | "shell-session" => "shell",
+        "js" | "jsx" | "mjs" => "javascript",
+        "ts" | "tsx" => "typescript",
+        "py" | "python3" => "python",
+        "rs" => "rust",
+        "rb" => "ruby",
+        "yml" => "yaml",
+        "c++" | "cc" | "hpp" => "cpp",
+        "cs" | "c#" => "csharp",
+        "golang" => "go",
+        "kt" => "kotlin",
+        "md" => "markdown",
+        "ps1" | "pwsh" => "powershell",
+        other => other,
+    }
+    .to_string()
+}
+
+/// 识别围栏起始行，返回 (缩进, 围栏字符, 围栏长度, 信息字符串)
+fn fence_start(line: &str) -> Option<(usize, char, usize, String)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let rest = &line[indent..];
+    let fence_char = rest.chars().next().filter(|c| *c == '`' || *c == '~')?;
+    let length = rest.chars().take_while(|c| *c == fence_char).count();
+    if length < 3 {
+        return None;
+    }
+    let info = rest[length..].trim();
+    // 反引号围栏的信息字符串中不能包含反引号（否则是行内代码）
+    if fence_char == '`' && info.contains('`') {
+        return None;
+    }
+    Some((indent, fence_char, length, info.to_string()))
+}
+
+fn is_fence_end(line: &str, fence_char: char, length: usize) -> bool {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return false;
+    }
+    let count = trimmed.chars().take_while(|c| *c == fence_char).count();
+    count >= length && trimmed[count..].trim().is_empty()
+}
+
+/// ATX 标题（# Title），返回标题文本
+fn atx_heading(line: &str) -> Option<String> {
+    let trimmed = line.trim_start_matches(' ');
+    if line.len() - trimmed.len() > 3 {
+        return None;
+    }
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with([' ', '\t']) {
+        return None;
+    }
+    Some(rest.trim().trim_end_matches('#').trim_end().to_string())
+}
+
+/// 将 Markdown 文本分解为标题、段落和代码块
+fn elements(content: &str) -> Vec<Element> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut elements = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let mut i = 0;
+
+    let flush = |paragraph: &mut Vec<&str>, elements: &mut Vec<Element>| {
+        if !paragraph.is_empty() {
+            elements.push(Element::Paragraph(paragraph.join(" ")));
+            paragraph.clear();
+        }
+    };
+
+    // YAML front matter
+    if lines.first().map(|l| l.trim_end()) == Some("---") {
+        if let Some(end) = lines[1..].iter().position(|l| l.trim_end() == "---") {
+            i = end + 2;
+        }
+    }
+
+    while i < lines.len() {
+        let line = lines[i];
+
+        if let Some((indent, fence_char, length, info)) = fence_start(line) {
+            flush(&mut paragraph, &mut elements);
+            let start = i;
+            let mut code = Vec::new();
+            i += 1;
+            // 未闭合的围栏延续到文件末尾
+            while i < lines.len() && !is_fence_end(lines[i], fence_char, length) {
+                let content_line = lines[i];
+                let strip = content_line.len() - content_line.trim_start_matches(' ').len();
+                code.push(&content_line[strip.min(indent)..]);
+                i += 1;
+            }
+            elements.push(Element::Code {
+                line: start + 1,
+                info,
+                code: code.join("\n"),
+            });
+            i += 1;
+            continue;
+        }
+
+        if let Some(heading) = atx_heading(line) {
+            flush(&mut paragraph, &mut elements);
+            elements.push(Element::Heading(heading));
+        } else if line.trim().is_empty() {
+            flush(&mut paragraph, &mut elements);
+        } else if !paragraph.is_empty()
+            && !line.trim().is_empty()
+            && (line.trim().chars().all(|c| c == '=') || line.trim().chars().all(|c| c == '-'))
+        {
+            // Setext 标题：段落下方的 === 或 ---
+            elements.push(Element::Heading(paragraph.join(" ")));
+            paragraph.clear();
+        } else {
+            paragraph.push(line.trim());
+        }
+        i += 1;
+    }
+    flush(&mut paragraph, &mut elements);
+
+    elements
+}
+
+/// 提取单个 Markdown 文本中的代码块
+///
+/// fallback_title 用于没有任何标题的代码块，通常为文件名；空代码块会被忽略
+pub fn extract(content: &str, fallback_title: &str) -> Vec<MarkdownCodeBlock> {
+    let elements = elements(content);
+    let mut blocks = Vec::new();
+    let mut heading: Option<&str> = None;
+    let mut heading_counts: HashMap<String, usize> = HashMap::new();
+
+    for (position, element) in elements.iter().enumerate() {
+        let (line, info, code) = match element {
+            Element::Heading(text) => {
+                heading = Some(text.as_str());
+                continue;
+            }
+            Element::Paragraph(_) => continue,
+            Element::Code { line, info, code } => (line, info, code),
+        };
+        if code.trim().is_empty() {
+            continue;
+        }
+
+        let description = match (
+            position.checked_sub(1).map(|p| &elements[p]),
+            elements.get(position + 1),
+        ) {
+            (Some(Element::Paragraph(text)), _) | (_, Some(Element::Paragraph(text))) => {
+                text.clone()
+            }
+            _ => String::new(),
+        };
+
+        let base = heading
+            .filter(|h| !h.is_empty())
+            .unwrap_or(fallback_title)
+            .to_string();
+        let count = heading_counts.entry(base.clone()).or_insert(0);
+        *count += 1;
+        let title = if *count == 1 {
+            base
+        } else {
+            format!("{} ({})", base, count)
+        };
+
+        blocks.push(MarkdownCodeBlock {
+            index: 0,
+            source: String::new(),
+            line: *line,
+            title,
+            description,
+            language: normalize_language(info),
+            code: code.clone(),
+            folder_path: Vec::new(),
+        });
+    }
+
+    blocks
+}
+
+fn is_markdown(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(|e| e.to_str()),
+        Some("md" | "markdown")
+    )
+}
+
+/// 从 Markdown 文件或目录中提取代码块
+///
+/// 只有路径本身无法访问时才返回错误；无法读取的文件以 EntryError 返回，title 为相对路径
+pub fn collect(path: &Path) -> Result<(Vec<MarkdownCodeBlock>, Vec<EntryError>), String> {
+    let metadata =
+        std::fs::metadata(path).map_err(|e| format!("Cannot access {}: {}", path.display(), e))?;
+
+    let (root, files) = if metadata.is_dir() {
+        let mut files = Vec::new();
+        list_files(path, &mut files)?;
+        files.retain(|file| is_markdown(file));
+        (path.to_path_buf(), files)
+    } else {
+        let root = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        (root, vec![path.to_path_buf()])
+    };
+
+    let mut blocks = Vec::new();
+    let mut errors = Vec::new();
+    for file in files {
+        let source = file
+            .strip_prefix(&root)
+            .unwrap_or(&file)
+            .to_string_lossy()
+            .into_owned();
+        let content = match std::fs::read_to_string(&file) {
+            Ok(content) => content,
+            Err(e) => {
+                errors.push(EntryError {
+                    title: Some(source),
+                    message: format!("Failed to read file: {}", e),
+                });
+                continue;
+            }
+        };
+
+        let stem = file
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let folder_path = relative_dirs(&root, &file);
+        for mut block in extract(&content, &stem) {
+            block.index = blocks.len();
+            block.source = source.clone();
+            block.folder_path = folder_path.clone();
+            blocks.push(block);
+        }
+    }
+
+    Ok((blocks, errors))
+}
+
+/// 代码块 -> 导入草稿
+pub fn to_draft(block: MarkdownCodeBlock) -> SnippetDraft {
+    let mut draft = SnippetDraft::new(block.title, block.code, block.language);
+    draft.description = block.description;
+    draft.folder_path = block.folder_path;
+    draft
+}
+
+/// 预览结果 -> 导入项，序号为代码块在预览中的 index
+///
+/// selection 缺省时导入全部代码块，无法读取的文件作为错误项排在代码块之后；
+/// 指定 selection 时只导入选中的代码块
+pub fn select(
+    blocks: Vec<MarkdownCodeBlock>,
+    errors: Vec<EntryError>,
+    selection: Option<&[usize]>,
+) -> Vec<(usize, Result<SnippetDraft, EntryError>)> {
+    let error_start = blocks.len();
+    let mut items: Vec<(usize, Result<SnippetDraft, EntryError>)> = blocks
+        .into_iter()
+        .filter(|block| selection.is_none_or(|selected| selected.contains(&block.index)))
+        .map(|block| (block.index, Ok(to_draft(block))))
+        .collect();
+    if selection.is_none() {
+        items.extend(
+            errors
+                .into_iter()
+                .enumerate()
+                .map(|(offset, error)| (error_start + offset, Err(error))),
+        );
+    }
+    items
+}
+
+/// 文件夹名 -> 合法的目录名
+fn dir_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '-',
+            c if c.is_control() => '-',
+            c => c,
+        })
+        .collect();
+    cleaned.trim().trim_matches('.').to_string()
+}
+
+/// 以 JSON 字符串形式输出，同时也是合法的 YAML 标量
+fn yaml_string(text: &str) -> String {
+    serde_json::Value::String(text.to_string()).to_string()
+}
+
+fn rfc3339(timestamp_millis: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_millis)
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+/// 单个代码片段的 Markdown 文件内容
+fn render_snippet(snippet: &CodeSnippet) -> String {
+    // 围栏长度需超过代码中最长的连续反引号
+    let longest = snippet
+        .code
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    let tags: Vec<String> = snippet.tags.iter().map(|t| yaml_string(t)).collect();
+
+    let mut content = String::from("---\n");
+    content.push_str(&format!("id: {}\n", yaml_string(&snippet.id)));
+    content.push_str(&format!("title: {}\n", yaml_string(&snippet.title)));
+    content.push_str(&format!("language: {}\n", yaml_string(&snippet.language)));
+    content.push_str(&format!("tags: [{}]\n", tags.join(", ")));
+    content.push_str(&format!("favorite: {}\n", snippet.is_favorite));
+    content.push_str(&format!("created: {}\n", rfc3339(snippet.created_at)));
+    content.push_str(&format!("updated: {}\n", rfc3339(snippet.updated_at)));
+    content.push_str("---\n\n");
+    content.push_str(&format!("# {}\n\n", snippet.title.replace('\n', " ")));
+    if !snippet.description.trim().is_empty() {
+        content.push_str(snippet.description.trim());
+        content.push_str("\n\n");
+    }
+    content.push_str(&format!("{}{}\n", fence, snippet.language));
+    content.push_str(&snippet.code);
+    if !snippet.code.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&format!("{}\n", fence));
+    content
+}
+
+/// 目录树中的一个目录
+#[derive(Default)]
+struct TreeDir {
+    title: String,
+    /// (目录名, 标题)
+    dirs: BTreeMap<String, String>,
+    /// (文件名, 标题, 语言)
+    files: Vec<(String, String, String)>,
+}
+
+/// 生成 Markdown 目录树，返回 (相对路径, 文件内容) 列表
+///
+/// 只包含含有导出片段的文件夹；不属于任何文件夹的片段放在根目录
+pub fn render_tree(snippets: &[CodeSnippet], folders: &[Folder]) -> Vec<(String, String)> {
+    let by_id: HashMap<&str, &Folder> = folders.iter().map(|f| (f.id.as_str(), f)).collect();
+
+    // 同一父文件夹下目录名重复时追加序号，按创建顺序分配以保持稳定
+    let mut names: HashMap<&str, String> = HashMap::new();
+    let mut used: HashSet<(Option<&str>, String)> = HashSet::new();
+    for folder in folders {
+        let base = match dir_name(&folder.name) {
+            name if name.is_empty() => folder.id.clone(),
+            name => name,
+        };
+        let parent = folder.parent_id.as_deref();
+        let mut name = base.clone();
+        let mut counter = 2;
+        while !used.insert((parent, name.to_lowercase())) {
+            name = format!("{}-{}", base, counter);
+            counter += 1;
+        }
+        names.insert(folder.id.as_str(), name);
+    }
+
+    // 文件夹ID -> 目录路径（各级目录名）
+    let folder_path = |id: &str| -> Vec<(String, String)> {
+        let mut path = Vec::new();
+        let mut current = by_id.get(id).copied();
+        // 防御父级循环引用
+        while let Some(folder) = current.filter(|_| path.len() <= folders.len()) {
+            path.push((names[folder.id.as_str()].clone(), folder.name.clone()));
+            current = folder
+                .parent_id
+                .as_deref()
+                .and_then(|parent| by_id.get(parent).copied());
+        }
+        path.reverse();
+        path
+    };
+
+    let mut dirs: BTreeMap<Vec<String>, TreeDir> = BTreeMap::new();
+    dirs.insert(
+        Vec::new(),
+        TreeDir {
+            title: "Snippets".to_string(),
+            ..Default::default()
+        },
+    );
+
+    let mut sorted: Vec<&CodeSnippet> = snippets.iter().collect();
+    sorted.sort_by(|a, b| a.title.cmp(&b.title).then(a.id.cmp(&b.id)));
+
+    let mut files = Vec::new();
+    let mut used_files: HashSet<String> = HashSet::new();
+    for snippet in sorted {
+        let path = snippet
+            .folder_id
+            .as_deref()
+            .map(&folder_path)
+            .unwrap_or_default();
+
+        // 逐级登记目录
+        let mut key: Vec<String> = Vec::new();
+        for (name, title) in &path {
+            dirs.get_mut(&key)
+                .expect("parent directory registered")
+                .dirs
+                .insert(name.clone(), title.clone());
+            key.push(name.clone());
+            dirs.entry(key.clone()).or_insert_with(|| TreeDir {
+                title: title.clone(),
+                ..Default::default()
+            });
+        }
+
+        // 文件名重复时追加ID后缀，使已有文件的路径保持不变
+        let slug = match slugify(&snippet.title) {
+            slug if slug.is_empty() => snippet.id.clone(),
+            slug => slug,
+        };
+        let prefix = if key.is_empty() {
+            String::new()
+        } else {
+            format!("{}/", key.join("/"))
+        };
+        let mut file_name = format!("{}.md", slug);
+        if file_name.eq_ignore_ascii_case("README.md")
+            || !used_files.insert(format!("{}{}", prefix, file_name).to_lowercase())
+        {
+            let short_id: String = snippet.id.chars().take(8).collect();
+            file_name = format!("{}-{}.md", slug, short_id);
+            used_files.insert(format!("{}{}", prefix, file_name).to_lowercase());
+        }
+
+        dirs.get_mut(&key)
+            .expect("directory registered")
+            .files
+            .push((
+                file_name.clone(),
+                snippet.title.clone(),
+                snippet.language.clone(),
+            ));
+        files.push((format!("{}{}", prefix, file_name), render_snippet(snippet)));
+    }
+
+    for (key, dir) in &dirs {
+        let mut readme = format!("# {}\n", dir.title.replace('\n', " "));
+        if !dir.dirs.is_empty() {
+            readme.push_str("\n## Folders\n\n");
+            for (name, title) in &dir.dirs {
+                readme.push_str(&format!("- [{}]({}/README.md)\n", title, link(name)));
+            }
+        }
+        if !dir.files.is_empty() {
+            readme.push_str("\n## Snippets\n\n");
+            for (file_name, title, language) in &dir.files {
+                readme.push_str(&format!(
+                    "- [{}]({}) - `{}`\n",
+                    title.replace('\n', " "),
+                    link(file_name),
+                    language
+                ));
+            }
+        }
+        let path = if key.is_empty() {
+            "README.md".to_string()
+        } else {
+            format!("{}/README.md", key.join("/"))
+        };
+        files.push((path, readme));
+    }
+
+    files
+}
+
+/// Markdown 链接中的路径，对空格和括号进行编码
+fn link(path: &str) -> String {
+    path.replace('%', "%25")
+        .replace(' ', "%20")
+        .replace('(', "%28")
+        .replace(')', "%29")
+}
+
+/// 清单中记录的文件在 root 下的路径
+///
+/// 清单来自目标目录，可能被修改或损坏：只接受由普通路径段组成的相对路径，
+/// 且解析符号链接后仍须位于 root 之内，否则返回 None，避免删除导出目录之外的文件
+fn exported_file(root: &Path, relative: &str) -> Option<PathBuf> {
+    let relative = Path::new(relative);
+    let mut components = relative.components().peekable();
+    components.peek()?;
+    if !components.all(|component| matches!(component, Component::Normal(_))) {
+        return None;
+    }
+
+    let path = root.join(relative);
+    let resolved = path.canonicalize().ok()?;
+    let root = root.canonicalize().ok()?;
+    resolved.starts_with(&root).then_some(path)
+}
+
+/// 将目录树写入 root，只写入内容有变化的文件，并删除上次导出遗留的文件
+pub fn write_tree(root: &Path, files: &[(String, String)]) -> Result<MarkdownExportReport, String> {
+    std::fs::create_dir_all(root)
+        .map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let manifest_path = root.join(EXPORT_MANIFEST);
+    let previous: BTreeSet<String> = std::fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default();
+
+    let mut report = MarkdownExportReport {
+        directory: root.to_string_lossy().into_owned(),
+        written: Vec::new(),
+        unchanged: 0,
+        removed: Vec::new(),
+    };
+
+    let mut current = BTreeSet::new();
+    for (relative, content) in files {
+        current.insert(relative.clone());
+        let path = root.join(relative);
+        if std::fs::read_to_string(&path).ok().as_deref() == Some(content.as_str()) {
+            report.unchanged += 1;
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(&path, content)
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        report.written.push(relative.clone());
+    }
+
+    // 只删除上次由导出生成的文件，目录中的其他文件保持不变
+    for relative in previous.difference(&current) {
+        let Some(path) = exported_file(root, relative) else {
+            continue;
+        };
+        if std::fs::remove_file(&path).is_ok() {
+            report.removed.push(relative.clone());
+            // 目录为空时一并删除，非空时 remove_dir 会失败
+            let mut parent = path.parent();
+            while let Some(dir) = parent.filter(|dir| *dir != root) {
+                if std::fs::remove_dir(dir).is_err() {
+                    break;
+                }
+                parent = dir.parent();
+            }
+        }
+    }
+
+    if previous != current {
+        let manifest = serde_json::to_string_pretty(&current)
+            .map_err(|e| format!("Failed to serialize export manifest: {}", e))?;
+        std::fs::write(&manifest_path, manifest)
+            .map_err(|e| format!("Failed to write export manifest: {}", e))?;
+    }
+
+    Ok(report)
+}
@@ -0,0 +1,225 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/masscode.rs - massCode 数据库导入
+ * @author Noah
+ * @description 解析 massCode 的 db.json（文件夹、标签、多片段代码片段）
+ * @created 2026-02-13
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - folders (parentId 层级) -> 文件夹
+ * - snippets.name / description -> 标题 / 描述
+ * - snippets.content[] (片段) -> 代码；多个片段时每个片段导入为一个代码片段
+ * - tagsIds -> 标签名称
+ * - isFavorites -> 收藏
+ * - createdAt / updatedAt -> 保留原始时间戳
+ * - isDeleted (回收站) -> 不导入，在报告中标记为跳过
+ */
+use super::EntryError;
+use crate::models::SnippetDraft;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MassCodeFolder {
+    id: String,
+    name: String,
+    #[serde(default)]
+    parent_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MassCodeTag {
+    id: String,
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MassCodeFragment {
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    value: String,
+    #[serde(default)]
+    language: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MassCodeSnippet {
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    content: Vec<MassCodeFragment>,
+    #[serde(default)]
+    folder_id: Option<String>,
+    #[serde(default)]
+    tags_ids: Vec<String>,
+    #[serde(default)]
+    is_favorites: bool,
+    #[serde(default)]
+    is_deleted: bool,
+    #[serde(default)]
+    created_at: Option<i64>,
+    #[serde(default)]
+    updated_at: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MassCodeDb {
+    #[serde(default)]
+    folders: Vec<MassCodeFolder>,
+    #[serde(default)]
+    snippets: Vec<serde_json::Value>,
+    #[serde(default)]
+    tags: Vec<MassCodeTag>,
+}
+
+/// 解析结果
+pub struct MassCodeImport {
+    pub items: Vec<Result<SnippetDraft, EntryError>>,
+    /// 回收站中未导入的代码片段标题
+    pub trashed: Vec<String>,
+}
+
+/// massCode 语言标识（Ace 编辑器模式名）-> 应用语言标识
+fn from_masscode_language(language: &str) -> String {
+    match language {
+        "plain_text" | "text" | "" => "text",
+        "sh" => "shell",
+        "c_cpp" => "cpp",
+        "golang" => "go",
+        "batchfile" => "batch",
+        "markdown" | "md" => "markdown",
+        other => other,
+    }
+    .to_string()
+}
+
+/// 解析 massCode 的 db.json
+pub fn parse(content: &str) -> Result<MassCodeImport, String> {
+    let db: MassCodeDb =
+        serde_json::from_str(content).map_err(|e| format!("Invalid massCode database: {}", e))?;
+
+    let folders: HashMap<&str, &MassCodeFolder> =
+        db.folders.iter().map(|f| (f.id.as_str(), f)).collect();
+    let tags: HashMap<&str, &str> = db
+        .tags
+        .iter()
+        .map(|t| (t.id.as_str(), t.name.as_str()))
+        .collect();
+
+    // 文件夹ID -> 各级文件夹名称
+    let folder_path = |id: &str| -> Option<Vec<String>> {
+        let mut path = Vec::new();
+        let mut current = Some(id);
+        while let Some(id) = current {
+            let folder = folders.get(id)?;
+            if path.len() > folders.len() {
+                // 父级循环引用
+                return None;
+            }
+            path.push(folder.name.clone());
+            current = folder.parent_id.as_deref();
+        }
+        path.reverse();
+        Some(path)
+    };
+
+    let mut items = Vec::new();
+    let mut trashed = Vec::new();
+
+    for value in db.snippets {
+        let name = value
+            .get("name")
+            .and_then(|v| v.as_str())
+            .map(str::to_string);
+        let snippet: MassCodeSnippet = match serde_json::from_value(value) {
+            Ok(snippet) => snippet,
+            Err(e) => {
+                items.push(Err(EntryError {
+                    title: name,
+                    message: format!("Invalid snippet: {}", e),
+                }));
+                continue;
+            }
+        };
+
+        let title = snippet
+            .name
+            .clone()
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| "Untitled snippet".to_string());
+        if snippet.is_deleted {
+            trashed.push(title);
+            continue;
+        }
+
+        let mut notes = Vec::new();
+        let path = match snippet.folder_id.as_deref() {
+            None => Vec::new(),
+            Some(id) => folder_path(id).unwrap_or_else(|| {
+                notes.push(format!("Folder {} not found, imported without folder", id));
+                Vec::new()
+            }),
+        };
+        let mut snippet_tags = Vec::new();
+        for id in &snippet.tags_ids {
+            match tags.get(id.as_str()) {
+                Some(name) => snippet_tags.push(name.to_string()),
+                None => notes.push(format!("Tag {} not found", id)),
+            }
+        }
+
+        let fragments: Vec<&MassCodeFragment> = snippet
+            .content
+            .iter()
+            .filter(|f| !f.value.trim().is_empty())
+            .collect();
+        if fragments.is_empty() {
+            items.push(Err(EntryError {
+                title: Some(title),
+                message: "Snippet has no content".to_string(),
+            }));
+            continue;
+        }
+
+        let count = fragments.len();
+        for (position, fragment) in fragments.into_iter().enumerate() {
+            let language = from_masscode_language(fragment.language.as_deref().unwrap_or(""));
+            let fragment_title = if count == 1 {
+                title.clone()
+            } else {
+                let label = fragment
+                    .label
+                    .clone()
+                    .filter(|l| !l.trim().is_empty())
+                    .unwrap_or_else(|| format!("Fragment {}", position + 1));
+                format!("{} / {}", title, label)
+            };
+
+            let mut draft = SnippetDraft::new(fragment_title, fragment.value.clone(), language);
+            draft.description = snippet.description.clone().unwrap_or_default();
+            draft.tags = snippet_tags.clone();
+            draft.folder_path = path.clone();
+            draft.is_favorite = snippet.is_favorites;
+            draft.created_at = snippet.created_at;
+            draft.updated_at = snippet.updated_at;
+            draft.notes = notes.clone();
+            if count > 1 {
+                draft.notes.push(format!(
+                    "Fragment {} of {} imported as a separate snippet",
+                    position + 1,
+                    count
+                ));
+            }
+            items.push(Ok(draft));
+        }
+    }
+
+    Ok(MassCodeImport { items, trashed })
+}
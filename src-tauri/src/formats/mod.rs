@@ -0,0 +1,188 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/mod.rs - 导入导出格式模块
+ * @author Noah
+ * @description 各种外部文件格式与内部数据模型之间的转换
+ * @created 2026-02-06
+ * @version 1.0.0
+ *
+ * 架构说明:
+ * 本目录下的模块只负责格式的解析与生成，不直接访问数据库。
+ * 数据的读取和写入由 commands.rs 通过 Database 完成。
+ */
+pub mod archive;
+pub mod checklist;
+pub mod csv;
+pub mod editors;
+pub mod gist;
+pub mod html;
+pub mod ical;
+pub mod jetbrains;
+pub mod json;
+pub mod markdown;
+pub mod masscode;
+pub mod snipmate;
+pub mod sublime;
+pub mod todotxt;
+pub mod ultisnips;
+pub mod vscode;
+pub mod yasnippet;
+
+use crate::models::TodoDraft;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// 转义 XML 属性值，换行和制表符使用字符引用以免被属性值规范化
+pub fn escape_xml_attr(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => {}
+            '\t' => escaped.push_str("&#9;"),
+            _ => escaped.push_str(&escape_html(&c.to_string())),
+        }
+    }
+    escaped
+}
+
+/// 递归列出目录下的文件，按路径排序以保证导入顺序稳定
+pub fn list_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read directory {}: {}", dir.display(), e))?;
+    let mut paths: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    paths.sort();
+
+    for path in paths {
+        let hidden = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .is_some_and(|n| n.starts_with('.'));
+        if hidden {
+            continue;
+        }
+        if path.is_dir() {
+            list_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// 相对于导入根目录的各级目录名
+pub fn relative_dirs(root: &Path, path: &Path) -> Vec<String> {
+    path.parent()
+        .and_then(|parent| parent.strip_prefix(root).ok())
+        .map(|relative| {
+            relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().into_owned())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 无法解析的条目，title 为可识别的名称或文件名
+#[derive(Debug)]
+pub struct EntryError {
+    pub title: Option<String>,
+    pub message: String,
+}
+
+/// 编辑器触发词（VS Code prefix、JetBrains abbreviation 等）以带此前缀的标签保存
+pub const TRIGGER_TAG_PREFIX: &str = "trigger:";
+
+pub fn trigger_tag(trigger: &str) -> String {
+    format!("{}{}", TRIGGER_TAG_PREFIX, trigger)
+}
+
+/// 从标签中提取触发词
+pub fn triggers(tags: &[String]) -> Vec<String> {
+    tags.iter()
+        .filter_map(|tag| tag.strip_prefix(TRIGGER_TAG_PREFIX))
+        .map(str::to_string)
+        .collect()
+}
+
+/// 由标题生成触发词，如 "Quick Sort" -> "quick-sort"
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.ends_with('-') && !slug.is_empty() {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Vim filetype -> 应用语言标识（UltiSnips 与 SnipMate 共用）
+pub fn from_vim_filetype(filetype: &str) -> String {
+    match filetype {
+        "sh" | "bash" | "zsh" => "shell",
+        "javascriptreact" => "javascript",
+        "typescriptreact" => "typescript",
+        "cs" => "csharp",
+        "tex" => "latex",
+        "objc" => "objective-c",
+        "all" | "text" | "" => "text",
+        other => other,
+    }
+    .to_string()
+}
+
+/// 转义 HTML/XML 文本中的特殊字符
+pub fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 按父任务在前、子任务在后的顺序排列同一批导入的 TODO（保留各自的序号）
+///
+/// 父任务不在本批中的 TODO 视为顶层；父任务关联构成循环时，断开循环中第一项的
+/// 父任务关联并在 notes 中说明，保证按此顺序写入不会违反外键约束
+pub fn parents_first(drafts: Vec<(usize, TodoDraft)>) -> Vec<(usize, TodoDraft)> {
+    let mut ordered = Vec::with_capacity(drafts.len());
+    let mut pending = drafts;
+
+    while !pending.is_empty() {
+        let waiting: HashSet<String> = pending
+            .iter()
+            .filter_map(|(_, draft)| draft.id.clone())
+            .collect();
+        let (mut ready, mut rest): (Vec<_>, Vec<_>) =
+            pending.into_iter().partition(|(_, draft)| {
+                draft
+                    .parent_id
+                    .as_ref()
+                    .is_none_or(|parent| !waiting.contains(parent))
+            });
+        if ready.is_empty() {
+            let (index, mut draft) = rest.remove(0);
+            if let Some(parent_id) = draft.parent_id.take() {
+                draft.notes.push(format!(
+                    "Parent {} forms a cycle, imported as top-level",
+                    parent_id
+                ));
+            }
+            ready.push((index, draft));
+        }
+        ordered.extend(ready);
+        pending = rest;
+    }
+
+    ordered
+}
@@ -0,0 +1,110 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/snipmate.rs - SnipMate 代码片段格式
+ * @author Noah
+ * @description 解析 Vim SnipMate 的 .snippets 文件
+ * @created 2026-02-11
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - snippet <trigger> [description] 及其后以 Tab 缩进的行 <-> 代码片段
+ * - trigger <-> trigger:<trigger> 标签
+ * - description <-> 标题与描述（缺省时以触发词作为标题）
+ * - 文件类型（文件名或所在目录）<-> 语言
+ */
+use super::{trigger_tag, EntryError};
+use crate::models::SnippetDraft;
+
+/// 解析中的片段
+struct Pending {
+    line: usize,
+    trigger: String,
+    description: String,
+    body: Vec<String>,
+}
+
+fn finish(pending: Pending, language: &str) -> Result<SnippetDraft, EntryError> {
+    let mut body = pending.body;
+    while body.last().is_some_and(|line| line.trim().is_empty()) {
+        body.pop();
+    }
+    if body.is_empty() {
+        return Err(EntryError {
+            title: Some(pending.trigger),
+            message: format!("Line {}: snippet has no body", pending.line),
+        });
+    }
+
+    let title = if pending.description.is_empty() {
+        pending.trigger.clone()
+    } else {
+        pending.description.clone()
+    };
+    let mut draft = SnippetDraft::new(title, body.join("\n"), language.to_string());
+    draft.description = pending.description;
+    draft.tags = vec![trigger_tag(&pending.trigger)];
+    if draft.code.contains('`') {
+        draft
+            .notes
+            .push("Vim script interpolation kept verbatim".to_string());
+    }
+    Ok(draft)
+}
+
+/// 解析 .snippets 文件内容
+///
+/// language 由调用方根据文件名或所在目录确定
+pub fn parse(content: &str, language: &str) -> Vec<Result<SnippetDraft, EntryError>> {
+    let mut results = Vec::new();
+    let mut pending: Option<Pending> = None;
+
+    for (number, line) in content.lines().enumerate() {
+        if let Some(current) = pending.as_mut() {
+            // 片段内容以一个 Tab 缩进，空行不结束片段
+            if let Some(body_line) = line.strip_prefix('\t') {
+                current.body.push(body_line.to_string());
+                continue;
+            }
+            if line.trim().is_empty() {
+                current.body.push(String::new());
+                continue;
+            }
+            results.push(finish(pending.take().unwrap(), language));
+        }
+
+        let Some(rest) = line.strip_prefix("snippet") else {
+            // 注释、extends、version 等
+            continue;
+        };
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let rest = rest.trim();
+        let (trigger, description) = rest
+            .split_once(char::is_whitespace)
+            .map(|(t, d)| (t, d.trim()))
+            .unwrap_or((rest, ""));
+        if trigger.is_empty() {
+            results.push(Err(EntryError {
+                title: None,
+                message: format!("Line {}: snippet has no trigger", number + 1),
+            }));
+            continue;
+        }
+
+        pending = Some(Pending {
+            line: number + 1,
+            trigger: trigger.to_string(),
+            description: description.to_string(),
+            body: Vec::new(),
+        });
+    }
+
+    if let Some(current) = pending {
+        results.push(finish(current, language));
+    }
+
+    results
+}
@@ -0,0 +1,151 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/sublime.rs - Sublime Text 代码片段格式
+ * @author Noah
+ * @description 解析 .sublime-snippet XML 文件
+ * @created 2026-02-11
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - content <-> 代码，占位符 ($1, ${1:name}) 原样保留
+ * - tabTrigger <-> trigger:<tabTrigger> 标签
+ * - description <-> 标题与描述（缺省时使用触发词或文件名）
+ * - scope <-> 语言（如 source.python -> python）
+ */
+use super::trigger_tag;
+use crate::models::SnippetDraft;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+/// Sublime scope -> 应用语言标识
+fn from_scope(scope: &str) -> String {
+    // "source.python - comment, source.cython" 只取第一个选择器
+    let selector = scope
+        .split([',', ' '])
+        .map(str::trim)
+        .find(|s| !s.is_empty())
+        .unwrap_or_default();
+    let mut parts = selector.split('.');
+    let kind = parts.next().unwrap_or_default();
+    let name = parts.next().unwrap_or_default();
+    let sub = parts.next().unwrap_or_default();
+
+    match (kind, name, sub) {
+        ("text", "html", "markdown") => "markdown",
+        ("text", "html", _) => "html",
+        ("text", "xml", _) => "xml",
+        ("text", "tex", _) => "latex",
+        ("text", _, _) | ("", _, _) => "text",
+        (_, "js", _) => "javascript",
+        (_, "ts" | "tsx", _) => "typescript",
+        (_, "shell", _) => "shell",
+        (_, "c++", _) => "cpp",
+        (_, "cs", _) => "csharp",
+        (_, "objc", _) => "objective-c",
+        (_, "dosbatch", _) => "batch",
+        (_, name, _) => name,
+    }
+    .to_string()
+}
+
+/// 解析单个 .sublime-snippet 文件
+///
+/// fallback_title 用于既没有描述也没有触发词的片段，通常为文件名
+pub fn parse(content: &str, fallback_title: &str) -> Result<SnippetDraft, String> {
+    let mut reader = Reader::from_str(content);
+
+    let mut path: Vec<String> = Vec::new();
+    let mut code: Option<String> = None;
+    let mut trigger = String::new();
+    let mut description = String::new();
+    let mut scope = String::new();
+
+    loop {
+        let event = reader.read_event().map_err(|e| {
+            format!(
+                "Invalid Sublime snippet XML at position {}: {}",
+                reader.error_position(),
+                e
+            )
+        })?;
+
+        let text = match event {
+            Event::Start(e) => {
+                path.push(String::from_utf8_lossy(e.name().as_ref()).into_owned());
+                continue;
+            }
+            Event::End(_) => {
+                path.pop();
+                continue;
+            }
+            Event::Text(e) => e
+                .decode()
+                .map_err(|e| format!("Invalid text: {}", e))?
+                .into_owned(),
+            Event::CData(e) => String::from_utf8(e.into_inner().into_owned())
+                .map_err(|e| format!("Invalid CDATA: {}", e))?,
+            Event::GeneralRef(e) => {
+                let resolved = if e.is_char_ref() {
+                    e.resolve_char_ref()
+                        .map_err(|e| format!("Invalid character reference: {}", e))?
+                } else {
+                    match e.as_ref() {
+                        b"amp" => Some('&'),
+                        b"lt" => Some('<'),
+                        b"gt" => Some('>'),
+                        b"quot" => Some('"'),
+                        b"apos" => Some('\''),
+                        _ => None,
+                    }
+                };
+                match resolved {
+                    Some(c) => c.to_string(),
+                    None => {
+                        return Err(format!(
+                            "Unknown entity &{};",
+                            String::from_utf8_lossy(e.as_ref())
+                        ))
+                    }
+                }
+            }
+            Event::Eof => break,
+            _ => continue,
+        };
+
+        if path.first().map(String::as_str) != Some("snippet") || path.len() != 2 {
+            continue;
+        }
+        match path[1].as_str() {
+            "content" => code.get_or_insert_with(String::new).push_str(&text),
+            "tabTrigger" => trigger.push_str(&text),
+            "description" => description.push_str(&text),
+            "scope" => scope.push_str(&text),
+            _ => {}
+        }
+    }
+
+    let code = code.ok_or_else(|| "Snippet has no <content> element".to_string())?;
+    // 内容通常以换行包裹在 CDATA 中
+    let code = code.trim_matches(['\r', '\n']).to_string();
+    if code.trim().is_empty() {
+        return Err("Snippet content is empty".to_string());
+    }
+
+    let trigger = trigger.trim().to_string();
+    let description = description.trim().to_string();
+    let title = if !description.is_empty() {
+        description.clone()
+    } else if !trigger.is_empty() {
+        trigger.clone()
+    } else {
+        fallback_title.to_string()
+    };
+
+    let mut draft = SnippetDraft::new(title, code, from_scope(scope.trim()));
+    draft.description = description;
+    if !trigger.is_empty() {
+        draft.tags = vec![trigger_tag(&trigger)];
+    }
+    Ok(draft)
+}
@@ -0,0 +1,187 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/todotxt.rs - todo.txt 格式
+ * @author Noah
+ * @description TODO 与 todo.txt（http://todotxt.org）之间的转换
+ * @created 2026-02-15
+ * @version 1.0.0
+ *
+ * 格式示例:
+ * (A) 2026-02-01 发布 2.0 +SnippetsHub @release due:2026-03-01
+ * x 2026-02-10 2026-02-01 更新变更日志 @release pri:B
+ *
+ * 字段映射:
+ * - x 前缀 <-> 已完成；todo.txt 不区分进行中、阻塞等其他状态
+ * - (A) / (B) / (C) <-> high / medium / low，已完成任务写为 pri:X
+ * - 完成日期 / 创建日期 <-> updated_at / created_at
+ * - +项目 <-> 项目名称（空白替换为 -）
+ * - @上下文 <-> 标签名称（空白替换为 -）
+ * - due:YYYY-MM-DD <-> 截止日期
+ * - 每行一个任务，子任务平铺输出，不保留父子关系
+ */
+use super::checklist::tag_word;
+use super::EntryError;
+use crate::models::{Todo, TodoDraft};
+use std::collections::HashMap;
+
+fn to_priority_letter(priority: &str) -> Option<char> {
+    match priority {
+        "high" => Some('A'),
+        "medium" => Some('B'),
+        "low" => Some('C'),
+        _ => None,
+    }
+}
+
+fn from_priority_letter(letter: char) -> Option<String> {
+    match letter {
+        'A' => Some("high".to_string()),
+        'B' => Some("medium".to_string()),
+        'C'..='Z' => Some("low".to_string()),
+        _ => None,
+    }
+}
+
+fn format_date(timestamp_millis: i64) -> Option<String> {
+    chrono::DateTime::from_timestamp_millis(timestamp_millis)
+        .map(|t| t.format("%Y-%m-%d").to_string())
+}
+
+fn parse_date(value: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok()
+}
+
+fn date_to_millis(date: chrono::NaiveDate) -> i64 {
+    date.and_hms_opt(0, 0, 0)
+        .map(|t| t.and_utc().timestamp_millis())
+        .unwrap_or_default()
+}
+
+/// 生成 todo.txt 内容，每个任务（包括已加载的子任务）一行
+pub fn render(
+    todos: &[Todo],
+    tag_names: &HashMap<String, String>,
+    project_names: &HashMap<String, String>,
+) -> String {
+    let mut output = String::new();
+    let mut seen = std::collections::HashSet::new();
+
+    for todo in todos
+        .iter()
+        .chain(todos.iter().flat_map(|t| t.subtasks.iter()))
+    {
+        if !seen.insert(todo.id.as_str()) {
+            continue;
+        }
+
+        let priority = todo.priority.as_deref().and_then(to_priority_letter);
+        let mut words = Vec::new();
+        if todo.completed {
+            words.push("x".to_string());
+            words.extend(format_date(todo.updated_at));
+        } else if let Some(letter) = priority {
+            words.push(format!("({})", letter));
+        }
+        words.extend(format_date(todo.created_at));
+        words.push(todo.title.split_whitespace().collect::<Vec<_>>().join(" "));
+
+        if let Some(name) = todo
+            .project_id
+            .as_ref()
+            .and_then(|id| project_names.get(id))
+        {
+            words.push(format!("+{}", tag_word(name)));
+        }
+        for tag_id in &todo.tags {
+            if let Some(name) = tag_names.get(tag_id) {
+                words.push(format!("@{}", tag_word(name)));
+            }
+        }
+        if let Some(due_date) = todo.due_date.as_deref().filter(|d| !d.is_empty()) {
+            words.push(format!("due:{}", due_date));
+        }
+        if let (true, Some(letter)) = (todo.completed, priority) {
+            words.push(format!("pri:{}", letter));
+        }
+
+        output.push_str(&words.join(" "));
+        output.push('\n');
+    }
+
+    output
+}
+
+/// 解析单行任务
+fn parse_task(line: &str, line_number: usize) -> Result<TodoDraft, EntryError> {
+    let mut words = line.split_whitespace().peekable();
+    let mut draft = TodoDraft::new(String::new());
+
+    if words.peek() == Some(&"x") {
+        words.next();
+        draft.status = "completed".to_string();
+        draft.completed = true;
+        draft.progress = 100;
+        if let Some(date) = words.peek().and_then(|w| parse_date(w)) {
+            words.next();
+            draft.updated_at = Some(date_to_millis(date));
+        }
+    } else if let Some(word) = words.peek() {
+        let bytes = word.as_bytes();
+        if bytes.len() == 3 && bytes[0] == b'(' && bytes[2] == b')' {
+            if let Some(priority) = from_priority_letter(bytes[1] as char) {
+                draft.priority = Some(priority);
+                words.next();
+            }
+        }
+    }
+    if let Some(date) = words.peek().and_then(|w| parse_date(w)) {
+        words.next();
+        draft.created_at = Some(date_to_millis(date));
+    }
+
+    let mut title = Vec::new();
+    for word in words {
+        if let Some(project) = word.strip_prefix('+').filter(|p| !p.is_empty()) {
+            if draft.project_name.is_none() {
+                draft.project_name = Some(project.to_string());
+            } else {
+                draft
+                    .notes
+                    .push(format!("Additional project +{} not preserved", project));
+            }
+        } else if let Some(context) = word.strip_prefix('@').filter(|c| !c.is_empty()) {
+            draft.tag_names.push(context.to_string());
+        } else if let Some(date) = word.strip_prefix("due:") {
+            match parse_date(date) {
+                Some(_) => draft.due_date = Some(date.to_string()),
+                None => draft
+                    .notes
+                    .push(format!("Line {}: invalid due date {}", line_number, date)),
+            }
+        } else if let Some(letter) = word.strip_prefix("pri:") {
+            draft.priority = letter.chars().next().and_then(from_priority_letter);
+        } else {
+            title.push(word);
+        }
+    }
+
+    draft.title = title.join(" ");
+    if draft.title.is_empty() {
+        return Err(EntryError {
+            title: None,
+            message: format!("Line {}: task has no description", line_number),
+        });
+    }
+    Ok(draft)
+}
+
+/// 解析 todo.txt 内容，忽略空行
+pub fn parse(content: &str) -> Vec<Result<TodoDraft, EntryError>> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(number, line)| parse_task(line, number + 1))
+        .collect()
+}
@@ -0,0 +1,171 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/ultisnips.rs - UltiSnips 代码片段格式
+ * @author Noah
+ * @description 解析 Vim UltiSnips 的 .snippets 文件
+ * @created 2026-02-11
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - snippet <trigger> "<description>" <options> ... endsnippet <-> 代码片段
+ * - trigger <-> trigger:<trigger> 标签
+ * - description <-> 标题与描述（缺省时以触发词作为标题）
+ * - 文件类型（文件名或所在目录）<-> 语言
+ *
+ * 不支持的内容（global 块、context/pre_expand 等动作、正则触发词、
+ * 内嵌的 python/vim 插值）按原样保留或跳过，并在导入报告中说明。
+ */
+use super::{trigger_tag, EntryError};
+use crate::models::SnippetDraft;
+
+/// 片段定义之前可出现的、无法映射的指令
+const UNSUPPORTED_DIRECTIVES: &[&str] = &["context", "pre_expand", "post_expand", "post_jump"];
+
+/// snippet 行解析结果
+struct Header {
+    trigger: String,
+    description: String,
+    options: String,
+}
+
+/// 解析 "snippet" 之后的部分，规则与 UltiSnips 一致：
+/// 末尾不含引号的单词为选项，其前以引号包裹的部分为描述，剩余部分为触发词
+fn parse_header(rest: &str) -> Result<Header, String> {
+    let mut remain = rest.trim().to_string();
+    let mut options = String::new();
+
+    if let Some((head, last)) = remain.rsplit_once(char::is_whitespace) {
+        if !last.contains('"') && head.trim_end().ends_with('"') && head.matches('"').count() >= 2 {
+            options = last.to_string();
+            remain = head.trim_end().to_string();
+        }
+    }
+
+    let mut description = String::new();
+    if remain.ends_with('"') && remain.len() > 1 {
+        if let Some(start) = remain[..remain.len() - 1].rfind('"') {
+            if start > 0 && remain[..start].ends_with(char::is_whitespace) {
+                description = remain[start + 1..remain.len() - 1].to_string();
+                remain = remain[..start].trim_end().to_string();
+            }
+        }
+    }
+
+    // 含空格或正则触发词使用首尾相同的分隔符包裹，如 !a b! 或 "^for"
+    let mut trigger = remain;
+    let chars: Vec<char> = trigger.chars().collect();
+    if chars.len() > 2
+        && chars[0] == chars[chars.len() - 1]
+        && (options.contains('r') || trigger.contains(char::is_whitespace))
+    {
+        trigger = chars[1..chars.len() - 1].iter().collect();
+    }
+
+    if trigger.is_empty() {
+        return Err("Snippet has no trigger".to_string());
+    }
+
+    Ok(Header {
+        trigger,
+        description,
+        options,
+    })
+}
+
+/// 解析 .snippets 文件内容
+///
+/// language 由调用方根据文件名或所在目录确定
+pub fn parse(content: &str, language: &str) -> Vec<Result<SnippetDraft, EntryError>> {
+    let mut results = Vec::new();
+    let mut lines = content.lines().enumerate();
+    let mut pending_notes: Vec<String> = Vec::new();
+
+    while let Some((number, line)) = lines.next() {
+        let line = line.trim_start();
+        let keyword = line.split_whitespace().next().unwrap_or_default();
+
+        if keyword == "global" {
+            // global !p ... endglobal: 共享的 python 代码，不导入
+            for (_, line) in lines.by_ref() {
+                if line.trim_end() == "endglobal" {
+                    break;
+                }
+            }
+            continue;
+        }
+        if UNSUPPORTED_DIRECTIVES.contains(&keyword) {
+            pending_notes.push(format!("{} action not preserved", keyword));
+            continue;
+        }
+        let Some(rest) = line.strip_prefix("snippet") else {
+            // 注释、priority、extends、clearsnippets 等
+            continue;
+        };
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) {
+            continue;
+        }
+
+        let notes = std::mem::take(&mut pending_notes);
+        let header = match parse_header(rest) {
+            Ok(header) => header,
+            Err(message) => {
+                results.push(Err(EntryError {
+                    title: None,
+                    message: format!("Line {}: {}", number + 1, message),
+                }));
+                continue;
+            }
+        };
+
+        let mut body = Vec::new();
+        let mut closed = false;
+        for (_, line) in lines.by_ref() {
+            if line.trim_end() == "endsnippet" {
+                closed = true;
+                break;
+            }
+            body.push(line);
+        }
+
+        if !closed {
+            results.push(Err(EntryError {
+                title: Some(header.trigger),
+                message: format!("Line {}: missing endsnippet", number + 1),
+            }));
+            break;
+        }
+
+        let code = body.join("\n");
+        if code.trim().is_empty() {
+            results.push(Err(EntryError {
+                title: Some(header.trigger),
+                message: format!("Line {}: snippet has no body", number + 1),
+            }));
+            continue;
+        }
+
+        let title = if header.description.is_empty() {
+            header.trigger.clone()
+        } else {
+            header.description.clone()
+        };
+        let mut draft = SnippetDraft::new(title, code, language.to_string());
+        draft.description = header.description;
+        draft.tags = vec![trigger_tag(&header.trigger)];
+        draft.notes = notes;
+        if header.options.contains('r') {
+            draft
+                .notes
+                .push("Regular expression trigger kept as plain text".to_string());
+        }
+        if ["`!p", "`!v", "`!"].iter().any(|p| draft.code.contains(p)) {
+            draft
+                .notes
+                .push("Interpolated code kept verbatim".to_string());
+        }
+        results.push(Ok(draft));
+    }
+
+    results
+}
@@ -0,0 +1,273 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/vscode.rs - VS Code 代码片段格式
+ * @author Noah
+ * @description VS Code .code-snippets (JSONC) 文件的解析与生成
+ * @created 2026-02-09
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - 条目名称 <-> 标题
+ * - prefix <-> trigger:<prefix> 标签
+ * - body (字符串数组) <-> 代码，按 \n 拆分/合并（保留末尾换行），占位符 ($1, ${1:name}, $TM_FILENAME) 原样保留
+ * - description <-> 描述
+ * - scope <-> 语言（多个 scope 时取第一个）
+ */
+use super::{slugify, trigger_tag, triggers, EntryError};
+use crate::models::{CodeSnippet, SnippetDraft};
+use serde::de::{MapAccess, Visitor};
+use serde::ser::SerializeMap;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+
+/// 保持源文件中条目顺序的 JSON 对象
+struct OrderedEntries<T>(Vec<(String, T)>);
+
+impl<'de> Deserialize<'de> for OrderedEntries<Value> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = OrderedEntries<Value>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("an object of snippet definitions")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                let mut entries = Vec::new();
+                while let Some((key, value)) = map.next_entry::<String, Value>()? {
+                    entries.push((key, value));
+                }
+                Ok(OrderedEntries(entries))
+            }
+        }
+
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+impl<T: Serialize> Serialize for OrderedEntries<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in &self.0 {
+            map.serialize_entry(key, value)?;
+        }
+        map.end()
+    }
+}
+
+/// 导出的单个片段定义，字段顺序与 VS Code 生成的文件一致
+#[derive(Serialize)]
+struct SnippetEntry {
+    prefix: Value,
+    body: Vec<String>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    description: String,
+    scope: String,
+}
+
+/// VS Code 语言标识 -> 应用语言标识
+fn from_vscode_language(scope: &str) -> String {
+    match scope {
+        "shellscript" => "shell",
+        "javascriptreact" => "javascript",
+        "typescriptreact" => "typescript",
+        "dockercompose" => "yaml",
+        "plaintext" | "" => "text",
+        other => other,
+    }
+    .to_string()
+}
+
+/// 应用语言标识 -> VS Code 语言标识
+fn to_vscode_language(language: &str) -> String {
+    match language {
+        "shell" | "bash" => "shellscript",
+        "text" => "plaintext",
+        other => other,
+    }
+    .to_string()
+}
+
+/// 去除 JSONC 中的注释和尾随逗号
+pub fn strip_jsonc(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut output = String::with_capacity(content.len());
+    let mut i = 0;
+    let mut in_string = false;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if in_string {
+            output.push(c);
+            if c == '\\' && i + 1 < chars.len() {
+                output.push(chars[i + 1]);
+                i += 2;
+                continue;
+            }
+            if c == '"' {
+                in_string = false;
+            }
+            i += 1;
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                output.push(c);
+                i += 1;
+            }
+            '/' if chars.get(i + 1) == Some(&'/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if chars.get(i + 1) == Some(&'*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            ',' => {
+                // 尾随逗号：下一个非空白字符是 } 或 ]
+                let next = chars[i + 1..].iter().find(|c| !c.is_whitespace()).copied();
+                if !matches!(next, Some('}') | Some(']')) {
+                    output.push(c);
+                }
+                i += 1;
+            }
+            _ => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+
+    output
+}
+
+/// 字符串或字符串数组
+fn string_list(value: Option<&Value>) -> Result<Vec<String>, String> {
+    match value {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(Value::String(s)) => Ok(vec![s.clone()]),
+        Some(Value::Array(items)) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .map(str::to_string)
+                    .ok_or_else(|| "expected an array of strings".to_string())
+            })
+            .collect(),
+        Some(_) => Err("expected a string or an array of strings".to_string()),
+    }
+}
+
+/// 解析 .code-snippets 文件
+///
+/// default_language 用于没有 scope 的条目（如语言专属的 javascript.json 片段文件）
+pub fn parse(
+    content: &str,
+    default_language: Option<&str>,
+) -> Result<Vec<Result<SnippetDraft, EntryError>>, String> {
+    let entries: OrderedEntries<Value> = serde_json::from_str(&strip_jsonc(content))
+        .map_err(|e| format!("Invalid VS Code snippets file: {}", e))?;
+
+    Ok(entries
+        .0
+        .into_iter()
+        .map(|(name, entry)| {
+            let error = |message: String| EntryError {
+                title: Some(name.clone()),
+                message,
+            };
+
+            if !entry.is_object() {
+                return Err(error("Snippet definition must be an object".to_string()));
+            }
+
+            let body = string_list(entry.get("body")).map_err(|e| error(format!("body: {}", e)))?;
+            if body.is_empty() {
+                return Err(error("Snippet has no body".to_string()));
+            }
+            let prefixes =
+                string_list(entry.get("prefix")).map_err(|e| error(format!("prefix: {}", e)))?;
+
+            let scope = entry
+                .get("scope")
+                .and_then(Value::as_str)
+                .and_then(|scope| scope.split(',').map(str::trim).find(|s| !s.is_empty()));
+            let language = match scope {
+                Some(scope) => from_vscode_language(scope),
+                None => default_language
+                    .map(from_vscode_language)
+                    .unwrap_or_else(|| "text".to_string()),
+            };
+
+            let mut draft = SnippetDraft::new(name.clone(), body.join("\n"), language);
+            draft.description = entry
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            draft.tags = prefixes
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| trigger_tag(p))
+                .collect();
+            Ok(draft)
+        })
+        .collect())
+}
+
+/// 生成 .code-snippets 文件内容
+pub fn render(snippets: &[CodeSnippet]) -> Result<String, String> {
+    let mut used_names = HashSet::new();
+    let mut entries = Vec::new();
+
+    for snippet in snippets {
+        // VS Code 以名称作为键，重名时追加序号
+        let mut name = snippet.title.clone();
+        let mut counter = 2;
+        while !used_names.insert(name.clone()) {
+            name = format!("{} ({})", snippet.title, counter);
+            counter += 1;
+        }
+
+        let mut prefixes = triggers(&snippet.tags);
+        if prefixes.is_empty() {
+            let slug = slugify(&snippet.title);
+            prefixes.push(if slug.is_empty() {
+                snippet.id.clone()
+            } else {
+                slug
+            });
+        }
+        let prefix = if prefixes.len() == 1 {
+            Value::String(prefixes.remove(0))
+        } else {
+            Value::from(prefixes)
+        };
+
+        entries.push((
+            name,
+            SnippetEntry {
+                prefix,
+                // 按 \n 拆分以保留末尾换行和 \r\n，导入时按 \n 合并即可还原
+                body: snippet.code.split('\n').map(str::to_string).collect(),
+                description: snippet.description.clone(),
+                scope: to_vscode_language(&snippet.language),
+            },
+        ));
+    }
+
+    serde_json::to_string_pretty(&OrderedEntries(entries))
+        .map_err(|e| format!("Failed to serialize VS Code snippets: {}", e))
+}
@@ -0,0 +1,212 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file formats/yasnippet.rs - Emacs yasnippet 代码片段格式
+ * @author Noah
+ * @description yasnippet 片段文件的解析与生成
+ * @created 2026-02-11
+ * @version 1.0.0
+ *
+ * 字段映射:
+ * - <mode>-mode 目录 <-> 语言
+ * - # name: <-> 标题
+ * - # key: <-> trigger:<key> 标签
+ * - # group:（以 . 分隔层级）或 mode 目录下的子目录 <-> 文件夹
+ * - "# --" 之后的内容 <-> 代码，占位符 ($1, ${1:name}, $0) 原样保留
+ */
+use super::{slugify, trigger_tag, triggers};
+use crate::models::{CodeSnippet, Folder, SnippetDraft};
+use std::collections::{HashMap, HashSet};
+
+/// 不影响片段内容、无需提示的指令
+const IGNORED_DIRECTIVES: &[&str] = &["contributor", "uuid"];
+
+/// Emacs major mode 目录名 -> 应用语言标识
+pub fn from_mode(mode_dir: &str) -> String {
+    let mode = mode_dir.strip_suffix("-mode").unwrap_or(mode_dir);
+    // tree-sitter 模式，如 rust-ts-mode
+    let mode = mode.strip_suffix("-ts").unwrap_or(mode);
+    match mode {
+        "sh" | "bash" | "shell-script" => "shell",
+        "js" | "js2" | "js3" | "rjsx" | "javascript" => "javascript",
+        "typescript" | "tsx" => "typescript",
+        "c++" => "cpp",
+        "csharp" => "csharp",
+        "emacs-lisp" | "lisp-interaction" => "elisp",
+        "rustic" => "rust",
+        "web" | "mhtml" | "html" => "html",
+        "gfm" => "markdown",
+        "latex" | "LaTeX" | "tex" => "latex",
+        "text" | "fundamental" | "prog" => "text",
+        other => other,
+    }
+    .to_string()
+}
+
+/// 应用语言标识 -> Emacs major mode 目录名
+pub fn to_mode(language: &str) -> String {
+    match language {
+        "shell" | "bash" => "sh-mode".to_string(),
+        "javascript" => "js-mode".to_string(),
+        "cpp" => "c++-mode".to_string(),
+        "elisp" => "emacs-lisp-mode".to_string(),
+        "latex" => "latex-mode".to_string(),
+        "" => "text-mode".to_string(),
+        other => format!("{}-mode", other),
+    }
+}
+
+/// 解析单个片段文件
+///
+/// mode_dir 为所在的 mode 目录名，group_dirs 为 mode 目录与文件之间的子目录，
+/// file_name 用于缺少 name 指令的片段
+pub fn parse(
+    content: &str,
+    mode_dir: &str,
+    group_dirs: &[String],
+    file_name: &str,
+) -> Result<SnippetDraft, String> {
+    let lines: Vec<&str> = content.lines().collect();
+    // 没有 "# --" 分隔行时整个文件都是片段内容
+    let separator = lines.iter().position(|line| line.trim_end() == "# --");
+    let (header, body) = match separator {
+        Some(index) => (&lines[..index], &lines[index + 1..]),
+        None => (&lines[..0], &lines[..]),
+    };
+
+    let mut name = String::new();
+    let mut key = String::new();
+    let mut group = String::new();
+    let mut notes = Vec::new();
+
+    for line in header {
+        let Some(directive) = line.strip_prefix('#') else {
+            continue;
+        };
+        let Some((directive, value)) = directive.split_once(':') else {
+            continue;
+        };
+        let directive = directive.trim();
+        let value = value.trim();
+        match directive {
+            "name" => name = value.to_string(),
+            "key" => key = value.to_string(),
+            "group" => group = value.to_string(),
+            "type" => {
+                if value == "command" {
+                    notes.push("Command snippet imported as plain text".to_string());
+                }
+            }
+            // 普通注释，如 "# -*- mode: snippet -*-"
+            _ if directive.contains(char::is_whitespace) => {}
+            _ if IGNORED_DIRECTIVES.contains(&directive) => {}
+            other => notes.push(format!("Directive {} not preserved", other)),
+        }
+    }
+
+    let raw = body.join("\n");
+    // 未转义的反引号为 Emacs Lisp 求值，转义的反引号还原为普通字符
+    let has_elisp = raw.replace("\\`", "").contains('`');
+    let code = raw.replace("\\`", "`");
+    if code.trim().is_empty() {
+        return Err("Snippet has no body".to_string());
+    }
+
+    let title = if !name.is_empty() {
+        name
+    } else if !key.is_empty() {
+        key.clone()
+    } else {
+        file_name.to_string()
+    };
+
+    let mut draft = SnippetDraft::new(title, code, from_mode(mode_dir));
+    if !key.is_empty() {
+        draft.tags = vec![trigger_tag(&key)];
+    }
+    draft.folder_path = if group.is_empty() {
+        group_dirs.to_vec()
+    } else {
+        group
+            .split('.')
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    if has_elisp {
+        draft
+            .notes
+            .push("Embedded Emacs Lisp kept verbatim".to_string());
+    }
+    draft.notes.extend(notes);
+    Ok(draft)
+}
+
+/// 文件夹的 group 值，如 "Backend.Rust"
+fn folder_group(folder_id: &str, folders: &HashMap<&str, &Folder>) -> String {
+    let mut names = Vec::new();
+    let mut current = folders.get(folder_id);
+    // 防御父级循环引用
+    while let Some(folder) = current.filter(|_| names.len() <= folders.len()) {
+        names.push(folder.name.trim());
+        current = folder
+            .parent_id
+            .as_deref()
+            .and_then(|parent| folders.get(parent));
+    }
+    names.reverse();
+    names.join(".")
+}
+
+/// 生成 yasnippet 目录内容，返回 (相对路径, 文件内容) 列表
+///
+/// 每个片段写入 <mode>/<触发词> 文件，同一目录中重名时追加序号；
+/// 所在文件夹写入 group 指令
+pub fn render(snippets: &[CodeSnippet], folders: &[Folder]) -> Vec<(String, String)> {
+    let folders: HashMap<&str, &Folder> = folders.iter().map(|f| (f.id.as_str(), f)).collect();
+    let mut used_paths = HashSet::new();
+    let mut files = Vec::new();
+
+    for snippet in snippets {
+        let mode = to_mode(&snippet.language);
+        let key = triggers(&snippet.tags).into_iter().next();
+
+        let base = key
+            .as_deref()
+            .map(slugify)
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| slugify(&snippet.title));
+        let base = if base.is_empty() {
+            snippet.id.clone()
+        } else {
+            base
+        };
+        let mut path = format!("{}/{}", mode, base);
+        let mut counter = 2;
+        while !used_paths.insert(path.clone()) {
+            path = format!("{}/{}-{}", mode, base, counter);
+            counter += 1;
+        }
+
+        let mut content = String::from("# -*- mode: snippet -*-\n");
+        content.push_str(&format!("# name: {}\n", snippet.title.replace('\n', " ")));
+        if let Some(key) = key {
+            content.push_str(&format!("# key: {}\n", key));
+        }
+        if let Some(folder_id) = &snippet.folder_id {
+            let group = folder_group(folder_id, &folders);
+            if !group.is_empty() {
+                content.push_str(&format!("# group: {}\n", group));
+            }
+        }
+        content.push_str("# --\n");
+        // 反引号在 yasnippet 中表示 Emacs Lisp 求值，需要转义；
+        // 文件末尾不追加换行，否则换行会成为片段内容的一部分
+        content.push_str(&snippet.code.replace('`', "\\`"));
+
+        files.push((path, content));
+    }
+
+    files
+}
@@ -0,0 +1,164 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file journal.rs - 撤销/重做历史
+ * @author Noah
+ * @description 记录每个修改数据的命令所影响的行，支持撤销和重做
+ * @created 2026-02-25
+ * @version 1.0.0
+ *
+ * 功能特性:
+ * - 一个用户操作（如删除文件夹、批量删除 TODO）记为一条历史，撤销时整体回退
+ * - 历史保存在资料库的 undo_journal 表中，重启后仍可撤销，最多保留 HISTORY_LIMIT 条
+ * - 撤销后执行新的操作会丢弃可重做的历史
+ * - 行在记录之后被其他途径修改（其他窗口、同步等）时拒绝撤销（conflict），避免覆盖新数据
+ * - 一次导入记为一条历史，可整体撤销；恢复备份不进入历史，并会清空历史
+ *
+ * 架构说明:
+ * Database 中修改数据的方法用 begin_action 开始一个事务，执行前用
+ * UndoAction::track 记录可能被修改的行（整行内容），修改通过 UndoAction::conn
+ * 在同一事务中执行；commit 重新读取这些行，并按同样的条件查找新增的行，得到每一行
+ * 操作前后的内容（RowChange），写入历史后与修改一起提交，其他窗口的写入不会混入。
+ * 撤销时把各行写回操作前的内容，重做时写回操作后的内容。这种基于行快照的方式
+ * 不需要为每个命令编写逆操作，外键级联删除的行只要被跟踪同样可以恢复。
+ */
+use crate::database::Database;
+use crate::error::{AppResult, ResultExt};
+use crate::formats::archive::TableRow;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteConnection;
+use sqlx::{Sqlite, Transaction};
+
+/// 保留的历史条数，超出时丢弃最早的操作
+pub const HISTORY_LIMIT: i64 = 100;
+
+/// 删除 TODO 时被外键级联删除的表
+const TODO_DEPENDENTS: &[&str] = &["todo_tag_relations", "todo_comments", "todo_attachments"];
+
+/// 一行数据在操作前后的内容，None 表示该行不存在
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RowChange {
+    pub table: String,
+    /// 主键列及其值
+    pub key: TableRow,
+    pub before: Option<TableRow>,
+    pub after: Option<TableRow>,
+}
+
+/// 表的主键列，标签关联表使用复合主键
+pub fn key_columns(table: &str) -> &'static [&'static str] {
+    match table {
+        "todo_tag_relations" => &["todo_id", "tag_id"],
+        _ => &["id"],
+    }
+}
+
+fn key_of(table: &str, row: &TableRow) -> TableRow {
+    key_columns(table)
+        .iter()
+        .map(|column| {
+            let value = row.get(*column).cloned().unwrap_or_default();
+            (column.to_string(), value)
+        })
+        .collect()
+}
+
+/// 正在记录的用户操作，持有执行修改的事务
+pub struct UndoAction {
+    label: String,
+    tx: Transaction<'static, Sqlite>,
+    /// 跟踪条件 (表, 列, 值)，commit 时重新查询以发现新增的行
+    selectors: Vec<(&'static str, &'static str, String)>,
+    /// 操作前的行 (表, 主键, 内容)
+    before: Vec<(&'static str, TableRow, TableRow)>,
+}
+
+impl UndoAction {
+    /// label 为显示在撤销/重做菜单中的操作名称
+    pub fn new(label: &str, tx: Transaction<'static, Sqlite>) -> Self {
+        UndoAction {
+            label: label.to_string(),
+            tx,
+            selectors: Vec::new(),
+            before: Vec::new(),
+        }
+    }
+
+    /// 执行修改使用的连接，与历史记录处于同一事务
+    pub fn conn(&mut self) -> &mut SqliteConnection {
+        &mut self.tx
+    }
+
+    /// 在修改前调用：记录 table 中 column = value 的行，返回这些行
+    pub async fn track(
+        &mut self,
+        table: &'static str,
+        column: &'static str,
+        value: &str,
+    ) -> AppResult<Vec<TableRow>> {
+        let rows = Database::select_rows(&mut self.tx, table, column, value).await?;
+        for row in &rows {
+            let key = key_of(table, row);
+            if !self.before.iter().any(|(t, k, _)| *t == table && *k == key) {
+                self.before.push((table, key, row.clone()));
+            }
+        }
+        self.selectors.push((table, column, value.to_string()));
+        Ok(rows)
+    }
+
+    /// 在修改前调用：记录 TODO、它所有层级的子任务以及它们的标签关联、评论和附件
+    pub async fn track_todo(&mut self, todo_id: &str) -> AppResult<()> {
+        let ids = Database::todo_subtree(&mut self.tx, todo_id).await?;
+        for id in &ids {
+            self.track("todos", "id", id).await?;
+            self.track("todos", "parent_id", id).await?;
+            for table in TODO_DEPENDENTS {
+                self.track(table, "todo_id", id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// table 中 column = value 的行都是本次操作新建的
+    pub fn created(&mut self, table: &'static str, column: &'static str, value: &str) {
+        self.selectors.push((table, column, value.to_string()));
+    }
+
+    /// 在修改完成后调用：比较前后内容并写入历史（没有变化时不记录），然后提交事务
+    pub async fn commit(mut self) -> AppResult<()> {
+        let mut changes = Vec::new();
+        for (table, key, row) in &self.before {
+            let after = Database::select_row(&mut self.tx, table, key).await?;
+            if after.as_ref() != Some(row) {
+                changes.push(RowChange {
+                    table: table.to_string(),
+                    key: key.clone(),
+                    before: Some(row.clone()),
+                    after,
+                });
+            }
+        }
+
+        for (table, column, value) in &self.selectors {
+            for row in Database::select_rows(&mut self.tx, table, column, value).await? {
+                let key = key_of(table, &row);
+                let known = self.before.iter().any(|(t, k, _)| t == table && *k == key)
+                    || changes.iter().any(|c| c.table == *table && c.key == key);
+                if !known {
+                    changes.push(RowChange {
+                        table: table.to_string(),
+                        key,
+                        before: None,
+                        after: Some(row),
+                    });
+                }
+            }
+        }
+
+        if !changes.is_empty() {
+            Database::push_undo_entry(&mut self.tx, &self.label, &changes).await?;
+        }
+        self.tx.commit().await.context("Failed to commit changes")
+    }
+}
@@ -11,14 +11,23 @@
  * 功能职责:
  * - 配置和构建 Tauri 应用实例
  * - 注册所有 Tauri 插件 (Shell, FS, Clipboard, etc.)
- * - 初始化数据库连接
+ * - 初始化数据库连接，启动自动备份调度
  * - 设置系统托盘 (System Tray) 和菜单
  * - 注册 invoke handler (命令处理)
  * - 管理全局状态 (AppState)
  */
+pub mod backup;
 mod commands;
-mod database;
-mod models;
+pub mod database;
+pub mod error;
+pub mod events;
+pub mod formats;
+pub mod journal;
+mod library;
+pub mod lint;
+mod migrations;
+pub mod models;
+pub mod repository;
 
 use database::Database;
 use std::collections::HashMap;
@@ -42,10 +51,21 @@ pub fn run() {
             // Async Database Initialization
             // Async Database Initialization
             let handle = app.handle().clone();
+            app.manage(library::MountedLibraries::default());
             tauri::async_runtime::spawn(async move {
-                match Database::new(&handle).await {
+                match library::open_initial(&handle).await {
                     Ok(db) => {
+                        events::forward(&handle, &db);
                         handle.manage(db);
+                        backup::start_scheduler(handle.clone());
+                        let mounts = handle.state::<library::MountedLibraries>();
+                        let db = handle.state::<Database>();
+                        if let Err(e) = mounts.open_saved(&handle, &db).await {
+                            let _ = handle.emit(
+                                "database-error",
+                                format!("Failed to open mounted libraries: {}", e),
+                            );
+                        }
                     }
                     Err(e) => {
                         // Log error without using eprintln! to avoid console window
@@ -118,7 +138,51 @@ pub fn run() {
             commands::get_all_folders,
             commands::delete_folder,
             commands::export_to_json,
+            commands::import_from_json,
+            commands::export_to_html,
+            commands::get_html_export_themes,
             commands::copy_to_clipboard,
+            // Backup commands
+            commands::create_backup_archive,
+            commands::inspect_backup_archive,
+            commands::restore_backup_archive,
+            commands::get_backup_settings,
+            commands::update_backup_settings,
+            commands::create_backup_snapshot,
+            commands::list_backup_snapshots,
+            commands::restore_backup_snapshot,
+            commands::list_libraries,
+            commands::get_current_library,
+            commands::create_library,
+            commands::open_library,
+            commands::remove_recent_library,
+            commands::list_mounted_libraries,
+            commands::mount_library,
+            commands::unmount_library,
+            commands::copy_snippet_to_library,
+            commands::get_undo_state,
+            commands::undo,
+            commands::redo,
+            commands::get_activity_log,
+            commands::clear_activity_log,
+            commands::check_database_integrity,
+            commands::get_database_stats,
+            commands::vacuum_database,
+            commands::optimize_search_index,
+            commands::rebuild_search_index,
+            commands::check_search_index,
+            // Editor snippet format commands
+            commands::import_vscode_snippets,
+            commands::export_vscode_snippets,
+            commands::import_jetbrains_templates,
+            commands::export_jetbrains_templates,
+            commands::import_editor_snippets,
+            commands::export_yasnippet,
+            commands::import_masscode_db,
+            commands::import_gist_export,
+            commands::preview_markdown_code_blocks,
+            commands::import_markdown_code_blocks,
+            commands::export_markdown_tree,
             // LSP Integration commands
             commands::check_command_available,
             commands::start_language_server,
@@ -148,6 +212,8 @@ pub fn run() {
             commands::file_exists,
             // Code Execution commands
             commands::execute_code,
+            // Lint commands
+            commands::lint_snippet,
             // Todo Management commands
             commands::create_todo,
             commands::get_todos,
@@ -162,6 +228,12 @@ pub fn run() {
             commands::get_todo_tags,
             commands::update_todo_tag,
             commands::delete_todo_tag,
+            // Todo Import & Export commands
+            commands::export_todos_ical,
+            commands::import_todos_ical,
+            commands::export_todos_text,
+            commands::import_todos_text,
+            commands::export_todos_csv,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
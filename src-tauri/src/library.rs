@@ -0,0 +1,483 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file library.rs - 资料库管理
+ * @author Noah
+ * @description 创建、打开和切换多个资料库文件，并记录最近打开的资料库
+ * @created 2026-02-21
+ * @version 1.0.0
+ *
+ * 功能特性:
+ * - 每个资料库是一个独立的 SQLite 文件（如工作、个人、团队共享）
+ * - 最近打开的资料库保存在应用数据目录的 libraries.json 中，最近使用的排在最前
+ * - 启动时打开上次使用的资料库，文件不存在或无法打开时回退到默认资料库
+ * - 切换成功后发送 library-changed 事件（载荷为 LibraryInfo），前端据此重新加载数据
+ * - 可额外挂载多个只读资料库（如共享盘上的团队库），列表和搜索结果会合并，
+ *   来自挂载库的代码片段带有 library_id；挂载列表保存在 mounted_libraries.json
+ * - 启动时无法打开的挂载库发送 mounted-library-error 事件（载荷为 MountedLibraryInfo）
+ * - 当前资料库不能被挂载，已挂载的资料库也不能切换为当前资料库
+ *
+ * 架构说明:
+ * Tauri 托管的 Database 状态本身不会被替换，切换时由 Database::switch_library
+ * 替换其内部的连接池，因此所有命令在切换后自动使用新的资料库。
+ * 挂载的资料库由单独的托管状态 MountedLibraries 持有，以只读连接打开，
+ * 写入在命令层被拒绝（read_only），SQLite 层面同样无法写入。
+ */
+use crate::database::Database;
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::models::{
+    CodeSnippet, LibraryInfo, MountedLibrary, MountedLibraryInfo, RecentLibrary, SearchQuery,
+};
+use crate::repository::SnippetRepository;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager};
+
+const RECENT_FILE: &str = "libraries.json";
+const RECENT_LIMIT: usize = 10;
+const MOUNTS_FILE: &str = "mounted_libraries.json";
+
+pub const LIBRARY_CHANGED_EVENT: &str = "library-changed";
+pub const MOUNT_ERROR_EVENT: &str = "mounted-library-error";
+
+// ============================================================================
+// 最近打开列表
+// ============================================================================
+
+fn recent_file(app: &AppHandle) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(RECENT_FILE))
+}
+
+/// 读取最近打开的资料库，文件不存在或无法解析时返回空列表
+fn load_recent(app: &AppHandle) -> AppResult<Vec<RecentLibrary>> {
+    match std::fs::read_to_string(recent_file(app)?) {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+        Err(_) => Ok(Vec::new()),
+    }
+}
+
+fn save_recent(app: &AppHandle, recent: &[RecentLibrary]) -> AppResult<()> {
+    let path = recent_file(app)?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let content =
+        serde_json::to_string_pretty(recent).context("Failed to serialize recent libraries")?;
+    std::fs::write(path, content).context("Failed to save recent libraries")
+}
+
+/// 把资料库移到最近打开列表的最前面
+fn touch_recent(app: &AppHandle, path: &Path) -> AppResult<()> {
+    let path = path.to_string_lossy().to_string();
+    let mut recent = load_recent(app)?;
+    recent.retain(|entry| entry.path != path);
+    recent.insert(
+        0,
+        RecentLibrary {
+            path,
+            last_opened_at: chrono::Utc::now().timestamp_millis(),
+        },
+    );
+    recent.truncate(RECENT_LIMIT);
+    save_recent(app, &recent)
+}
+
+fn library_info(entry: &RecentLibrary, current: Option<&Path>) -> LibraryInfo {
+    let path = Path::new(&entry.path);
+    LibraryInfo {
+        path: entry.path.clone(),
+        name: path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| entry.path.clone()),
+        last_opened_at: entry.last_opened_at,
+        is_current: current == Some(path),
+        exists: path.exists(),
+    }
+}
+
+// ============================================================================
+// 资料库
+// ============================================================================
+
+/// 启动时打开上次使用的资料库
+pub async fn open_initial(app: &AppHandle) -> AppResult<Database> {
+    let last = load_recent(app)?
+        .into_iter()
+        .next()
+        .map(|entry| PathBuf::from(entry.path))
+        .filter(|path| path.exists());
+
+    let db = match last {
+        Some(path) => match Database::open(&path, false).await {
+            Ok(db) => db,
+            Err(_e) => {
+                #[cfg(debug_assertions)]
+                println!("Failed to open library {}: {}", path.display(), _e);
+
+                Database::new(app).await?
+            }
+        },
+        None => Database::new(app).await?,
+    };
+
+    if let Some(path) = db.path() {
+        touch_recent(app, &path)?;
+    }
+    Ok(db)
+}
+
+/// 最近打开的资料库，当前资料库始终在列表中
+pub fn list(app: &AppHandle, db: &Database) -> AppResult<Vec<LibraryInfo>> {
+    let current = db.path();
+    Ok(load_recent(app)?
+        .iter()
+        .map(|entry| library_info(entry, current.as_deref()))
+        .collect())
+}
+
+pub fn current(app: &AppHandle, db: &Database) -> AppResult<LibraryInfo> {
+    list(app, db)?
+        .into_iter()
+        .find(|library| library.is_current)
+        .ok_or(AppError::DatabaseNotReady)
+}
+
+/// 规范化资料库路径，使同一文件的不同写法指向同一条记录
+///
+/// create 为 true 时要求文件尚不存在，否则要求文件已存在
+fn resolve_path(path: &str, create: bool) -> AppResult<PathBuf> {
+    let path = Path::new(path);
+    if !path.is_absolute() {
+        return Err(AppError::validation(format!(
+            "Library path must be absolute: {}",
+            path.display()
+        )));
+    }
+
+    if create {
+        if path.exists() {
+            return Err(AppError::validation(format!(
+                "A file already exists at {}",
+                path.display()
+            )));
+        }
+        let (Some(dir), Some(file_name)) = (path.parent(), path.file_name()) else {
+            return Err(AppError::validation(format!(
+                "Invalid library path: {}",
+                path.display()
+            )));
+        };
+        std::fs::create_dir_all(dir).context("Failed to create library directory")?;
+        Ok(std::fs::canonicalize(dir)?.join(file_name))
+    } else {
+        if !path.is_file() {
+            return Err(AppError::not_found("library", path.to_string_lossy()));
+        }
+        Ok(std::fs::canonicalize(path)?)
+    }
+}
+
+/// path 是否为当前资料库，按规范化后的路径比较
+fn is_current(db: &Database, path: &Path) -> bool {
+    db.path().is_some_and(|current| {
+        current == path || std::fs::canonicalize(&current).is_ok_and(|current| current == path)
+    })
+}
+
+/// 创建（create 为 true）或打开资料库并切换过去，成功后发送 library-changed 事件
+///
+/// 已挂载为只读的资料库需要先卸载
+pub async fn switch(
+    app: &AppHandle,
+    db: &Database,
+    path: &str,
+    create: bool,
+) -> AppResult<LibraryInfo> {
+    let path = resolve_path(path, create)?;
+    if is_current(db, &path) {
+        return current(app, db);
+    }
+    if app.state::<MountedLibraries>().is_mounted(&path).await {
+        return Err(AppError::validation(format!(
+            "Library is mounted as read-only; unmount it before opening: {}",
+            path.display()
+        )));
+    }
+
+    db.switch_library(&path, create).await?;
+    touch_recent(app, &path)?;
+
+    let info = current(app, db)?;
+    let _ = app.emit(LIBRARY_CHANGED_EVENT, info.clone());
+    Ok(info)
+}
+
+/// 从最近打开列表中移除资料库（不删除文件）
+pub fn forget(app: &AppHandle, db: &Database, path: &str) -> AppResult<Vec<LibraryInfo>> {
+    if db.path().is_some_and(|current| current == Path::new(path)) {
+        return Err(AppError::validation(
+            "The current library cannot be removed from the list",
+        ));
+    }
+
+    let mut recent = load_recent(app)?;
+    recent.retain(|entry| entry.path != path);
+    save_recent(app, &recent)?;
+    list(app, db)
+}
+
+// ============================================================================
+// 挂载的只读资料库
+// ============================================================================
+
+struct Mount {
+    library: MountedLibrary,
+    /// 打开失败时保留错误，挂载记录不会因共享盘暂时离线而丢失
+    db: AppResult<Database>,
+    /// 最近一次读取代码片段失败的错误（如架构版本过旧），成功读取后清除
+    query_error: std::sync::Mutex<Option<String>>,
+}
+
+impl Mount {
+    async fn open(library: MountedLibrary) -> Self {
+        let db = Database::open_read_only(Path::new(&library.path)).await;
+        Mount::new(library, db)
+    }
+
+    fn new(library: MountedLibrary, db: AppResult<Database>) -> Self {
+        Mount {
+            library,
+            db,
+            query_error: std::sync::Mutex::new(None),
+        }
+    }
+
+    fn query_error(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.query_error
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn info(&self) -> MountedLibraryInfo {
+        MountedLibraryInfo {
+            id: self.library.id.clone(),
+            name: self.library.name.clone(),
+            path: self.library.path.clone(),
+            available: self.db.is_ok(),
+            error: match &self.db {
+                Ok(_) => self.query_error().clone(),
+                Err(e) => Some(e.to_string()),
+            },
+        }
+    }
+
+    /// 挂载库中的代码片段，标记来源资料库；挂载库不可用时返回空列表，
+    /// 读取失败的错误记录下来，由 info 的 error 字段报告
+    async fn snippets(&self, query: Option<&SearchQuery>) -> Vec<CodeSnippet> {
+        let Ok(db) = &self.db else {
+            return Vec::new();
+        };
+        let result = match query {
+            Some(query) => db.search_snippets(query.clone()).await,
+            None => db.get_all_snippets().await,
+        };
+        let mut snippets = match result {
+            Ok(snippets) => {
+                *self.query_error() = None;
+                snippets
+            }
+            Err(e) => {
+                *self.query_error() = Some(e.to_string());
+                Vec::new()
+            }
+        };
+        for snippet in &mut snippets {
+            snippet.library_id = Some(self.library.id.clone());
+        }
+        snippets
+    }
+}
+
+/// 已挂载的只读资料库，作为 Tauri 托管状态
+#[derive(Default)]
+pub struct MountedLibraries {
+    mounts: tokio::sync::RwLock<Vec<Mount>>,
+}
+
+fn current_library_error() -> AppError {
+    AppError::validation("The current library cannot be mounted as read-only")
+}
+
+fn mounts_file(app: &AppHandle) -> AppResult<PathBuf> {
+    Ok(app.path().app_data_dir()?.join(MOUNTS_FILE))
+}
+
+impl MountedLibraries {
+    /// 启动时重新挂载上次的资料库，db 为当前资料库
+    ///
+    /// 无法打开的挂载库保留在列表中，并逐个发送 mounted-library-error 事件
+    pub async fn open_saved(&self, app: &AppHandle, db: &Database) -> AppResult<()> {
+        let saved: Vec<MountedLibrary> = match std::fs::read_to_string(mounts_file(app)?) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+
+        let mut mounts = Vec::new();
+        for library in saved {
+            let mount = if is_current(db, Path::new(&library.path)) {
+                Mount::new(library, Err(current_library_error()))
+            } else {
+                Mount::open(library).await
+            };
+            if mount.db.is_err() {
+                let _ = app.emit(MOUNT_ERROR_EVENT, mount.info());
+            }
+            mounts.push(mount);
+        }
+        *self.mounts.write().await = mounts;
+        Ok(())
+    }
+
+    async fn is_mounted(&self, path: &Path) -> bool {
+        let path = path.to_string_lossy();
+        self.mounts
+            .read()
+            .await
+            .iter()
+            .any(|m| m.library.path == path)
+    }
+
+    fn save(app: &AppHandle, mounts: &[Mount]) -> AppResult<()> {
+        let libraries: Vec<&MountedLibrary> = mounts.iter().map(|m| &m.library).collect();
+        let content = serde_json::to_string_pretty(&libraries)
+            .context("Failed to serialize mounted libraries")?;
+        std::fs::write(mounts_file(app)?, content).context("Failed to save mounted libraries")
+    }
+
+    pub async fn list(&self) -> Vec<MountedLibraryInfo> {
+        self.mounts.read().await.iter().map(Mount::info).collect()
+    }
+
+    /// 以只读方式挂载资料库文件，name 缺省时使用文件名
+    pub async fn mount(
+        &self,
+        app: &AppHandle,
+        db: &Database,
+        path: &str,
+        name: Option<String>,
+    ) -> AppResult<MountedLibraryInfo> {
+        let path = resolve_path(path, false)?;
+        if is_current(db, &path) {
+            return Err(current_library_error());
+        }
+
+        let path_string = path.to_string_lossy().to_string();
+        let mut mounts = self.mounts.write().await;
+        if mounts.iter().any(|m| m.library.path == path_string) {
+            return Err(AppError::validation(format!(
+                "Library is already mounted: {}",
+                path_string
+            )));
+        }
+
+        let name = name
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty())
+            .or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+            })
+            .unwrap_or_else(|| path_string.clone());
+        let mount = Mount::new(
+            MountedLibrary {
+                id: uuid::Uuid::new_v4().to_string(),
+                name,
+                path: path_string,
+            },
+            Ok(Database::open_read_only(&path).await?),
+        );
+
+        let info = mount.info();
+        mounts.push(mount);
+        Self::save(app, &mounts)?;
+        Ok(info)
+    }
+
+    pub async fn unmount(&self, app: &AppHandle, library_id: &str) -> AppResult<()> {
+        let mut mounts = self.mounts.write().await;
+        let index = mounts
+            .iter()
+            .position(|m| m.library.id == library_id)
+            .ok_or_else(|| AppError::not_found("mounted_library", library_id))?;
+
+        let mount = mounts.remove(index);
+        Self::save(app, &mounts)?;
+        if let Ok(db) = mount.db {
+            db.close().await;
+        }
+        Ok(())
+    }
+
+    /// 合并当前资料库与所有挂载库的代码片段，按更新时间从新到旧排列
+    ///
+    /// query 为 None 时列出全部，否则按同一条件搜索每个资料库
+    pub async fn merge(
+        &self,
+        mut snippets: Vec<CodeSnippet>,
+        query: Option<&SearchQuery>,
+    ) -> Vec<CodeSnippet> {
+        for mount in self.mounts.read().await.iter() {
+            snippets.extend(mount.snippets(query).await);
+        }
+        snippets.sort_by_key(|snippet| std::cmp::Reverse(snippet.updated_at));
+        snippets
+    }
+
+    /// 在挂载库中按 ID 查找代码片段
+    pub async fn find_snippet(&self, snippet_id: &str) -> AppResult<Option<CodeSnippet>> {
+        for mount in self.mounts.read().await.iter() {
+            let Ok(db) = &mount.db else {
+                continue;
+            };
+            if let Some(mut snippet) = db.get_snippet(snippet_id).await? {
+                snippet.library_id = Some(mount.library.id.clone());
+                return Ok(Some(snippet));
+            }
+        }
+        Ok(None)
+    }
+
+    /// 读取指定挂载库中的代码片段
+    pub async fn get_snippet(&self, library_id: &str, snippet_id: &str) -> AppResult<CodeSnippet> {
+        let mounts = self.mounts.read().await;
+        let mount = mounts
+            .iter()
+            .find(|m| m.library.id == library_id)
+            .ok_or_else(|| AppError::not_found("mounted_library", library_id))?;
+        let db = mount.db.as_ref().map_err(|e| e.clone())?;
+
+        let mut snippet = db
+            .get_snippet(snippet_id)
+            .await?
+            .ok_or_else(|| AppError::not_found("snippet", snippet_id))?;
+        snippet.library_id = Some(library_id.to_string());
+        Ok(snippet)
+    }
+
+    /// 拒绝修改只存在于挂载库中的代码片段
+    pub async fn ensure_writable(&self, db: &Database, snippet_id: &str) -> AppResult<()> {
+        if db.get_snippet(snippet_id).await?.is_some() {
+            return Ok(());
+        }
+        match self.find_snippet(snippet_id).await? {
+            Some(snippet) => Err(AppError::ReadOnly {
+                details: format!(
+                    "Snippet {} belongs to mounted library {}",
+                    snippet_id,
+                    snippet.library_id.unwrap_or_default()
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,467 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file lint.rs - 代码片段静态检查模块
+ * @author Noah
+ * @description 在临时目录中调用各语言的 Linter，并将其机器可读输出转换为 LSP 诊断模型
+ * @created 2026-02-05
+ * @version 1.0.0
+ *
+ * 功能特性:
+ * - Rust: clippy-driver (--error-format=json)
+ * - Python: ruff (--output-format=json)
+ * - JavaScript/TypeScript: eslint (-f json)
+ * - Shell: shellcheck (-f json)
+ * - Go: golangci-lint (--out-format json)
+ *
+ * 架构说明:
+ * 编辑器无需运行语言服务器即可显示波浪线提示。所有 Linter 输出的行列号
+ * 均为 1 起始，这里统一转换为 LSP 使用的 0 起始坐标。
+ */
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::models::{Diagnostic, LSPPosition, LSPRange, LintResult};
+use serde_json::Value;
+use std::path::Path;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::process::Command as TokioCommand;
+
+/// 单次检查的最长运行时间，超时后终止 Linter 进程
+const LINT_TIMEOUT: Duration = Duration::from_secs(30);
+
+// LSP DiagnosticSeverity
+const SEVERITY_ERROR: i32 = 1;
+const SEVERITY_WARNING: i32 = 2;
+const SEVERITY_INFORMATION: i32 = 3;
+const SEVERITY_HINT: i32 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinterKind {
+    Clippy,
+    Ruff,
+    Eslint,
+    Shellcheck,
+    GolangciLint,
+}
+
+impl LinterKind {
+    fn name(&self) -> &'static str {
+        match self {
+            LinterKind::Clippy => "clippy",
+            LinterKind::Ruff => "ruff",
+            LinterKind::Eslint => "eslint",
+            LinterKind::Shellcheck => "shellcheck",
+            LinterKind::GolangciLint => "golangci-lint",
+        }
+    }
+
+    /// 把 Linter 的 JSON 输出（clippy 为 stderr，其余为 stdout）转换为诊断
+    pub fn parse(&self, report: &str) -> Result<Vec<Diagnostic>, String> {
+        match self {
+            LinterKind::Clippy => parse_rustc_json(report),
+            LinterKind::Ruff => parse_ruff_json(report),
+            LinterKind::Eslint => parse_eslint_json(report),
+            LinterKind::Shellcheck => parse_shellcheck_json(report),
+            LinterKind::GolangciLint => parse_golangci_json(report),
+        }
+    }
+}
+
+/// 单个语言对应的 Linter 配置
+struct Linter {
+    kind: LinterKind,
+    command: &'static str,
+    file_name: &'static str,
+}
+
+fn linter_for(language: &str) -> Option<Linter> {
+    let linter = match language {
+        "rust" => Linter {
+            kind: LinterKind::Clippy,
+            command: "clippy-driver",
+            file_name: "snippet.rs",
+        },
+        "python" => Linter {
+            kind: LinterKind::Ruff,
+            command: "ruff",
+            file_name: "snippet.py",
+        },
+        "javascript" => Linter {
+            kind: LinterKind::Eslint,
+            command: "eslint",
+            file_name: "snippet.js",
+        },
+        "typescript" => Linter {
+            kind: LinterKind::Eslint,
+            command: "eslint",
+            file_name: "snippet.ts",
+        },
+        "shell" | "bash" | "sh" => Linter {
+            kind: LinterKind::Shellcheck,
+            command: "shellcheck",
+            file_name: "snippet.sh",
+        },
+        "go" => Linter {
+            kind: LinterKind::GolangciLint,
+            command: "golangci-lint",
+            file_name: "main.go",
+        },
+        _ => return None,
+    };
+    Some(linter)
+}
+
+/// 对代码片段运行 Linter 并返回诊断信息
+///
+/// 不支持的语言返回 validation 错误；无法启动 Linter 或读写临时文件返回 io 错误，
+/// 超时、Linter 自身出错或输出无法解析返回 internal 错误
+pub async fn lint_snippet(language: &str, code: &str) -> AppResult<LintResult> {
+    let linter = linter_for(language).ok_or_else(|| {
+        AppError::validation(format!("No linter configured for language: {}", language))
+    })?;
+
+    let work_dir = std::env::temp_dir().join(format!("snippetshub_lint_{}", uuid::Uuid::new_v4()));
+    tokio::fs::create_dir_all(&work_dir)
+        .await
+        .context("Failed to create lint directory")?;
+
+    let result = run_linter(&linter, language, code, &work_dir).await;
+
+    // 清理临时目录
+    let _ = tokio::fs::remove_dir_all(&work_dir).await;
+
+    result
+}
+
+async fn run_linter(
+    linter: &Linter,
+    language: &str,
+    code: &str,
+    work_dir: &Path,
+) -> AppResult<LintResult> {
+    let file_path = work_dir.join(linter.file_name);
+    tokio::fs::write(&file_path, code)
+        .await
+        .context("Failed to write snippet file")?;
+
+    let mut cmd = TokioCommand::new(linter.command);
+    cmd.current_dir(work_dir)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    match linter.kind {
+        LinterKind::Clippy => {
+            // 含 main 函数的片段按可执行程序检查，避免 dead_code 误报
+            let crate_type = if code.contains("fn main(") {
+                "bin"
+            } else {
+                "lib"
+            };
+            cmd.arg("--edition=2021")
+                .arg(format!("--crate-type={}", crate_type))
+                .arg("--error-format=json")
+                .arg("--emit=metadata")
+                .arg("--out-dir")
+                .arg(work_dir)
+                .arg(&file_path);
+        }
+        LinterKind::Ruff => {
+            cmd.arg("check")
+                .arg("--output-format=json")
+                .arg("--no-cache")
+                .arg("--exit-zero")
+                .arg(&file_path);
+        }
+        LinterKind::Eslint => {
+            cmd.arg("-f").arg("json").arg(&file_path);
+        }
+        LinterKind::Shellcheck => {
+            cmd.arg("-f").arg("json").arg(&file_path);
+        }
+        LinterKind::GolangciLint => {
+            tokio::fs::write(work_dir.join("go.mod"), "module snippet\n\ngo 1.21\n")
+                .await
+                .context("Failed to write go.mod")?;
+            cmd.arg("run").arg("--out-format").arg("json").arg("./...");
+        }
+    }
+
+    let name = linter.kind.name();
+    let output = tokio::time::timeout(LINT_TIMEOUT, cmd.output())
+        .await
+        .map_err(|_| {
+            AppError::internal(format!(
+                "{} did not finish within {} seconds",
+                name,
+                LINT_TIMEOUT.as_secs()
+            ))
+        })?
+        .with_context(|| format!("Failed to run {}", linter.command))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+
+    // clippy 的诊断输出在 stderr，其余 Linter 在 stdout
+    let report = match linter.kind {
+        LinterKind::Clippy => &stderr,
+        _ => &stdout,
+    };
+
+    // Linter 发现问题时通常以非零状态码退出，此时仍有 JSON 输出；
+    // 非零退出且没有 JSON 输出说明 Linter 本身出错（缺少配置、参数不受支持、崩溃等）
+    if !output.status.success() && !has_json_output(report) {
+        return Err(AppError::internal(format!(
+            "{} failed ({}): {}",
+            name,
+            output.status,
+            stderr.trim()
+        )));
+    }
+
+    let diagnostics = linter.kind.parse(report).map_err(|e| {
+        AppError::internal(format!(
+            "Failed to parse {} output: {} {}",
+            name,
+            e,
+            stderr.trim()
+        ))
+    })?;
+
+    Ok(LintResult {
+        language: language.to_string(),
+        linter: name.to_string(),
+        diagnostics,
+    })
+}
+
+/// 将 1 起始的行列号转换为 LSP 范围
+fn range_from_one_based(line: u64, column: u64, end_line: u64, end_column: u64) -> LSPRange {
+    let start = LSPPosition {
+        line: line.saturating_sub(1) as u32,
+        character: column.saturating_sub(1) as u32,
+    };
+    let mut end = LSPPosition {
+        line: end_line.saturating_sub(1) as u32,
+        character: end_column.saturating_sub(1) as u32,
+    };
+    if end.line < start.line || (end.line == start.line && end.character < start.character) {
+        end = start.clone();
+    }
+    LSPRange { start, end }
+}
+
+fn u64_field(value: &Value, key: &str) -> Option<u64> {
+    value.get(key).and_then(Value::as_u64)
+}
+
+fn str_field(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(str::to_string)
+}
+
+/// rustc/clippy: stderr 中每行一个 JSON 诊断对象
+fn parse_rustc_json(output: &str) -> Result<Vec<Diagnostic>, String> {
+    let mut diagnostics = Vec::new();
+
+    for line in output.lines().filter(|l| l.trim_start().starts_with('{')) {
+        let message: Value = serde_json::from_str(line).map_err(|e| e.to_string())?;
+
+        let severity = match message.get("level").and_then(Value::as_str) {
+            Some("error") | Some("error: internal compiler error") => SEVERITY_ERROR,
+            Some("warning") => SEVERITY_WARNING,
+            Some("note") => SEVERITY_INFORMATION,
+            Some("help") => SEVERITY_HINT,
+            _ => continue,
+        };
+
+        let spans = message
+            .get("spans")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        // 没有位置信息的汇总消息（如 "aborting due to ..."）不显示
+        let Some(span) = spans
+            .iter()
+            .find(|s| s.get("is_primary").and_then(Value::as_bool) == Some(true))
+            .or_else(|| spans.first())
+        else {
+            continue;
+        };
+
+        diagnostics.push(Diagnostic {
+            range: range_from_one_based(
+                u64_field(span, "line_start").unwrap_or(1),
+                u64_field(span, "column_start").unwrap_or(1),
+                u64_field(span, "line_end").unwrap_or(1),
+                u64_field(span, "column_end").unwrap_or(1),
+            ),
+            severity: Some(severity),
+            code: message
+                .get("code")
+                .and_then(|c| c.get("code"))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            source: Some("clippy".to_string()),
+            message: str_field(&message, "message").unwrap_or_default(),
+            related_information: None,
+        });
+    }
+
+    Ok(diagnostics)
+}
+
+/// ruff: 顶层为诊断数组，location/end_location 使用 row/column
+fn parse_ruff_json(output: &str) -> Result<Vec<Diagnostic>, String> {
+    let items: Vec<Value> = parse_json_or_empty(output)?;
+
+    Ok(items
+        .iter()
+        .map(|item| {
+            let start = item.get("location").cloned().unwrap_or_default();
+            let end = item
+                .get("end_location")
+                .cloned()
+                .unwrap_or_else(|| start.clone());
+            // ruff 中 code 为空表示语法错误
+            let code = str_field(item, "code");
+            Diagnostic {
+                range: range_from_one_based(
+                    u64_field(&start, "row").unwrap_or(1),
+                    u64_field(&start, "column").unwrap_or(1),
+                    u64_field(&end, "row").unwrap_or(1),
+                    u64_field(&end, "column").unwrap_or(1),
+                ),
+                severity: Some(if code.is_some() {
+                    SEVERITY_WARNING
+                } else {
+                    SEVERITY_ERROR
+                }),
+                code,
+                source: Some("ruff".to_string()),
+                message: str_field(item, "message").unwrap_or_default(),
+                related_information: None,
+            }
+        })
+        .collect())
+}
+
+/// eslint: 每个文件一个结果对象，messages 中 severity 1 为警告、2 为错误
+fn parse_eslint_json(output: &str) -> Result<Vec<Diagnostic>, String> {
+    let files: Vec<Value> = parse_json_or_empty(output)?;
+    let mut diagnostics = Vec::new();
+
+    for file in &files {
+        let messages = file
+            .get("messages")
+            .and_then(Value::as_array)
+            .cloned()
+            .unwrap_or_default();
+        for message in &messages {
+            let line = u64_field(message, "line").unwrap_or(1);
+            let column = u64_field(message, "column").unwrap_or(1);
+            diagnostics.push(Diagnostic {
+                range: range_from_one_based(
+                    line,
+                    column,
+                    u64_field(message, "endLine").unwrap_or(line),
+                    u64_field(message, "endColumn").unwrap_or(column),
+                ),
+                severity: Some(match u64_field(message, "severity") {
+                    Some(2) => SEVERITY_ERROR,
+                    _ => SEVERITY_WARNING,
+                }),
+                code: str_field(message, "ruleId"),
+                source: Some("eslint".to_string()),
+                message: str_field(message, "message").unwrap_or_default(),
+                related_information: None,
+            });
+        }
+    }
+
+    Ok(diagnostics)
+}
+
+/// shellcheck: 顶层为诊断数组，code 为数字（显示为 SC2086 形式）
+fn parse_shellcheck_json(output: &str) -> Result<Vec<Diagnostic>, String> {
+    let items: Vec<Value> = parse_json_or_empty(output)?;
+
+    Ok(items
+        .iter()
+        .map(|item| {
+            let line = u64_field(item, "line").unwrap_or(1);
+            let column = u64_field(item, "column").unwrap_or(1);
+            Diagnostic {
+                range: range_from_one_based(
+                    line,
+                    column,
+                    u64_field(item, "endLine").unwrap_or(line),
+                    u64_field(item, "endColumn").unwrap_or(column),
+                ),
+                severity: Some(match item.get("level").and_then(Value::as_str) {
+                    Some("error") => SEVERITY_ERROR,
+                    Some("warning") => SEVERITY_WARNING,
+                    Some("info") => SEVERITY_INFORMATION,
+                    _ => SEVERITY_HINT,
+                }),
+                code: u64_field(item, "code").map(|c| format!("SC{}", c)),
+                source: Some("shellcheck".to_string()),
+                message: str_field(item, "message").unwrap_or_default(),
+                related_information: None,
+            }
+        })
+        .collect())
+}
+
+/// golangci-lint: { "Issues": [{ "FromLinter", "Text", "Severity", "Pos": { "Line", "Column" } }] }
+fn parse_golangci_json(output: &str) -> Result<Vec<Diagnostic>, String> {
+    // golangci-lint 会在 JSON 之后追加文本摘要，只解析第一行
+    let json_line = output.lines().find(|l| l.trim_start().starts_with('{'));
+    let Some(json_line) = json_line else {
+        return Ok(Vec::new());
+    };
+    let report: Value = serde_json::from_str(json_line).map_err(|e| e.to_string())?;
+
+    let issues = report
+        .get("Issues")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    Ok(issues
+        .iter()
+        .map(|issue| {
+            let pos = issue.get("Pos").cloned().unwrap_or_default();
+            let line = u64_field(&pos, "Line").unwrap_or(1);
+            let column = u64_field(&pos, "Column").unwrap_or(1).max(1);
+            Diagnostic {
+                range: range_from_one_based(line, column, line, column),
+                severity: Some(match issue.get("Severity").and_then(Value::as_str) {
+                    Some("error") => SEVERITY_ERROR,
+                    Some("info") => SEVERITY_INFORMATION,
+                    _ => SEVERITY_WARNING,
+                }),
+                code: str_field(issue, "FromLinter"),
+                source: Some("golangci-lint".to_string()),
+                message: str_field(issue, "Text").unwrap_or_default(),
+                related_information: None,
+            }
+        })
+        .collect())
+}
+
+/// 输出中是否有 JSON 内容（整体为 JSON，或有以 { 开头的 JSON 行）
+fn has_json_output(output: &str) -> bool {
+    output
+        .lines()
+        .any(|line| matches!(line.trim_start().chars().next(), Some('{') | Some('[')))
+}
+
+/// 空输出表示没有诊断（调用方已确认 Linter 正常退出）
+fn parse_json_or_empty(output: &str) -> Result<Vec<Value>, String> {
+    let trimmed = output.trim();
+    if trimmed.is_empty() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_str(trimmed).map_err(|e| e.to_string())
+}
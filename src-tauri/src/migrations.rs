@@ -0,0 +1,568 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file migrations.rs - 数据库结构迁移
+ * @author Noah
+ * @description 按版本顺序执行数据库结构迁移，并在 schema_migrations 表中记录
+ * @created 2026-02-18
+ * @version 1.0.0
+ *
+ * 规则:
+ * - 每个迁移有唯一递增的版本号，在单独的事务中执行，失败时整体回滚
+ * - 已执行迁移的 SHA-256 校验和保存在 schema_migrations 中，
+ *   启动时若发现已发布的迁移被修改则拒绝打开数据库
+ * - 数据库中记录的版本高于应用支持的最新版本时拒绝打开，避免旧版本写坏新结构
+ * - 已发布的迁移不可修改，结构变更一律追加新迁移
+ *
+ * 早期版本没有迁移记录，表由 CREATE TABLE IF NOT EXISTS 创建，列由
+ * 忽略错误的 ALTER TABLE 添加。迁移 1 以同样幂等的方式描述这一结构，
+ * 因此既能初始化新数据库，也能接管这些旧数据库。
+ */
+use crate::error::{AppError, AppResult, ResultExt};
+use crate::formats::archive::sha256_hex;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+
+/// 迁移中的单个步骤
+pub enum Step {
+    Sql(&'static str),
+    /// 列不存在时添加；早期版本未记录迁移，列可能已经存在
+    AddColumn {
+        table: &'static str,
+        column: &'static str,
+        definition: &'static str,
+    },
+    /// 按表当前的列生成 ACTIVITY_TABLES 中各表的活动记录触发器
+    ActivityTriggers,
+}
+
+/// 记录活动日志的表及其实体类型
+///
+/// 触发器覆盖除主键外的所有列（更新时忽略 updated_at），由表结构生成；
+/// 之后对这些表执行 AddColumn 时会自动重建触发器，新列无需手动补充。
+/// 以其他方式修改这些表的结构时，需要在同一迁移中再执行一次 ActivityTriggers。
+pub const ACTIVITY_TABLES: &[(&str, &str)] = &[
+    ("snippets", "snippet"),
+    ("folders", "folder"),
+    ("workspaces", "workspace"),
+    ("projects", "project"),
+    ("git_repositories", "git_repository"),
+    ("todos", "todo"),
+    ("todo_tags", "todo_tag"),
+];
+
+/// 活动记录触发器模板，只记录变化的字段名，不复制字段值（如代码内容）
+///
+/// {table}、{suffix} 等占位符由 create_activity_triggers 填入；
+/// 模板与 ACTIVITY_TABLES 一起参与 ActivityTriggers 步骤的校验和
+const ACTIVITY_TRIGGER_SQL: &str = "CREATE TRIGGER activity_{table}_{suffix} AFTER {event} ON {table}
+WHEN NOT EXISTS (SELECT 1 FROM activity_pause)
+BEGIN
+  INSERT INTO activity_log (entity_type, entity_id, action, changes, created_at)
+  SELECT '{entity}', {row}.id, '{action}', changes, CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER)
+  FROM (
+    SELECT json_group_array(field) AS changes, COUNT(*) AS changed
+    FROM ({fields})
+  )
+  WHERE changed > 0;
+END;";
+
+/// 触发器中每一列的比较，多列以 UNION ALL 连接后填入 {fields}
+const ACTIVITY_FIELD_SQL: &str = "SELECT '{column}' AS field WHERE {old} IS NOT {new}";
+
+impl Step {
+    /// 参与校验和计算的文本
+    fn text(&self) -> String {
+        match self {
+            Step::Sql(sql) => sql.to_string(),
+            Step::AddColumn {
+                table,
+                column,
+                definition,
+            } => format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            Step::ActivityTriggers => {
+                let tables: Vec<String> = ACTIVITY_TABLES
+                    .iter()
+                    .map(|(table, entity)| format!("{}:{}", table, entity))
+                    .collect();
+                format!(
+                    "{}\n{}\n{}",
+                    tables.join(","),
+                    ACTIVITY_TRIGGER_SQL,
+                    ACTIVITY_FIELD_SQL
+                )
+            }
+        }
+    }
+}
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub steps: &'static [Step],
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        let text: Vec<String> = self.steps.iter().map(Step::text).collect();
+        sha256_hex(text.join("\n;\n").as_bytes())
+    }
+}
+
+/// 所有迁移，按版本号升序排列
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "initial_schema",
+        steps: &[
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS snippets (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT,
+                code TEXT NOT NULL,
+                language TEXT NOT NULL,
+                tags TEXT, -- JSON array
+                folder_id TEXT,
+                project_id TEXT,
+                is_favorite INTEGER NOT NULL DEFAULT 0,
+                usage_count INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL
+            )",
+            ),
+            Step::AddColumn {
+                table: "snippets",
+                column: "is_favorite",
+                definition: "INTEGER NOT NULL DEFAULT 0",
+            },
+            Step::AddColumn {
+                table: "snippets",
+                column: "usage_count",
+                definition: "INTEGER NOT NULL DEFAULT 0",
+            },
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS folders (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                parent_id TEXT,
+                created_at INTEGER NOT NULL
+            )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS workspaces (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                color TEXT NOT NULL,
+                is_default INTEGER NOT NULL DEFAULT 0,
+                settings TEXT, -- JSON object
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS projects (
+                id TEXT PRIMARY KEY,
+                workspace_id TEXT NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT,
+                project_type TEXT NOT NULL,
+                template TEXT,
+                parent_id TEXT,
+                path TEXT NOT NULL,
+                color TEXT NOT NULL,
+                icon TEXT NOT NULL,
+                tags TEXT, -- JSON array
+                settings TEXT, -- JSON object
+                metadata TEXT, -- JSON object
+                is_folder INTEGER NOT NULL DEFAULT 0,
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS git_repositories (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                description TEXT,
+                path TEXT NOT NULL UNIQUE,
+                is_default INTEGER NOT NULL DEFAULT 0,
+                remotes TEXT, -- JSON array
+                created_at TEXT NOT NULL,
+                updated_at TEXT NOT NULL
+            )",
+            ),
+            Step::Sql(
+                "CREATE VIRTUAL TABLE IF NOT EXISTS snippets_fts USING fts5(
+                id UNINDEXED,
+                title,
+                description,
+                code,
+                tags,
+                tokenize='trigram'
+            )",
+            ),
+            Step::Sql(
+                "CREATE TRIGGER IF NOT EXISTS snippets_ai AFTER INSERT ON snippets BEGIN
+              INSERT INTO snippets_fts(id, title, description, code, tags)
+              VALUES (new.id, new.title, new.description, new.code, new.tags);
+            END;",
+            ),
+            Step::Sql(
+                "CREATE TRIGGER IF NOT EXISTS snippets_ad AFTER DELETE ON snippets BEGIN
+              DELETE FROM snippets_fts WHERE id = old.id;
+            END;",
+            ),
+            Step::Sql(
+                "CREATE TRIGGER IF NOT EXISTS snippets_au AFTER UPDATE ON snippets BEGIN
+              UPDATE snippets_fts SET
+                title = new.title,
+                description = new.description,
+                code = new.code,
+                tags = new.tags
+              WHERE id = old.id;
+            END;",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS todos (
+                id TEXT PRIMARY KEY,
+                title TEXT NOT NULL,
+                description TEXT,
+                status TEXT DEFAULT 'todo',
+                priority TEXT,
+                due_date TEXT,
+                estimated_hours REAL,
+                actual_hours REAL,
+                progress INTEGER DEFAULT 0,
+                assignee TEXT,
+                project_id TEXT,
+                parent_id TEXT,
+                recurring_config TEXT, -- JSON
+                dependencies TEXT, -- JSON array
+                completed BOOLEAN DEFAULT FALSE,
+                archived BOOLEAN DEFAULT FALSE,
+                created_by TEXT,
+                updated_by TEXT,
+                created_at INTEGER NOT NULL,
+                updated_at INTEGER NOT NULL,
+                archived_at INTEGER
+            )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS todo_tags (
+                id TEXT PRIMARY KEY,
+                name TEXT NOT NULL,
+                color TEXT NOT NULL,
+                bg_color TEXT NOT NULL,
+                color_id TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS todo_tag_relations (
+                todo_id TEXT,
+                tag_id TEXT,
+                PRIMARY KEY (todo_id, tag_id),
+                FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE,
+                FOREIGN KEY (tag_id) REFERENCES todo_tags(id) ON DELETE CASCADE
+            )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS todo_comments (
+                id TEXT PRIMARY KEY,
+                todo_id TEXT NOT NULL,
+                content TEXT NOT NULL,
+                author TEXT,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
+            )",
+            ),
+            Step::Sql(
+                "CREATE TABLE IF NOT EXISTS todo_attachments (
+                id TEXT PRIMARY KEY,
+                todo_id TEXT NOT NULL,
+                filename TEXT NOT NULL,
+                filepath TEXT NOT NULL,
+                size INTEGER,
+                mime_type TEXT,
+                created_at INTEGER NOT NULL,
+                FOREIGN KEY (todo_id) REFERENCES todos(id) ON DELETE CASCADE
+            )",
+            ),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_snippets_project_id ON snippets(project_id)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_todos_parent_id ON todos(parent_id)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_todos_project_id ON todos(project_id)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_todos_due_date ON todos(due_date)"),
+            Step::Sql("CREATE INDEX IF NOT EXISTS idx_todos_status ON todos(status)"),
+        ],
+    },
+    Migration {
+        version: 2,
+        name: "undo_journal",
+        steps: &[Step::Sql(
+            "CREATE TABLE undo_journal (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL,
+                changes TEXT NOT NULL, -- JSON array of row changes
+                undone INTEGER NOT NULL DEFAULT 0,
+                created_at INTEGER NOT NULL
+            )",
+        )],
+    },
+    Migration {
+        version: 3,
+        name: "activity_log",
+        steps: &[
+            Step::Sql(
+                "CREATE TABLE activity_log (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                entity_type TEXT NOT NULL,
+                entity_id TEXT NOT NULL,
+                action TEXT NOT NULL,
+                changes TEXT NOT NULL, -- JSON array of changed field names
+                created_at INTEGER NOT NULL
+            )",
+            ),
+            Step::Sql(
+                "CREATE INDEX idx_activity_log_entity ON activity_log(entity_type, entity_id)",
+            ),
+            Step::Sql(
+                "CREATE INDEX idx_activity_log_created_at ON activity_log(created_at)",
+            ),
+            Step::Sql(
+                "CREATE TABLE activity_pause (
+                id INTEGER PRIMARY KEY CHECK (id = 1)
+            )",
+            ),
+            Step::ActivityTriggers,
+            Step::Sql(
+                "CREATE TRIGGER activity_todo_tag_relations_ai AFTER INSERT ON todo_tag_relations
+                WHEN NOT EXISTS (SELECT 1 FROM activity_pause)
+                BEGIN
+                  INSERT INTO activity_log (entity_type, entity_id, action, changes, created_at)
+                  VALUES ('todo', new.todo_id, 'updated',
+                          json_array('tags'), CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                END;",
+            ),
+            Step::Sql(
+                "CREATE TRIGGER activity_todo_tag_relations_ad AFTER DELETE ON todo_tag_relations
+                WHEN NOT EXISTS (SELECT 1 FROM activity_pause)
+                  AND EXISTS (SELECT 1 FROM todos WHERE id = old.todo_id)
+                BEGIN
+                  INSERT INTO activity_log (entity_type, entity_id, action, changes, created_at)
+                  VALUES ('todo', old.todo_id, 'updated',
+                          json_array('tags'), CAST((julianday('now') - 2440587.5) * 86400000 AS INTEGER));
+                END;",
+            ),
+        ],
+    },
+    Migration {
+        version: 4,
+        name: "todo_completed_at",
+        steps: &[
+            Step::AddColumn {
+                table: "todos",
+                column: "completed_at",
+                definition: "INTEGER",
+            },
+            // 已完成的任务没有记录完成时间，以最后更新时间近似；不写入活动日志
+            Step::Sql("INSERT INTO activity_pause (id) VALUES (1)"),
+            Step::Sql(
+                "UPDATE todos SET completed_at = updated_at
+                 WHERE completed = 1 OR status = 'completed'",
+            ),
+            Step::Sql("DELETE FROM activity_pause"),
+        ],
+    },
+];
+
+/// 应用支持的最新结构版本
+pub const LATEST_VERSION: i64 = MIGRATIONS[MIGRATIONS.len() - 1].version;
+
+/// 执行所有未执行的迁移，返回本次执行的版本号
+pub async fn run(pool: &SqlitePool) -> AppResult<Vec<i64>> {
+    sqlx::query(
+        "CREATE TABLE IF NOT EXISTS schema_migrations (
+            version INTEGER PRIMARY KEY,
+            name TEXT NOT NULL,
+            checksum TEXT NOT NULL,
+            applied_at INTEGER NOT NULL
+        )",
+    )
+    .execute(pool)
+    .await
+    .context("Failed to create schema_migrations")?;
+
+    let rows =
+        sqlx::query("SELECT version, name, checksum FROM schema_migrations ORDER BY version")
+            .fetch_all(pool)
+            .await
+            .context("Failed to read schema_migrations")?;
+
+    let newest = rows.iter().map(|row| row.get::<i64, _>("version")).max();
+    if let Some(newest) = newest.filter(|v| *v > LATEST_VERSION) {
+        return Err(AppError::UnsupportedVersion(format!(
+            "Database schema version {} is newer than this version of SnippetsHub supports ({}); please update the application",
+            newest, LATEST_VERSION
+        )));
+    }
+
+    let mut applied = Vec::new();
+    for row in &rows {
+        let version: i64 = row.get("version");
+        let name: String = row.get("name");
+        let checksum: String = row.get("checksum");
+
+        let Some(migration) = MIGRATIONS.iter().find(|m| m.version == version) else {
+            return Err(AppError::internal(format!(
+                "Unknown migration {} ({}) in database",
+                version, name
+            )));
+        };
+        if migration.checksum() != checksum {
+            return Err(AppError::internal(format!(
+                "Migration {} ({}) has changed since it was applied to this database",
+                version, name
+            )));
+        }
+        applied.push(version);
+    }
+
+    let mut executed = Vec::new();
+    for migration in MIGRATIONS {
+        if applied.contains(&migration.version) {
+            continue;
+        }
+        apply(pool, migration).await?;
+        executed.push(migration.version);
+    }
+
+    Ok(executed)
+}
+
+/// 在一个事务中执行单个迁移并记录
+async fn apply(pool: &SqlitePool, migration: &Migration) -> AppResult<()> {
+    let failed = |e: sqlx::Error| {
+        AppError::from(e).context(&format!(
+            "Migration {} ({}) failed",
+            migration.version, migration.name
+        ))
+    };
+    let mut tx = pool.begin().await.map_err(failed)?;
+
+    for step in migration.steps {
+        match step {
+            Step::Sql(sql) => {
+                sqlx::query(sql).execute(&mut *tx).await.map_err(failed)?;
+            }
+            Step::AddColumn { table, column, .. } => {
+                let columns: Vec<String> =
+                    sqlx::query_scalar(&format!("SELECT name FROM pragma_table_info('{}')", table))
+                        .fetch_all(&mut *tx)
+                        .await
+                        .map_err(failed)?;
+                if !columns.iter().any(|name| name == column) {
+                    sqlx::query(&step.text())
+                        .execute(&mut *tx)
+                        .await
+                        .map_err(failed)?;
+                }
+
+                let trigger: Option<String> = sqlx::query_scalar(
+                    "SELECT name FROM sqlite_master WHERE type = 'trigger' AND name = ?",
+                )
+                .bind(format!("activity_{}_ai", table))
+                .fetch_optional(&mut *tx)
+                .await
+                .map_err(failed)?;
+                let tracked = ACTIVITY_TABLES.iter().find(|(name, _)| name == table);
+                if let (Some(_), Some((table, entity))) = (trigger, tracked) {
+                    create_activity_triggers(&mut tx, table, entity)
+                        .await
+                        .map_err(failed)?;
+                }
+            }
+            Step::ActivityTriggers => {
+                for (table, entity) in ACTIVITY_TABLES {
+                    create_activity_triggers(&mut tx, table, entity)
+                        .await
+                        .map_err(failed)?;
+                }
+            }
+        }
+    }
+
+    sqlx::query(
+        "INSERT INTO schema_migrations (version, name, checksum, applied_at) VALUES (?, ?, ?, ?)",
+    )
+    .bind(migration.version)
+    .bind(migration.name)
+    .bind(migration.checksum())
+    .bind(chrono::Utc::now().timestamp_millis())
+    .execute(&mut *tx)
+    .await
+    .map_err(failed)?;
+
+    tx.commit().await.map_err(failed)
+}
+
+/// 按表当前的列（重新）创建活动记录触发器
+///
+/// 每个触发器把变化的列名写成 `["title", "code"]`；activity_pause 中有记录时
+/// （如恢复备份期间）不写日志。
+async fn create_activity_triggers(
+    conn: &mut SqliteConnection,
+    table: &str,
+    entity: &str,
+) -> Result<(), sqlx::Error> {
+    let columns: Vec<String> = sqlx::query_scalar(&format!(
+        "SELECT name FROM pragma_table_info('{}') WHERE pk = 0 ORDER BY cid",
+        table
+    ))
+    .fetch_all(&mut *conn)
+    .await?;
+
+    for (suffix, event, action) in [
+        ("ai", "INSERT", "created"),
+        ("au", "UPDATE", "updated"),
+        ("ad", "DELETE", "deleted"),
+    ] {
+        let fields: Vec<String> = columns
+            .iter()
+            .filter(|column| event != "UPDATE" || column.as_str() != "updated_at")
+            .map(|column| {
+                let old = match event {
+                    "INSERT" => "NULL".to_string(),
+                    _ => format!("old.{}", column),
+                };
+                let new = match event {
+                    "DELETE" => "NULL".to_string(),
+                    _ => format!("new.{}", column),
+                };
+                ACTIVITY_FIELD_SQL
+                    .replace("{column}", column)
+                    .replace("{old}", &old)
+                    .replace("{new}", &new)
+            })
+            .collect();
+        let row = if event == "DELETE" { "old" } else { "new" };
+
+        sqlx::query(&format!(
+            "DROP TRIGGER IF EXISTS activity_{}_{}",
+            table, suffix
+        ))
+        .execute(&mut *conn)
+        .await?;
+        let sql = ACTIVITY_TRIGGER_SQL
+            .replace("{table}", table)
+            .replace("{suffix}", suffix)
+            .replace("{event}", event)
+            .replace("{entity}", entity)
+            .replace("{row}", row)
+            .replace("{action}", action)
+            .replace("{fields}", &fields.join(" UNION ALL "));
+        sqlx::query(&sql).execute(&mut *conn).await?;
+    }
+
+    Ok(())
+}
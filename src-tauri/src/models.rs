@@ -65,6 +65,9 @@ pub struct CodeSnippet {
     pub created_at: i64,
     /// 更新时间戳
     pub updated_at: i64,
+    /// 来源资料库：None 为当前资料库，否则为挂载的只读资料库 ID
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_id: Option<String>,
 }
 
 /// 文件夹数据模型
@@ -116,13 +119,473 @@ pub struct UpdateSnippetRequest {
 /// 搜索查询参数
 ///
 /// 定义全文搜索和过滤的参数结构
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchQuery {
     pub keyword: String,
     pub tags: Option<Vec<String>>,
     pub language: Option<String>,
 }
 
+/// 导出范围
+///
+/// 指定导出单个片段、某个文件夹（含子文件夹）、某个标签或一次搜索的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ExportScope {
+    All,
+    Snippet { id: String },
+    Folder { id: String },
+    Tag { tag: String },
+    Search { query: SearchQuery },
+}
+
+/// HTML 导出选项
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HtmlExportOptions {
+    /// 页面标题，默认为 "SnippetsHub Export"
+    pub title: Option<String>,
+    /// syntect 主题名称，默认为 "InspiredGitHub"
+    pub theme: Option<String>,
+    /// 是否生成目录
+    pub include_toc: bool,
+}
+
+/// 从外部格式解析出的代码片段草稿
+///
+/// 各种编辑器格式的导入器都先转换为草稿，再由统一的导入流程写入数据库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnippetDraft {
+    pub title: String,
+    pub description: String,
+    pub code: String,
+    pub language: String,
+    pub tags: Vec<String>,
+    /// 相对于导入目标文件夹的子文件夹路径，按层级排列
+    pub folder_path: Vec<String>,
+    pub is_favorite: bool,
+    /// 保留源数据中的时间戳，缺省时使用导入时间
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    /// 源数据中无法映射的内容说明，会写入导入报告
+    pub notes: Vec<String>,
+}
+
+impl SnippetDraft {
+    pub fn new(title: String, code: String, language: String) -> Self {
+        SnippetDraft {
+            title,
+            description: String::new(),
+            code,
+            language,
+            tags: Vec::new(),
+            folder_path: Vec::new(),
+            is_favorite: false,
+            created_at: None,
+            updated_at: None,
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// 可导入的编辑器代码片段格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EditorSnippetFormat {
+    /// Sublime Text .sublime-snippet 文件
+    Sublime,
+    /// Vim UltiSnips .snippets 文件
+    #[serde(rename = "ultisnips")]
+    UltiSnips,
+    /// Vim SnipMate .snippets 文件
+    #[serde(rename = "snipmate")]
+    SnipMate,
+    /// Emacs yasnippet 目录（<mode>/<snippet> 结构）
+    Yasnippet,
+}
+
+/// 从 Markdown 文件中提取出的代码块
+///
+/// 导入前先以列表形式预览，前端按 index 选择需要创建的代码块
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkdownCodeBlock {
+    /// 在预览列表中的序号
+    pub index: usize,
+    /// 相对于导入路径的源文件
+    pub source: String,
+    /// 代码块起始围栏所在行（从 1 开始）
+    pub line: usize,
+    /// 最近的上级标题，缺省时为文件名
+    pub title: String,
+    /// 代码块前（或后）紧邻的段落
+    pub description: String,
+    pub language: String,
+    pub code: String,
+    /// 源文件所在的相对目录，导入时创建为文件夹
+    pub folder_path: Vec<String>,
+}
+
+/// Markdown 目录树导出结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkdownExportReport {
+    pub directory: String,
+    /// 新建或内容有变化的文件（相对路径）
+    pub written: Vec<String>,
+    /// 内容未变、未重写的文件数
+    pub unchanged: usize,
+    /// 上次导出生成、本次已不存在而被删除的文件
+    pub removed: Vec<String>,
+}
+
+/// Markdown 代码块提取预览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarkdownHarvestPreview {
+    pub blocks: Vec<MarkdownCodeBlock>,
+    /// 无法读取的文件，格式为 "路径: 原因"
+    pub errors: Vec<String>,
+}
+
+/// 导入冲突处理策略
+///
+/// 当导入的数据与现有数据 ID 相同时的处理方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportConflictStrategy {
+    /// 保留现有数据，跳过导入项
+    Skip,
+    /// 使用导入项覆盖现有数据
+    Overwrite,
+    /// 两者都保留，导入项使用新的ID
+    KeepBoth,
+    /// 按 updated_at 保留较新的一方
+    MergeNewer,
+}
+
+/// 单个导入项的处理结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportAction {
+    Create,
+    Update,
+    Skip,
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportItemResult {
+    /// 导入项在源数据中的位置（从0开始）
+    pub index: usize,
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub action: ImportAction,
+    /// 错误原因或附加说明
+    pub message: Option<String>,
+}
+
+/// 导入报告
+///
+/// dry_run 为 true 时仅预览将要发生的变化，不写入数据库
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub dry_run: bool,
+    pub total: usize,
+    pub created: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub failed: usize,
+    pub items: Vec<ImportItemResult>,
+}
+
+impl ImportReport {
+    pub fn new(dry_run: bool) -> Self {
+        ImportReport {
+            dry_run,
+            total: 0,
+            created: 0,
+            updated: 0,
+            skipped: 0,
+            failed: 0,
+            items: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, item: ImportItemResult) {
+        self.total += 1;
+        match item.action {
+            ImportAction::Create => self.created += 1,
+            ImportAction::Update => self.updated += 1,
+            ImportAction::Skip => self.skipped += 1,
+            ImportAction::Error => self.failed += 1,
+        }
+        self.items.push(item);
+    }
+}
+
+// ============================================================================
+// Backup Models
+// ============================================================================
+
+/// 备份归档清单 (manifest.json)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format: String,
+    /// 归档格式版本
+    pub archive_version: i64,
+    /// 创建备份时的数据库结构版本
+    pub schema_version: i64,
+    pub app_version: String,
+    pub created_at: i64,
+    pub tables: Vec<BackupTableEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupTableEntry {
+    pub name: String,
+    /// 归档内的文件路径
+    pub file: String,
+    pub rows: usize,
+    /// 表数据文件的 SHA-256 校验和
+    pub sha256: String,
+}
+
+/// 备份恢复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupRestoreReport {
+    pub manifest: BackupManifest,
+    /// 若归档由旧版本升级而来，记录原版本号
+    pub upgraded_from: Option<i64>,
+    /// 每张表恢复的行数
+    pub restored: HashMap<String, usize>,
+    /// 归档中存在但当前版本不再支持的表
+    pub ignored_tables: Vec<String>,
+}
+
+/// 自动备份设置，保存在应用数据目录的 backup_settings.json 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BackupSettings {
+    pub enabled: bool,
+    /// 备份目录，缺省时使用应用数据目录下的 backups
+    pub directory: Option<String>,
+    /// 两次自动备份的最小间隔（小时）
+    pub interval_hours: u32,
+    /// 保留最近多少天的每日备份（每天保留最新一份）
+    pub keep_daily: usize,
+    /// 保留最近多少周的每周备份（每周保留最新一份）
+    pub keep_weekly: usize,
+}
+
+impl Default for BackupSettings {
+    fn default() -> Self {
+        BackupSettings {
+            enabled: true,
+            directory: None,
+            interval_hours: 24,
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupSnapshotKind {
+    /// 定时任务创建，参与轮换
+    Auto,
+    /// 手动创建，不会被自动删除
+    Manual,
+    /// 恢复前自动创建的安全快照
+    PreRestore,
+}
+
+/// 备份目录中的一个数据库快照
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSnapshot {
+    pub file_name: String,
+    pub path: String,
+    pub kind: BackupSnapshotKind,
+    /// 创建时间（毫秒时间戳，取自文件名）
+    pub created_at: i64,
+    pub size: u64,
+}
+
+/// 快照恢复结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupSnapshotRestoreReport {
+    pub snapshot: BackupSnapshot,
+    /// 恢复前创建的安全快照，可用于撤销本次恢复
+    pub safety_snapshot: BackupSnapshot,
+    /// 每张表恢复的行数
+    pub restored: HashMap<String, usize>,
+}
+
+// ============================================================================
+// Library Models
+// ============================================================================
+
+/// 最近打开的资料库记录，保存在应用数据目录的 libraries.json 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecentLibrary {
+    pub path: String,
+    pub last_opened_at: i64,
+}
+
+/// 资料库信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryInfo {
+    pub path: String,
+    /// 显示名称，取自文件名
+    pub name: String,
+    pub last_opened_at: i64,
+    /// 是否为当前打开的资料库
+    pub is_current: bool,
+    /// 文件是否仍然存在（移动或删除后为 false）
+    pub exists: bool,
+}
+
+/// 挂载的只读资料库，保存在应用数据目录的 mounted_libraries.json 中
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountedLibrary {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+}
+
+/// 挂载资料库的当前状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountedLibraryInfo {
+    pub id: String,
+    pub name: String,
+    pub path: String,
+    /// 是否已成功打开（共享盘离线时为 false）
+    pub available: bool,
+    /// 无法打开时的原因
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// Maintenance Models
+// ============================================================================
+
+/// 数据库完整性检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IntegrityReport {
+    /// PRAGMA integrity_check 报告的问题，数据库正常时为空
+    pub integrity_errors: Vec<String>,
+    /// PRAGMA foreign_key_check 发现的外键冲突（修复前）
+    pub foreign_key_violations: Vec<ForeignKeyViolation>,
+    /// 存在孤立记录的引用关系
+    pub orphans: Vec<OrphanedReference>,
+    /// 是否已修复孤立记录
+    pub repaired: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForeignKeyViolation {
+    pub table: String,
+    pub row_id: Option<i64>,
+    /// 被引用的表
+    pub parent: String,
+}
+
+/// 引用了不存在记录的行，如指向已删除 TODO 的标签关联
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedReference {
+    pub table: String,
+    pub column: String,
+    pub parent_table: String,
+    pub count: i64,
+    /// 修复方式: "delete" 删除记录，"clear_reference" 把引用列置为 NULL
+    pub repair: String,
+}
+
+/// 数据库文件与各表的占用情况
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatabaseStats {
+    /// 数据库文件大小（不含 WAL 文件）
+    pub size_bytes: i64,
+    /// 可通过 VACUUM 回收的空闲页大小
+    pub free_bytes: i64,
+    pub tables: Vec<TableStats>,
+    /// 全文索引影子表的总大小，SQLite 未启用 dbstat 时为 None
+    pub fts_index_bytes: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableStats {
+    pub name: String,
+    pub row_count: i64,
+    /// 表及其索引占用的空间，SQLite 未启用 dbstat 时为 None
+    pub size_bytes: Option<i64>,
+}
+
+/// 全文索引与 snippets 表的一致性检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FtsSyncReport {
+    /// 没有索引行的代码片段数量
+    pub missing: i64,
+    /// 对应代码片段已不存在的索引行数量
+    pub stale: i64,
+    /// 索引内容与代码片段不一致（或重复索引）的代码片段数量
+    pub outdated: i64,
+    /// 是否已修复
+    pub repaired: bool,
+}
+
+// ============================================================================
+// Undo Models
+// ============================================================================
+
+/// 撤销历史中的一个用户操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoEntry {
+    pub id: i64,
+    /// 操作名称，如 "Delete folder"
+    pub label: String,
+    pub created_at: i64,
+}
+
+/// 撤销/重做菜单的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UndoState {
+    /// 下一个可撤销的操作
+    pub undo: Option<UndoEntry>,
+    /// 下一个可重做的操作
+    pub redo: Option<UndoEntry>,
+}
+
+// ============================================================================
+// Activity Models
+// ============================================================================
+
+/// 一条数据变更记录，由数据库触发器写入 activity_log
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub id: i64,
+    /// snippet、folder、workspace、project、git_repository、todo、todo_tag；
+    /// 恢复备份时为 library
+    pub entity_type: String,
+    /// 恢复备份时为空字符串
+    pub entity_id: String,
+    /// created、updated、deleted；恢复备份时为 restored
+    pub action: String,
+    /// 变更的字段名: ["title", "code"]，不记录字段值；
+    /// 恢复备份时为各表恢复的行数: {"snippets": 12}
+    pub changes: serde_json::Value,
+    pub created_at: i64,
+}
+
+/// 活动记录查询条件，时间为毫秒时间戳（包含边界）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ActivityQuery {
+    pub entity_type: Option<String>,
+    pub entity_id: Option<String>,
+    pub action: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    /// 最多返回的条数，默认 100
+    pub limit: Option<i64>,
+}
+
 // ============================================================================
 // Todo Models
 // ============================================================================
@@ -150,6 +613,8 @@ pub struct Todo {
     pub created_at: i64,
     pub updated_at: i64,
     pub archived_at: Option<i64>,
+    /// 标记为完成的时间，重新打开后清空
+    pub completed_at: Option<i64>,
     pub tags: Vec<String>,   // Will be populated from relations
     pub subtasks: Vec<Todo>, // Will be populated for hierarchical display
 }
@@ -229,6 +694,17 @@ pub struct TodoSearchQuery {
     pub archived: Option<bool>,
 }
 
+/// TODO 列表的返回形式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoLayout {
+    /// 平铺列表，子任务与父任务并列，通过 parent_id 关联
+    #[default]
+    Flat,
+    /// 子任务树，只返回顶层 TODO，子任务逐层填入 subtasks
+    Tree,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BatchTodoOperation {
     pub todo_ids: Vec<String>,
@@ -271,6 +747,114 @@ pub struct TodoStats {
     pub by_assignee: HashMap<String, i64>,
 }
 
+/// 从外部格式（iCalendar、todo.txt 等）解析出的 TODO 草稿
+///
+/// 与 SnippetDraft 类似，由统一的导入流程创建或更新 todos 表中的记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TodoDraft {
+    /// 源数据中的唯一标识（如 VTODO 的 UID），缺省时生成新ID
+    pub id: Option<String>,
+    pub title: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub priority: Option<String>,
+    pub due_date: Option<String>,
+    pub progress: i32,
+    pub parent_id: Option<String>,
+    pub recurring_config: Option<String>,
+    pub dependencies: Vec<String>,
+    /// 标签名称，导入时解析为 todo_tags 中的ID，不存在则创建
+    pub tag_names: Vec<String>,
+    /// 项目名称，导入时按名称匹配已有项目，不会新建项目
+    pub project_name: Option<String>,
+    pub completed: bool,
+    pub archived: bool,
+    pub created_at: Option<i64>,
+    pub updated_at: Option<i64>,
+    /// 源数据中无法映射的内容说明
+    pub notes: Vec<String>,
+}
+
+impl TodoDraft {
+    pub fn new(title: String) -> Self {
+        TodoDraft {
+            id: None,
+            title,
+            description: None,
+            status: "todo".to_string(),
+            priority: None,
+            due_date: None,
+            progress: 0,
+            parent_id: None,
+            recurring_config: None,
+            dependencies: Vec::new(),
+            tag_names: Vec::new(),
+            project_name: None,
+            completed: false,
+            archived: false,
+            created_at: None,
+            updated_at: None,
+            notes: Vec::new(),
+        }
+    }
+}
+
+/// TODO 的纯文本交换格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoTextFormat {
+    /// Markdown 任务列表（- [ ] / - [x]），缩进表示子任务
+    Markdown,
+    /// todo.txt（http://todotxt.org）
+    #[serde(rename = "todotxt")]
+    TodoTxt,
+}
+
+/// TODO CSV 导出的列
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TodoCsvColumn {
+    Id,
+    Title,
+    Description,
+    Status,
+    Priority,
+    DueDate,
+    EstimatedHours,
+    ActualHours,
+    Progress,
+    Assignee,
+    /// 项目名称（由 project_id 解析）
+    Project,
+    /// 标签名称，以逗号分隔
+    Tags,
+    ParentId,
+    Completed,
+    CompletedAt,
+    CreatedAt,
+    UpdatedAt,
+    ArchivedAt,
+}
+
+impl TodoCsvColumn {
+    /// 未指定列时的默认列
+    pub fn defaults() -> Vec<TodoCsvColumn> {
+        vec![
+            TodoCsvColumn::Title,
+            TodoCsvColumn::Status,
+            TodoCsvColumn::Priority,
+            TodoCsvColumn::DueDate,
+            TodoCsvColumn::EstimatedHours,
+            TodoCsvColumn::ActualHours,
+            TodoCsvColumn::Progress,
+            TodoCsvColumn::Assignee,
+            TodoCsvColumn::Project,
+            TodoCsvColumn::Tags,
+            TodoCsvColumn::CompletedAt,
+        ]
+    }
+}
+
 // ============================================================================
 // Workspace and Project Models
 // ============================================================================
@@ -510,3 +1094,11 @@ pub struct LSPLocation {
     pub uri: String,
     pub range: LSPRange,
 }
+
+/// 代码片段静态检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LintResult {
+    pub language: String,
+    pub linter: String,
+    pub diagnostics: Vec<Diagnostic>,
+}
@@ -0,0 +1,105 @@
+/*
+ * SnippetsHub - 代码片段管理工具
+ *
+ * @file repository.rs - 存储层接口
+ * @author Noah
+ * @description 按数据类型划分的仓储 trait，命令层与测试通过它们访问存储
+ * @created 2026-02-20
+ * @version 1.0.0
+ *
+ * 仓储划分:
+ * - SnippetRepository: 代码片段的增删改查与检索
+ * - FolderRepository: 文件夹
+ * - ProjectRepository: 工作区与项目
+ * - GitRepoRepository: Git 仓库记录
+ * - TodoRepository: TODO 任务及其标签
+ *
+ * 架构说明:
+ * 目前唯一的实现是 database.rs 中基于 SQLite 的 Database。Database::from_pool
+ * 只需要一个 SqlitePool，集成测试（tests/）借此在内存数据库上运行。
+ * 导入导出、备份恢复等跨表操作仍是 Database 的固有方法。
+ */
+use crate::error::AppResult;
+use crate::models::*;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait SnippetRepository: Send + Sync {
+    async fn create_snippet(&self, req: CreateSnippetRequest) -> AppResult<CodeSnippet>;
+    async fn get_all_snippets(&self) -> AppResult<Vec<CodeSnippet>>;
+    /// 不存在时返回 None
+    async fn get_snippet(&self, id: &str) -> AppResult<Option<CodeSnippet>>;
+    /// 未提供的字段保持原值
+    async fn update_snippet(&self, req: UpdateSnippetRequest) -> AppResult<CodeSnippet>;
+    async fn delete_snippet(&self, id: &str) -> AppResult<()>;
+    async fn search_snippets(&self, query: SearchQuery) -> AppResult<Vec<CodeSnippet>>;
+    /// 包含所有子文件夹中的代码片段
+    async fn get_snippets_by_folder(&self, folder_id: &str) -> AppResult<Vec<CodeSnippet>>;
+    async fn get_snippets_by_tag(&self, tag: &str) -> AppResult<Vec<CodeSnippet>>;
+}
+
+#[async_trait]
+pub trait FolderRepository: Send + Sync {
+    async fn create_folder(&self, name: String, parent_id: Option<String>) -> AppResult<Folder>;
+    async fn get_all_folders(&self) -> AppResult<Vec<Folder>>;
+    async fn delete_folder(&self, id: &str) -> AppResult<()>;
+}
+
+#[async_trait]
+pub trait ProjectRepository: Send + Sync {
+    async fn create_workspace(&self, workspace: Workspace) -> AppResult<Workspace>;
+    async fn get_workspaces(&self) -> AppResult<Vec<Workspace>>;
+    async fn update_workspace(
+        &self,
+        workspace_id: String,
+        updates: WorkspaceUpdate,
+    ) -> AppResult<Workspace>;
+    async fn get_workspace(&self, workspace_id: &str) -> AppResult<Workspace>;
+    async fn delete_workspace(&self, workspace_id: String) -> AppResult<()>;
+
+    async fn create_project(&self, project: Project) -> AppResult<Project>;
+    async fn get_projects(&self) -> AppResult<Vec<Project>>;
+    async fn update_project(
+        &self,
+        project_id: String,
+        updates: ProjectUpdate,
+    ) -> AppResult<Project>;
+    async fn get_project(&self, project_id: &str) -> AppResult<Project>;
+    async fn delete_project(&self, project_id: String) -> AppResult<()>;
+    async fn get_snippets_by_project(&self, project_id: String) -> AppResult<Vec<CodeSnippet>>;
+}
+
+#[async_trait]
+pub trait GitRepoRepository: Send + Sync {
+    async fn create_git_repository(&self, repository: GitRepository) -> AppResult<GitRepository>;
+    async fn get_git_repositories(&self) -> AppResult<Vec<GitRepository>>;
+    async fn update_git_repository(
+        &self,
+        repository_id: String,
+        updates: GitRepositoryUpdate,
+    ) -> AppResult<GitRepository>;
+    async fn get_git_repository(&self, repository_id: &str) -> AppResult<GitRepository>;
+    async fn delete_git_repository(&self, repository_id: String) -> AppResult<()>;
+}
+
+#[async_trait]
+pub trait TodoRepository: Send + Sync {
+    async fn create_todo(&self, req: CreateTodoRequest) -> AppResult<Todo>;
+    /// 所有未归档的任务，平铺返回（subtasks 为空），树形结构见 build_todo_tree
+    async fn get_todos(&self) -> AppResult<Vec<Todo>>;
+    /// 任务及其所有层级的子任务
+    async fn get_todo(&self, todo_id: &str) -> AppResult<Todo>;
+    async fn update_todo(&self, req: UpdateTodoRequest) -> AppResult<Todo>;
+    /// 同时删除子任务
+    async fn delete_todo(&self, todo_id: String) -> AppResult<()>;
+    /// 匹配的任务，平铺返回
+    async fn search_todos(&self, query: TodoSearchQuery) -> AppResult<Vec<Todo>>;
+    async fn batch_update_todos(&self, operation: BatchTodoOperation) -> AppResult<Vec<Todo>>;
+    async fn get_todo_stats(&self) -> AppResult<TodoStats>;
+
+    async fn create_todo_tag(&self, req: CreateTodoTagRequest) -> AppResult<TodoTag>;
+    async fn get_todo_tags_list(&self) -> AppResult<Vec<TodoTag>>;
+    async fn update_todo_tag(&self, req: UpdateTodoTagRequest) -> AppResult<TodoTag>;
+    async fn get_todo_tag(&self, tag_id: &str) -> AppResult<TodoTag>;
+    async fn delete_todo_tag(&self, tag_id: String) -> AppResult<()>;
+}
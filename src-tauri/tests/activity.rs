@@ -0,0 +1,155 @@
+//! 活动记录的集成测试
+mod common;
+
+use common::{memory_db, snippet, snippet_update, todo};
+use snippets_hub_lib::formats::archive::TableSet;
+use snippets_hub_lib::models::{ActivityQuery, CreateTodoTagRequest};
+use snippets_hub_lib::repository::{SnippetRepository, TodoRepository};
+
+fn for_entity(entity_type: &str, entity_id: &str) -> ActivityQuery {
+    ActivityQuery {
+        entity_type: Some(entity_type.to_string()),
+        entity_id: Some(entity_id.to_string()),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn snippet_changes_record_the_changed_fields() {
+    let db = memory_db().await;
+    let created = db
+        .create_snippet(snippet("Hello", "rust", "fn main() {}"))
+        .await
+        .unwrap();
+    let mut update = snippet_update(&created.id);
+    update.title = Some("Hello, world".to_string());
+    db.update_snippet(update).await.unwrap();
+    db.delete_snippet(&created.id).await.unwrap();
+
+    let activity = db
+        .get_activity(for_entity("snippet", &created.id))
+        .await
+        .unwrap();
+    let actions: Vec<&str> = activity.iter().map(|a| a.action.as_str()).collect();
+    assert_eq!(actions, vec!["deleted", "updated", "created"]);
+
+    // 只记录实际变化的字段名，不复制字段值
+    assert_eq!(activity[1].changes, serde_json::json!(["title"]));
+
+    let created_fields = activity[2].changes.as_array().unwrap();
+    assert!(created_fields.contains(&serde_json::json!("code")));
+    assert!(created_fields.contains(&serde_json::json!("title")));
+    assert!(!activity[2].changes.to_string().contains("fn main() {}"));
+    assert!(activity[0]
+        .changes
+        .as_array()
+        .unwrap()
+        .contains(&serde_json::json!("title")));
+}
+
+#[tokio::test]
+async fn tag_changes_are_recorded_on_the_todo() {
+    let db = memory_db().await;
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "ops".to_string(),
+            color_id: "red".to_string(),
+        })
+        .await
+        .unwrap();
+    let mut req = todo("Deploy");
+    req.tags = Some(vec![tag.id.clone()]);
+    let created = db.create_todo(req).await.unwrap();
+    db.delete_todo(created.id.clone()).await.unwrap();
+
+    let activity = db
+        .get_activity(for_entity("todo", &created.id))
+        .await
+        .unwrap();
+    let actions: Vec<&str> = activity.iter().map(|a| a.action.as_str()).collect();
+    // 删除 TODO 时级联删除的标签关联不单独记录
+    assert_eq!(actions, vec!["deleted", "updated", "created"]);
+    assert_eq!(activity[1].changes, serde_json::json!(["tags"]));
+}
+
+#[tokio::test]
+async fn activity_can_be_filtered_by_entity_action_and_date() {
+    let db = memory_db().await;
+    let started = chrono::Utc::now().timestamp_millis() - 1;
+    db.create_snippet(snippet("One", "text", "one"))
+        .await
+        .unwrap();
+    db.create_todo(todo("Two")).await.unwrap();
+
+    let all = db.get_activity(ActivityQuery::default()).await.unwrap();
+    assert_eq!(all.len(), 2);
+
+    let snippets = db
+        .get_activity(ActivityQuery {
+            entity_type: Some("snippet".to_string()),
+            action: Some("created".to_string()),
+            from: Some(started),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(snippets.len(), 1);
+
+    let before_start = db
+        .get_activity(ActivityQuery {
+            to: Some(started - 60_000),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert!(before_start.is_empty());
+
+    let limited = db
+        .get_activity(ActivityQuery {
+            limit: Some(1),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(limited[0].entity_type, "todo");
+
+    assert_eq!(db.clear_activity(None).await.unwrap(), 2);
+    assert!(db
+        .get_activity(ActivityQuery::default())
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[tokio::test]
+async fn restoring_a_backup_records_a_single_summary_entry() {
+    let db = memory_db().await;
+    db.create_snippet(snippet("One", "text", "one"))
+        .await
+        .unwrap();
+    db.create_todo(todo("Two")).await.unwrap();
+    let tables: TableSet = db.dump_tables().await.unwrap().into_iter().collect();
+    db.clear_activity(None).await.unwrap();
+
+    db.restore_tables(&tables).await.unwrap();
+
+    let activity = db.get_activity(ActivityQuery::default()).await.unwrap();
+    assert_eq!(activity.len(), 1);
+    assert_eq!(activity[0].entity_type, "library");
+    assert_eq!(activity[0].action, "restored");
+    assert_eq!(activity[0].changes["snippets"], 1);
+    assert_eq!(activity[0].changes["todos"], 1);
+
+    // 恢复结束后恢复逐行记录
+    db.create_snippet(snippet("Three", "text", "three"))
+        .await
+        .unwrap();
+    let snippets = db
+        .get_activity(ActivityQuery {
+            entity_type: Some("snippet".to_string()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    assert_eq!(snippets.len(), 1);
+}
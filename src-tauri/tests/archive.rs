@@ -0,0 +1,88 @@
+//! 备份归档读写与旧版导出升级的测试
+mod common;
+
+use common::memory_db;
+use serde_json::json;
+use snippets_hub_lib::database::SCHEMA_VERSION;
+use snippets_hub_lib::formats::archive::{self, TableRow};
+use snippets_hub_lib::repository::TodoRepository;
+
+fn row(value: serde_json::Value) -> TableRow {
+    value.as_object().unwrap().clone()
+}
+
+#[test]
+fn archive_round_trips_and_detects_tampering() {
+    let tables = vec![(
+        "folders".to_string(),
+        vec![row(json!({ "id": "f1", "name": "Rust" }))],
+    )];
+    let (bytes, manifest) = archive::write(&tables, 3).unwrap();
+    assert_eq!(manifest.tables[0].rows, 1);
+
+    let read = archive::read(&bytes, 3).unwrap();
+    assert_eq!(read.upgraded_from, None);
+    assert_eq!(read.tables["folders"][0]["name"], "Rust");
+
+    let error = archive::read(&bytes, 2).err().unwrap();
+    assert!(error.contains("newer app"), "{}", error);
+    assert!(archive::read(b"not an archive", 3).is_err());
+}
+
+#[test]
+fn legacy_export_is_upgraded() {
+    let legacy = r#"[{"id": "s1", "title": "Hello", "tags": ["demo"], "is_favorite": true}]"#;
+
+    for prefix in ["", "\u{feff}", "\n  ", "\u{feff}\r\n"] {
+        let bytes = format!("{}{}", prefix, legacy).into_bytes();
+        let read = archive::read(&bytes, 3).unwrap();
+        assert_eq!(read.upgraded_from, Some(0), "prefix {:?}", prefix);
+
+        let snippet = &read.tables["snippets"][0];
+        assert_eq!(snippet["title"], "Hello");
+        assert_eq!(snippet["tags"], r#"["demo"]"#);
+        assert_eq!(snippet["is_favorite"], 1);
+    }
+}
+
+#[tokio::test]
+async fn archives_from_older_schemas_are_upgraded_before_restore() {
+    let todo = |id: &str, status: &str, completed: i64| {
+        row(json!({
+            "id": id,
+            "title": id,
+            "status": status,
+            "progress": 0,
+            "completed": completed,
+            "archived": 0,
+            "created_at": 1_000,
+            "updated_at": 2_000,
+        }))
+    };
+    let tables = vec![(
+        "todos".to_string(),
+        vec![
+            todo("done", "completed", 1),
+            todo("checked", "todo", 1),
+            todo("open", "todo", 0),
+        ],
+    )];
+    let (bytes, _) = archive::write(&tables, 3).unwrap();
+
+    let read = archive::read(&bytes, SCHEMA_VERSION).unwrap();
+    assert_eq!(read.manifest.schema_version, 3);
+    let todos = &read.tables["todos"];
+    assert_eq!(todos[0]["completed_at"], 2_000);
+    assert_eq!(todos[1]["completed_at"], 2_000);
+    assert!(todos[2]["completed_at"].is_null());
+
+    // 不支持该结构版本的应用不做转换
+    let read = archive::read(&bytes, 3).unwrap();
+    assert!(read.tables["todos"][0].get("completed_at").is_none());
+
+    let db = memory_db().await;
+    let read = archive::read(&bytes, SCHEMA_VERSION).unwrap();
+    db.restore_tables(&read.tables).await.unwrap();
+    assert_eq!(db.get_todo("done").await.unwrap().completed_at, Some(2_000));
+    assert_eq!(db.get_todo("open").await.unwrap().completed_at, None);
+}
@@ -0,0 +1,152 @@
+//! 快照列表与轮换规则的测试
+use snippets_hub_lib::backup::{list_snapshots, rotate};
+use snippets_hub_lib::models::{BackupSettings, BackupSnapshotKind};
+use std::fs;
+use std::path::PathBuf;
+
+/// 测试专用的临时目录，测试结束时删除
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("snippetshub_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+
+    /// 创建空文件作为快照，name 不含前缀和扩展名，如 auto-20260311-120000
+    fn snapshot(&self, name: &str) {
+        fs::write(self.0.join(format!("snippets_hub-{}.sqlite", name)), b"").unwrap();
+    }
+
+    fn names(&self) -> Vec<String> {
+        list_snapshots(&self.0)
+            .unwrap()
+            .into_iter()
+            .map(|s| s.file_name)
+            .collect()
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn settings(keep_daily: usize, keep_weekly: usize) -> BackupSettings {
+    BackupSettings {
+        keep_daily,
+        keep_weekly,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn snapshots_are_listed_newest_first_including_same_second_suffixes() {
+    let dir = TempDir::new();
+    dir.snapshot("auto-20260311-120000");
+    dir.snapshot("auto-20260311-120000-2");
+    dir.snapshot("auto-20260311-120000-1");
+    dir.snapshot("manual-20260310-090000");
+    dir.snapshot("pre-restore-20260312-080000");
+    // 不符合命名规则的文件被忽略
+    dir.snapshot("auto-20260311-120000-x");
+    dir.snapshot("auto-2026031-120000");
+    dir.snapshot("hourly-20260311-120000");
+    fs::write(dir.0.join("notes.txt"), b"").unwrap();
+
+    assert_eq!(
+        dir.names(),
+        vec![
+            "snippets_hub-pre-restore-20260312-080000.sqlite",
+            "snippets_hub-auto-20260311-120000-2.sqlite",
+            "snippets_hub-auto-20260311-120000-1.sqlite",
+            "snippets_hub-auto-20260311-120000.sqlite",
+            "snippets_hub-manual-20260310-090000.sqlite",
+        ]
+    );
+
+    let snapshots = list_snapshots(&dir.0).unwrap();
+    assert_eq!(snapshots[0].kind, BackupSnapshotKind::PreRestore);
+    assert_eq!(snapshots[1].kind, BackupSnapshotKind::Auto);
+    assert_eq!(snapshots[1].created_at, snapshots[3].created_at);
+    assert_eq!(snapshots[4].kind, BackupSnapshotKind::Manual);
+}
+
+#[test]
+fn rotation_keeps_the_newest_auto_snapshot_per_day_and_week() {
+    let dir = TempDir::new();
+    // 2026-03-11 为周三，03-09 为同一 ISO 周的周一，03-08 与 03-01 为前两周的周日
+    dir.snapshot("auto-20260311-120000");
+    dir.snapshot("auto-20260311-120000-1");
+    dir.snapshot("auto-20260311-080000");
+    dir.snapshot("auto-20260310-100000");
+    dir.snapshot("auto-20260309-100000");
+    dir.snapshot("auto-20260308-100000");
+    dir.snapshot("auto-20260301-100000");
+    dir.snapshot("manual-20200101-000000");
+
+    let mut removed = rotate(&dir.0, &settings(2, 2)).unwrap();
+    removed.sort();
+    assert_eq!(
+        removed,
+        vec![
+            "snippets_hub-auto-20260301-100000.sqlite",
+            "snippets_hub-auto-20260309-100000.sqlite",
+            "snippets_hub-auto-20260311-080000.sqlite",
+            "snippets_hub-auto-20260311-120000.sqlite",
+        ]
+    );
+    assert_eq!(
+        dir.names(),
+        vec![
+            "snippets_hub-auto-20260311-120000-1.sqlite",
+            "snippets_hub-auto-20260310-100000.sqlite",
+            "snippets_hub-auto-20260308-100000.sqlite",
+            "snippets_hub-manual-20200101-000000.sqlite",
+        ]
+    );
+
+    // 再次轮换不会删除更多快照
+    assert!(rotate(&dir.0, &settings(2, 2)).unwrap().is_empty());
+}
+
+#[test]
+fn rotation_always_keeps_the_latest_auto_snapshot_and_manual_ones() {
+    let dir = TempDir::new();
+    dir.snapshot("auto-20260311-120000");
+    dir.snapshot("auto-20260310-120000");
+    dir.snapshot("manual-20260101-000000");
+    dir.snapshot("manual-20250101-000000");
+
+    let removed = rotate(&dir.0, &settings(0, 0)).unwrap();
+    assert_eq!(removed, vec!["snippets_hub-auto-20260310-120000.sqlite"]);
+    assert_eq!(
+        dir.names(),
+        vec![
+            "snippets_hub-auto-20260311-120000.sqlite",
+            "snippets_hub-manual-20260101-000000.sqlite",
+            "snippets_hub-manual-20250101-000000.sqlite",
+        ]
+    );
+}
+
+#[test]
+fn rotation_caps_pre_restore_snapshots() {
+    let dir = TempDir::new();
+    for day in 1..=7 {
+        dir.snapshot(&format!("pre-restore-202603{:02}-100000", day));
+    }
+
+    let mut removed = rotate(&dir.0, &BackupSettings::default()).unwrap();
+    removed.sort();
+    assert_eq!(
+        removed,
+        vec![
+            "snippets_hub-pre-restore-20260301-100000.sqlite",
+            "snippets_hub-pre-restore-20260302-100000.sqlite",
+        ]
+    );
+    assert_eq!(dir.names().len(), 5);
+}
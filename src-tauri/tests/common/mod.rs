@@ -0,0 +1,132 @@
+//! 集成测试共用的内存数据库与请求构造函数
+#![allow(dead_code)]
+
+use snippets_hub_lib::database::Database;
+use snippets_hub_lib::models::*;
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::SqlitePool;
+use std::str::FromStr;
+
+/// 新建一个已迁移到最新版本的内存数据库
+pub async fn memory_db() -> Database {
+    Database::from_pool(memory_pool().await)
+        .await
+        .expect("run migrations")
+}
+
+/// 内存数据库的连接池，测试需要绕过仓储直接执行 SQL 时使用
+///
+/// 每个连接都有独立的内存数据库，因此连接池固定为一个且不回收连接
+pub async fn memory_pool() -> SqlitePool {
+    let options = SqliteConnectOptions::from_str("sqlite::memory:")
+        .expect("parse in-memory url")
+        .foreign_keys(true);
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .idle_timeout(None)
+        .max_lifetime(None)
+        .connect_with(options)
+        .await
+        .expect("open in-memory database")
+}
+
+pub fn snippet(title: &str, language: &str, code: &str) -> CreateSnippetRequest {
+    CreateSnippetRequest {
+        title: title.to_string(),
+        description: String::new(),
+        code: code.to_string(),
+        language: language.to_string(),
+        tags: Vec::new(),
+        folder_id: None,
+        project_id: None,
+    }
+}
+
+/// 导出文件或备份中的完整代码片段记录
+pub fn code_snippet(id: &str, title: &str) -> CodeSnippet {
+    CodeSnippet {
+        id: id.to_string(),
+        title: title.to_string(),
+        description: String::new(),
+        code: "echo hi".to_string(),
+        language: "shell".to_string(),
+        tags: Vec::new(),
+        folder_id: None,
+        project_id: None,
+        is_favorite: false,
+        usage_count: 0,
+        created_at: 1,
+        updated_at: 1,
+        library_id: None,
+    }
+}
+
+pub fn snippet_update(id: &str) -> UpdateSnippetRequest {
+    UpdateSnippetRequest {
+        id: id.to_string(),
+        title: None,
+        description: None,
+        code: None,
+        language: None,
+        tags: None,
+        folder_id: None,
+        project_id: None,
+        is_favorite: None,
+        usage_count: None,
+    }
+}
+
+pub fn todo(title: &str) -> CreateTodoRequest {
+    CreateTodoRequest {
+        title: title.to_string(),
+        description: None,
+        status: None,
+        priority: None,
+        due_date: None,
+        estimated_hours: None,
+        assignee: None,
+        project_id: None,
+        parent_id: None,
+        recurring_config: None,
+        dependencies: None,
+        tags: None,
+    }
+}
+
+pub fn todo_update(id: &str) -> UpdateTodoRequest {
+    UpdateTodoRequest {
+        id: id.to_string(),
+        title: None,
+        description: None,
+        status: None,
+        priority: None,
+        due_date: None,
+        estimated_hours: None,
+        actual_hours: None,
+        progress: None,
+        assignee: None,
+        project_id: None,
+        parent_id: None,
+        recurring_config: None,
+        dependencies: None,
+        completed: None,
+        archived: None,
+        tags: None,
+    }
+}
+
+pub fn todo_query() -> TodoSearchQuery {
+    TodoSearchQuery {
+        keyword: None,
+        status: None,
+        priority: None,
+        tags: None,
+        assignee: None,
+        project_id: None,
+        parent_id: None,
+        due_date_from: None,
+        due_date_to: None,
+        completed: None,
+        archived: None,
+    }
+}
@@ -0,0 +1,131 @@
+//! TODO CSV 导出的测试
+mod common;
+
+use common::{memory_db, todo, todo_update};
+use snippets_hub_lib::formats::csv;
+use snippets_hub_lib::models::TodoCsvColumn;
+use snippets_hub_lib::repository::TodoRepository;
+use std::collections::HashMap;
+
+#[tokio::test]
+async fn formula_cells_are_neutralised_and_quoted() {
+    let db = memory_db().await;
+    let mut titles = Vec::new();
+    for title in [
+        "=HYPERLINK(\"http://x\")",
+        "+1+cmd|' /C calc'!A0",
+        "-2+3",
+        "- bullet",
+        "@SUM(A1)",
+        "\tTab",
+        "Plain, text",
+    ] {
+        titles.push(db.create_todo(todo(title)).await.unwrap());
+    }
+
+    let output = csv::render(
+        &titles,
+        &[TodoCsvColumn::Title],
+        &HashMap::new(),
+        &HashMap::new(),
+    );
+    let lines: Vec<&str> = output
+        .trim_start_matches('\u{feff}')
+        .split("\r\n")
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            "Title",
+            "\"'=HYPERLINK(\"\"http://x\"\")\"",
+            "'+1+cmd|' /C calc'!A0",
+            "'-2+3",
+            "'- bullet",
+            "'@SUM(A1)",
+            "'\tTab",
+            "\"Plain, text\"",
+            "",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn completion_time_is_exported_and_cleared_on_reopen() {
+    let db = memory_db().await;
+    let done = db.create_todo(todo("Done")).await.unwrap();
+    let open = db.create_todo(todo("Open")).await.unwrap();
+    assert!(done.completed_at.is_none());
+
+    let mut update = todo_update(&done.id);
+    update.status = Some("completed".to_string());
+    update.completed = Some(true);
+    let done = db.update_todo(update).await.unwrap();
+    let completed_at = done.completed_at.unwrap();
+
+    // 再次保存不改变完成时间
+    let mut update = todo_update(&done.id);
+    update.completed = Some(true);
+    assert_eq!(
+        db.update_todo(update).await.unwrap().completed_at,
+        Some(completed_at)
+    );
+
+    let output = csv::render(
+        &[done.clone(), open],
+        &[TodoCsvColumn::Title, TodoCsvColumn::CompletedAt],
+        &HashMap::new(),
+        &HashMap::new(),
+    );
+    let expected = chrono::DateTime::from_timestamp_millis(completed_at)
+        .unwrap()
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string();
+    assert_eq!(
+        output,
+        format!(
+            "\u{feff}Title,Completed At\r\nDone,{}\r\nOpen,\r\n",
+            expected
+        )
+    );
+
+    let mut update = todo_update(&done.id);
+    update.status = Some("todo".to_string());
+    update.completed = Some(false);
+    assert!(db.update_todo(update).await.unwrap().completed_at.is_none());
+}
+
+#[tokio::test]
+async fn signed_numbers_are_exported_unchanged() {
+    let db = memory_db().await;
+    let mut todos = Vec::new();
+    for title in ["-5", "+12", "-0.25", "-1e3"] {
+        todos.push(db.create_todo(todo(title)).await.unwrap());
+    }
+    let mut update = todo_update(&todos[0].id);
+    update.actual_hours = Some(-1.5);
+    todos[0] = db.update_todo(update).await.unwrap();
+
+    let output = csv::render(
+        &todos,
+        &[TodoCsvColumn::Title, TodoCsvColumn::ActualHours],
+        &HashMap::new(),
+        &HashMap::new(),
+    );
+    let lines: Vec<&str> = output
+        .trim_start_matches('\u{feff}')
+        .split("\r\n")
+        .collect();
+
+    assert_eq!(
+        lines,
+        vec![
+            "Title,Actual Hours",
+            "-5,-1.5",
+            "+12,",
+            "-0.25,",
+            "-1e3,",
+            "",
+        ]
+    );
+}
@@ -0,0 +1,280 @@
+//! 编辑器代码片段格式的解析与生成测试
+mod common;
+
+use common::code_snippet;
+use snippets_hub_lib::formats::{
+    jetbrains, slugify, snipmate, sublime, trigger_tag, triggers, ultisnips, vscode, yasnippet,
+};
+use snippets_hub_lib::models::Folder;
+
+#[test]
+fn strip_jsonc_removes_comments_and_trailing_commas() {
+    let content = r#"{
+        // line comment
+        "a": "keep // this, and /* this */",
+        /* block
+           comment */
+        "b": [1, 2,],
+        "c": "quote \" inside",
+    }"#;
+    let value: serde_json::Value = serde_json::from_str(&vscode::strip_jsonc(content)).unwrap();
+    assert_eq!(value["a"], "keep // this, and /* this */");
+    assert_eq!(value["b"], serde_json::json!([1, 2]));
+    assert_eq!(value["c"], "quote \" inside");
+}
+
+#[test]
+fn slugify_builds_triggers_from_titles() {
+    assert_eq!(slugify("Quick Sort"), "quick-sort");
+    assert_eq!(slugify("  HTTP -> JSON!  "), "http-json");
+    assert_eq!(slugify("--"), "");
+}
+
+#[test]
+fn trigger_tags_round_trip() {
+    let tags = vec![
+        trigger_tag("qs"),
+        "algorithm".to_string(),
+        trigger_tag("sort"),
+    ];
+    assert_eq!(tags[0], "trigger:qs");
+    assert_eq!(triggers(&tags), vec!["qs", "sort"]);
+}
+
+#[test]
+fn vscode_round_trip_keeps_line_endings_and_triggers() {
+    let mut snippet = code_snippet("s1", "Print");
+    snippet.language = "shell".to_string();
+    snippet.code = "echo $1\r\necho done\n".to_string();
+    snippet.description = "Print things".to_string();
+    snippet.tags = vec![trigger_tag("pr")];
+
+    let rendered = vscode::render(&[snippet.clone()]).unwrap();
+    assert!(rendered.contains("\"scope\": \"shellscript\""));
+
+    let drafts = vscode::parse(&rendered, None).unwrap();
+    let draft = drafts[0].as_ref().unwrap();
+    assert_eq!(draft.title, "Print");
+    assert_eq!(draft.code, snippet.code);
+    assert_eq!(draft.language, "shell");
+    assert_eq!(draft.description, "Print things");
+    assert_eq!(draft.tags, vec![trigger_tag("pr")]);
+}
+
+#[test]
+fn vscode_parse_accepts_jsonc_and_reports_bad_entries() {
+    let content = r#"{
+        // generated by hand
+        "Log": { "prefix": ["log", "cl"], "body": ["console.log($1);", "$0"], },
+        "Broken": { "prefix": "x" },
+    }"#;
+    let drafts = vscode::parse(content, Some("javascript")).unwrap();
+    let log = drafts[0].as_ref().unwrap();
+    assert_eq!(log.code, "console.log($1);\n$0");
+    assert_eq!(log.language, "javascript");
+    assert_eq!(log.tags, vec![trigger_tag("log"), trigger_tag("cl")]);
+    assert_eq!(
+        drafts[1].as_ref().unwrap_err().message,
+        "Snippet has no body"
+    );
+}
+
+#[test]
+fn jetbrains_round_trip_keeps_title_description_and_dollars() {
+    let mut shell = code_snippet("s1", "Loop files");
+    shell.description = "Iterate over arguments".to_string();
+    shell.code = "for f in \"$@\"; do echo \"$f\" $$; done\n$END$".to_string();
+    shell.tags = vec![trigger_tag("forf")];
+    let mut untitled = code_snippet("s2", "Greeting");
+    untitled.language = "python".to_string();
+    untitled.code = "print(\"$NAME$\")".to_string();
+
+    let xml = jetbrains::render(&[shell.clone(), untitled.clone()], "Shell");
+    assert!(xml.contains("$$@"), "{}", xml);
+    assert!(xml.contains("<variable name=\"NAME\""));
+
+    let drafts = jetbrains::parse(&xml).unwrap();
+    let first = drafts[0].as_ref().unwrap();
+    assert_eq!(first.title, "Loop files");
+    assert_eq!(first.description, "Iterate over arguments");
+    assert_eq!(first.code, shell.code);
+    assert_eq!(first.language, "shell");
+    assert_eq!(first.tags, vec![trigger_tag("forf")]);
+    assert_eq!(first.folder_path, vec!["Shell"]);
+
+    let second = drafts[1].as_ref().unwrap();
+    assert_eq!(second.title, "Greeting");
+    assert_eq!(second.description, "");
+    assert_eq!(second.code, untitled.code);
+    assert_eq!(second.language, "python");
+}
+
+#[test]
+fn jetbrains_templates_from_the_ide_use_description_as_title() {
+    let xml = r#"<templateSet group="Rust">
+  <template name="pn" value="println!(&quot;$$ {}&quot;, $VALUE$);$END$" description="Print a value">
+    <variable name="VALUE" expression="" defaultValue="&quot;x&quot;" alwaysStopAt="true" />
+    <context><option name="RUST_FILE" value="true" /></context>
+  </template>
+  <template name="bare" value="x" />
+</templateSet>"#;
+    let drafts = jetbrains::parse(xml).unwrap();
+    let printed = drafts[0].as_ref().unwrap();
+    assert_eq!(printed.title, "Print a value");
+    assert_eq!(printed.code, "println!(\"$ {}\", $VALUE$);$END$");
+    assert_eq!(printed.language, "rust");
+    assert_eq!(printed.notes.len(), 1);
+    assert_eq!(drafts[1].as_ref().unwrap().title, "bare");
+}
+
+#[test]
+fn ultisnips_parses_headers_and_skips_unsupported_blocks() {
+    let content = [
+        "priority -50",
+        "global !p",
+        "def helper(): pass",
+        "endglobal",
+        "",
+        "snippet fn \"Function definition\" b",
+        "fn ${1:name}() {",
+        "    $0",
+        "}",
+        "endsnippet",
+        "",
+        "\tsnippet !for each! \"Loop\"",
+        "for x in xs {}",
+        "endsnippet",
+        "\u{a0}\u{a0}\u{a0}\u{a0}snippet nbsp",
+        "ok",
+        "endsnippet",
+        "context \"math()\"",
+        "snippet ^beg \"Begin\" r",
+        "\\begin{$1}",
+        "endsnippet",
+        "snippet missing",
+        "never closed",
+    ]
+    .join("\n");
+
+    let results = ultisnips::parse(&content, "rust");
+    assert_eq!(results.len(), 5);
+
+    let function = results[0].as_ref().unwrap();
+    assert_eq!(function.title, "Function definition");
+    assert_eq!(function.code, "fn ${1:name}() {\n    $0\n}");
+    assert_eq!(function.tags, vec![trigger_tag("fn")]);
+
+    let each = results[1].as_ref().unwrap();
+    assert_eq!(each.tags, vec![trigger_tag("for each")]);
+    assert_eq!(each.title, "Loop");
+    assert_eq!(results[2].as_ref().unwrap().tags, vec![trigger_tag("nbsp")]);
+
+    let regex = results[3].as_ref().unwrap();
+    assert_eq!(regex.tags, vec![trigger_tag("^beg")]);
+    assert_eq!(regex.notes.len(), 2);
+
+    let error = results[4].as_ref().unwrap_err();
+    assert!(
+        error.message.contains("missing endsnippet"),
+        "{}",
+        error.message
+    );
+}
+
+#[test]
+fn snipmate_parses_tab_indented_bodies() {
+    let content = [
+        "# comment",
+        "snippet for for loop",
+        "\tfor ${1:i} in ${2:items}:",
+        "\t\t${3:pass}",
+        "",
+        "snippet imp",
+        "\timport ${1:os}",
+        "snippet",
+        "snippet empty",
+    ]
+    .join("\n");
+
+    let results = snipmate::parse(&content, "python");
+    assert_eq!(results.len(), 4);
+
+    let for_loop = results[0].as_ref().unwrap();
+    assert_eq!(for_loop.title, "for loop");
+    assert_eq!(for_loop.code, "for ${1:i} in ${2:items}:\n\t${3:pass}");
+    assert_eq!(for_loop.tags, vec![trigger_tag("for")]);
+
+    let import = results[1].as_ref().unwrap();
+    assert_eq!(import.title, "imp");
+    assert_eq!(import.code, "import ${1:os}");
+    assert!(results[2].is_err());
+    assert!(results[3].is_err());
+}
+
+#[test]
+fn sublime_parses_cdata_entities_and_scope() {
+    let content = r#"<snippet>
+    <content><![CDATA[
+console.log(${1:value}) && 1 < 2;
+]]></content>
+    <tabTrigger>log</tabTrigger>
+    <description>Log &amp; print</description>
+    <scope>source.js - comment</scope>
+</snippet>"#;
+    let draft = sublime::parse(content, "log.sublime-snippet").unwrap();
+    assert_eq!(draft.title, "Log & print");
+    assert_eq!(draft.code, "console.log(${1:value}) && 1 < 2;");
+    assert_eq!(draft.language, "javascript");
+    assert_eq!(draft.tags, vec![trigger_tag("log")]);
+
+    let bare = sublime::parse("<snippet><content>x</content></snippet>", "bare").unwrap();
+    assert_eq!(bare.title, "bare");
+    assert_eq!(bare.language, "text");
+    assert!(sublime::parse("<snippet></snippet>", "none").is_err());
+}
+
+#[test]
+fn yasnippet_round_trip_keeps_group_and_backquotes() {
+    let backend = Folder {
+        id: "f1".to_string(),
+        name: "Backend".to_string(),
+        parent_id: None,
+        created_at: 1,
+    };
+    let rust = Folder {
+        id: "f2".to_string(),
+        name: "Rust".to_string(),
+        parent_id: Some("f1".to_string()),
+        created_at: 1,
+    };
+    let mut snippet = code_snippet("s1", "Debug print");
+    snippet.language = "rust".to_string();
+    snippet.code = "dbg!(`$1`);".to_string();
+    snippet.folder_id = Some(rust.id.clone());
+    snippet.tags = vec![trigger_tag("dbg")];
+
+    let files = yasnippet::render(&[snippet.clone()], &[backend, rust]);
+    let (path, content) = &files[0];
+    assert_eq!(path, "rust-mode/dbg");
+    assert!(content.contains("# group: Backend.Rust\n"), "{}", content);
+
+    let draft = yasnippet::parse(content, "rust-mode", &[], "dbg").unwrap();
+    assert_eq!(draft.title, "Debug print");
+    assert_eq!(draft.code, snippet.code);
+    assert_eq!(draft.language, "rust");
+    assert_eq!(draft.tags, vec![trigger_tag("dbg")]);
+    assert_eq!(draft.folder_path, vec!["Backend", "Rust"]);
+    assert!(draft.notes.is_empty());
+}
+
+#[test]
+fn yasnippet_uses_directories_without_group_and_notes_elisp() {
+    let content =
+        "# -*- mode: snippet -*-\n# key: now\n# expand-env: ((x 1))\n# --\n`(current-time-string)`";
+    let group_dirs = vec!["time".to_string()];
+    let draft = yasnippet::parse(content, "emacs-lisp-mode", &group_dirs, "now").unwrap();
+    assert_eq!(draft.title, "now");
+    assert_eq!(draft.language, "elisp");
+    assert_eq!(draft.folder_path, group_dirs);
+    assert_eq!(draft.notes.len(), 2);
+}
@@ -0,0 +1,84 @@
+//! 数据变更事件的集成测试
+mod common;
+
+use common::{memory_db, snippet, todo};
+use snippets_hub_lib::events::{ChangeAction, ChangeEntity, ChangeEvent};
+use snippets_hub_lib::repository::{FolderRepository, SnippetRepository, TodoRepository};
+use tokio::sync::broadcast::Receiver;
+
+/// 取出目前已发布的全部事件
+fn drain(events: &mut Receiver<ChangeEvent>) -> Vec<ChangeEvent> {
+    std::iter::from_fn(|| events.try_recv().ok()).collect()
+}
+
+fn names(events: &[ChangeEvent]) -> Vec<String> {
+    events.iter().map(ChangeEvent::name).collect()
+}
+
+#[tokio::test]
+async fn snippet_writes_publish_typed_events() {
+    let db = memory_db().await;
+    let mut events = db.subscribe();
+
+    let created = db
+        .create_snippet(snippet("Hello", "rust", "fn main() {}"))
+        .await
+        .unwrap();
+    let mut update = common::snippet_update(&created.id);
+    update.title = Some("Hello, world".to_string());
+    db.update_snippet(update).await.unwrap();
+    db.delete_snippet(&created.id).await.unwrap();
+
+    let published = drain(&mut events);
+    assert_eq!(
+        names(&published),
+        vec!["snippet:created", "snippet:updated", "snippet:deleted"]
+    );
+    assert!(published.iter().all(|e| e.ids == vec![created.id.clone()]));
+    assert_eq!(
+        published[1].payload.as_ref().unwrap()["title"],
+        "Hello, world"
+    );
+    assert!(published[2].payload.is_none());
+}
+
+#[tokio::test]
+async fn cascading_deletes_report_every_affected_id() {
+    let db = memory_db().await;
+
+    let folder = db.create_folder("Old".to_string(), None).await.unwrap();
+    let mut req = snippet("Filed", "text", "filed");
+    req.folder_id = Some(folder.id.clone());
+    let filed = db.create_snippet(req).await.unwrap();
+    let parent = db.create_todo(todo("Parent")).await.unwrap();
+    let mut child = todo("Child");
+    child.parent_id = Some(parent.id.clone());
+    let child = db.create_todo(child).await.unwrap();
+
+    let mut events = db.subscribe();
+    db.delete_folder(&folder.id).await.unwrap();
+    db.delete_todo(parent.id.clone()).await.unwrap();
+
+    let published = drain(&mut events);
+    assert_eq!(
+        names(&published),
+        vec!["folder:deleted", "snippet:updated", "todo:deleted"]
+    );
+    assert_eq!(published[1].ids, vec![filed.id]);
+    assert_eq!(published[2].entity, ChangeEntity::Todo);
+    assert_eq!(published[2].action, ChangeAction::Deleted);
+    assert_eq!(published[2].ids, vec![child.id, parent.id]);
+}
+
+#[tokio::test]
+async fn reads_publish_nothing() {
+    let db = memory_db().await;
+    db.create_snippet(snippet("Hello", "rust", "fn main() {}"))
+        .await
+        .unwrap();
+
+    let mut events = db.subscribe();
+    db.get_all_snippets().await.unwrap();
+    db.get_todos().await.unwrap();
+    assert!(drain(&mut events).is_empty());
+}
@@ -0,0 +1,157 @@
+//! iCalendar VTODO 解析与导入顺序的测试
+mod common;
+
+use chrono::{Local, TimeZone, Utc};
+use common::memory_db;
+use snippets_hub_lib::formats::{ical, parents_first};
+use snippets_hub_lib::models::{ImportAction, TodoDraft};
+use snippets_hub_lib::repository::TodoRepository;
+use std::collections::HashSet;
+
+fn calendar(lines: &[&str]) -> String {
+    let mut content = vec!["BEGIN:VCALENDAR", "VERSION:2.0"];
+    content.extend_from_slice(lines);
+    content.push("END:VCALENDAR");
+    content.join("\r\n")
+}
+
+fn parse_one(lines: &[&str]) -> TodoDraft {
+    let mut items = ical::parse(&calendar(lines)).unwrap();
+    assert_eq!(items.len(), 1);
+    items.remove(0).unwrap()
+}
+
+fn draft(id: &str, parent_id: Option<&str>) -> TodoDraft {
+    let mut draft = TodoDraft::new(id.to_string());
+    draft.id = Some(id.to_string());
+    draft.parent_id = parent_id.map(str::to_string);
+    draft
+}
+
+#[test]
+fn folded_lines_and_escaped_text_are_decoded() {
+    let todo = parse_one(&[
+        "BEGIN:VTODO",
+        "UID:a",
+        "SUMMARY:Write release notes\\, part 1\\; draft",
+        "DESCRIPTION:First line\\nSecond line with a back\\\\slash and a long",
+        "  folded tail",
+        "CATEGORIES:docs,release\\,notes",
+        "END:VTODO",
+    ]);
+    assert_eq!(todo.title, "Write release notes, part 1; draft");
+    assert_eq!(
+        todo.description.as_deref(),
+        Some("First line\nSecond line with a back\\slash and a long folded tail")
+    );
+    assert_eq!(todo.tag_names, vec!["docs", "release,notes"]);
+}
+
+#[test]
+fn date_times_honour_tzid_utc_and_dates() {
+    let todo = parse_one(&[
+        "BEGIN:VTODO",
+        "UID:a",
+        "SUMMARY:Call",
+        "DUE;TZID=Asia/Tokyo:20260301T080000",
+        "CREATED:20260101T120000Z",
+        "LAST-MODIFIED;TZID=America/New_York:20260102T070000",
+        "END:VTODO",
+    ]);
+    let due = Utc.with_ymd_and_hms(2026, 2, 28, 23, 0, 0).unwrap();
+    let expected = due.with_timezone(&Local).format("%Y-%m-%d").to_string();
+    assert_eq!(todo.due_date.as_deref(), Some(expected.as_str()));
+    assert_eq!(
+        todo.created_at,
+        Some(
+            Utc.with_ymd_and_hms(2026, 1, 1, 12, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        )
+    );
+    assert_eq!(
+        todo.updated_at,
+        Some(
+            Utc.with_ymd_and_hms(2026, 1, 2, 12, 0, 0)
+                .unwrap()
+                .timestamp_millis()
+        )
+    );
+    assert!(todo.notes.is_empty(), "{:?}", todo.notes);
+
+    let todo = parse_one(&[
+        "BEGIN:VTODO",
+        "UID:b",
+        "SUMMARY:Pay rent",
+        "DUE;VALUE=DATE:20260315",
+        "CREATED;TZID=W. Europe Standard Time:20260101T120000",
+        "END:VTODO",
+    ]);
+    assert_eq!(todo.due_date.as_deref(), Some("2026-03-15"));
+    assert!(todo.created_at.is_some());
+    assert!(todo.notes[0].contains("W. Europe Standard Time"));
+}
+
+#[test]
+fn related_to_and_nested_alarms_are_mapped() {
+    let todo = parse_one(&[
+        "BEGIN:VTODO",
+        "UID:child",
+        "SUMMARY:Child",
+        "RELATED-TO:parent",
+        "RELATED-TO;RELTYPE=DEPENDS-ON:other",
+        "RELATED-TO;RELTYPE=SIBLING:sibling",
+        "BEGIN:VALARM",
+        "ACTION:DISPLAY",
+        "SUMMARY:Alarm text must not replace the title",
+        "TRIGGER:-PT15M",
+        "END:VALARM",
+        "STATUS:IN-PROCESS",
+        "END:VTODO",
+    ]);
+    assert_eq!(todo.title, "Child");
+    assert_eq!(todo.parent_id.as_deref(), Some("parent"));
+    assert_eq!(todo.dependencies, vec!["other"]);
+    assert_eq!(todo.status, "in_progress");
+    assert_eq!(todo.notes.len(), 2);
+    assert!(todo.notes.iter().any(|n| n.contains("VALARM")));
+}
+
+#[tokio::test]
+async fn children_before_parents_and_cycles_import_cleanly() {
+    let db = memory_db().await;
+    let drafts = vec![
+        (0, draft("grandchild", Some("child"))),
+        (1, draft("child", Some("root"))),
+        (2, draft("root", None)),
+        (3, draft("loop-a", Some("loop-b"))),
+        (4, draft("loop-b", Some("loop-a"))),
+        (5, draft("self", Some("self"))),
+    ];
+    let known_ids: HashSet<String> = drafts
+        .iter()
+        .filter_map(|(_, draft)| draft.id.clone())
+        .collect();
+
+    let ordered = parents_first(drafts);
+    let order: Vec<usize> = ordered.iter().map(|(index, _)| *index).collect();
+    assert_eq!(order, vec![2, 1, 0, 3, 4, 5]);
+
+    let results = db.import_todos(ordered, &known_ids, false).await.unwrap();
+    assert!(results
+        .iter()
+        .all(|result| result.action == ImportAction::Create));
+
+    assert_eq!(
+        db.get_todo("root").await.unwrap().subtasks[0]
+            .subtasks
+            .len(),
+        1
+    );
+    assert_eq!(db.get_todo("loop-a").await.unwrap().parent_id, None);
+    assert_eq!(
+        db.get_todo("loop-b").await.unwrap().parent_id.as_deref(),
+        Some("loop-a")
+    );
+    assert_eq!(db.get_todo("self").await.unwrap().parent_id, None);
+}
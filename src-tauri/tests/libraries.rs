@@ -0,0 +1,167 @@
+//! 资料库文件的打开与切换
+mod common;
+
+use common::snippet;
+use snippets_hub_lib::database::Database;
+use snippets_hub_lib::repository::SnippetRepository;
+use std::path::PathBuf;
+
+/// 测试结束时删除的临时目录
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        let dir = std::env::temp_dir().join(format!("snippets-hub-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        TempDir(dir)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+#[tokio::test]
+async fn switching_libraries_moves_every_clone_to_the_new_file() {
+    let dir = TempDir::new();
+    let work = dir.0.join("work.sqlite");
+    let personal = dir.0.join("personal.sqlite");
+
+    let db = Database::open(&work, true).await.unwrap();
+    let managed = db.clone();
+    db.create_snippet(snippet("Work", "text", "work"))
+        .await
+        .unwrap();
+
+    db.switch_library(&personal, true).await.unwrap();
+    assert_eq!(managed.path(), Some(personal.clone()));
+    assert!(managed.get_all_snippets().await.unwrap().is_empty());
+    managed
+        .create_snippet(snippet("Personal", "text", "personal"))
+        .await
+        .unwrap();
+
+    db.switch_library(&work, false).await.unwrap();
+    let titles: Vec<String> = managed
+        .get_all_snippets()
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|s| s.title)
+        .collect();
+    assert_eq!(titles, vec!["Work".to_string()]);
+}
+
+#[tokio::test]
+async fn failed_switch_keeps_the_current_library() {
+    let dir = TempDir::new();
+    let work = dir.0.join("work.sqlite");
+
+    let db = Database::open(&work, true).await.unwrap();
+    let missing = dir.0.join("missing.sqlite");
+    assert!(db.switch_library(&missing, false).await.is_err());
+    assert_eq!(db.path(), Some(work));
+    assert!(db.get_all_snippets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn read_only_libraries_reject_writes() {
+    let dir = TempDir::new();
+    let team = dir.0.join("team.sqlite");
+
+    let writer = Database::open(&team, true).await.unwrap();
+    let shared = writer
+        .create_snippet(snippet("Deploy script", "bash", "./deploy.sh"))
+        .await
+        .unwrap();
+    writer.close().await;
+    // 发布到共享盘的只有主文件
+    let _ = std::fs::remove_file(dir.0.join("team.sqlite-wal"));
+    let _ = std::fs::remove_file(dir.0.join("team.sqlite-shm"));
+
+    let mounted = Database::open_read_only(&team).await.unwrap();
+    let loaded = mounted.get_snippet(&shared.id).await.unwrap().unwrap();
+    assert_eq!(loaded.title, "Deploy script");
+
+    let error = mounted
+        .create_snippet(snippet("Local edit", "text", "nope"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), "read_only");
+    let error = mounted.delete_snippet(&shared.id).await.unwrap_err();
+    assert_eq!(error.code(), "read_only");
+}
+
+#[tokio::test]
+async fn read_only_libraries_see_later_changes() {
+    let dir = TempDir::new();
+    let team = dir.0.join("team.sqlite");
+
+    let writer = Database::open(&team, true).await.unwrap();
+    writer
+        .create_snippet(snippet("Deploy script", "bash", "./deploy.sh"))
+        .await
+        .unwrap();
+    writer.close().await;
+
+    let mounted = Database::open_read_only(&team).await.unwrap();
+    assert_eq!(mounted.get_all_snippets().await.unwrap().len(), 1);
+
+    let writer = Database::open(&team, false).await.unwrap();
+    writer
+        .create_snippet(snippet("Rollback script", "bash", "./rollback.sh"))
+        .await
+        .unwrap();
+    assert_eq!(mounted.get_all_snippets().await.unwrap().len(), 2);
+    writer.close().await;
+    assert_eq!(mounted.get_all_snippets().await.unwrap().len(), 2);
+}
+
+#[tokio::test]
+async fn read_only_open_rejects_files_that_are_not_libraries() {
+    let dir = TempDir::new();
+    let other = dir.0.join("other.sqlite");
+
+    let pool = sqlx::SqlitePool::connect(&format!("sqlite:{}?mode=rwc", other.display()))
+        .await
+        .unwrap();
+    sqlx::query("CREATE TABLE notes (id TEXT)")
+        .execute(&pool)
+        .await
+        .unwrap();
+    pool.close().await;
+
+    let error = Database::open_read_only(&other).await.err().unwrap();
+    assert_eq!(error.code(), "invalid_data");
+}
+
+#[tokio::test]
+async fn concurrent_edits_from_two_connections_all_succeed() {
+    let dir = TempDir::new();
+    let path = dir.0.join("shared.sqlite");
+    let first = Database::open(&path, true).await.unwrap();
+    let second = Database::open(&path, true).await.unwrap();
+    let created = first
+        .create_snippet(snippet("Shared", "text", "shared"))
+        .await
+        .unwrap();
+
+    // 每次编辑都先读后写；两个连接交替提交时不应出现 SQLITE_BUSY_SNAPSHOT
+    let edit = |db: &Database, prefix: &'static str| {
+        let db = db.clone();
+        let id = created.id.clone();
+        async move {
+            for i in 0..20 {
+                let mut update = common::snippet_update(&id);
+                update.title = Some(format!("{} {}", prefix, i));
+                db.update_snippet(update).await.unwrap();
+            }
+        }
+    };
+    tokio::join!(edit(&first, "first"), edit(&second, "second"));
+
+    first.close().await;
+    second.close().await;
+}
@@ -0,0 +1,185 @@
+//! Linter 输出解析与错误分类的测试
+use snippets_hub_lib::lint::{lint_snippet, LinterKind};
+use snippets_hub_lib::models::Diagnostic;
+
+/// (起始行, 起始列, 结束行, 结束列, 严重程度, 代码, 消息)，坐标为 0 起始
+type Summary = (u32, u32, u32, u32, Option<i32>, Option<String>, String);
+
+fn summarize(diagnostics: &[Diagnostic]) -> Vec<Summary> {
+    diagnostics
+        .iter()
+        .map(|d| {
+            (
+                d.range.start.line,
+                d.range.start.character,
+                d.range.end.line,
+                d.range.end.character,
+                d.severity,
+                d.code.clone(),
+                d.message.clone(),
+            )
+        })
+        .collect()
+}
+
+fn parse(kind: LinterKind, report: &str) -> Vec<Summary> {
+    summarize(&kind.parse(report).expect("parse linter output"))
+}
+
+#[test]
+fn clippy_keeps_primary_spans_and_skips_summaries() {
+    let report = r#"{"$message_type":"diagnostic","message":"unused variable: `x`","code":{"code":"unused_variables","explanation":null},"level":"warning","spans":[{"file_name":"snippet.rs","line_start":2,"line_end":2,"column_start":9,"column_end":10,"is_primary":true}],"children":[],"rendered":"warning: unused variable"}
+{"$message_type":"diagnostic","message":"mismatched types","code":{"code":"E0308","explanation":"..."},"level":"error","spans":[{"file_name":"snippet.rs","line_start":1,"line_end":1,"column_start":1,"column_end":4,"is_primary":false},{"file_name":"snippet.rs","line_start":3,"line_end":3,"column_start":5,"column_end":12,"is_primary":true}],"children":[],"rendered":"error[E0308]"}
+{"$message_type":"diagnostic","message":"aborting due to 1 previous error","code":null,"level":"error","spans":[],"children":[],"rendered":"error: aborting"}
+error: could not compile
+"#;
+    assert_eq!(
+        parse(LinterKind::Clippy, report),
+        vec![
+            (
+                1,
+                8,
+                1,
+                9,
+                Some(2),
+                Some("unused_variables".to_string()),
+                "unused variable: `x`".to_string()
+            ),
+            (
+                2,
+                4,
+                2,
+                11,
+                Some(1),
+                Some("E0308".to_string()),
+                "mismatched types".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn ruff_marks_syntax_errors_as_errors() {
+    let report = r#"[
+  {"code":"F401","message":"`os` imported but unused","location":{"row":1,"column":8},"end_location":{"row":1,"column":10},"filename":"snippet.py"},
+  {"code":null,"message":"SyntaxError: Expected an expression","location":{"row":3,"column":5},"end_location":null,"filename":"snippet.py"}
+]"#;
+    assert_eq!(
+        parse(LinterKind::Ruff, report),
+        vec![
+            (
+                0,
+                7,
+                0,
+                9,
+                Some(2),
+                Some("F401".to_string()),
+                "`os` imported but unused".to_string()
+            ),
+            (
+                2,
+                4,
+                2,
+                4,
+                Some(1),
+                None,
+                "SyntaxError: Expected an expression".to_string()
+            ),
+        ]
+    );
+    assert!(parse(LinterKind::Ruff, "  \n").is_empty());
+}
+
+#[test]
+fn eslint_reads_messages_of_every_file() {
+    let report = r#"[{"filePath":"snippet.js","messages":[
+  {"ruleId":"no-unused-vars","severity":2,"message":"'a' is assigned a value but never used.","line":1,"column":7,"endLine":1,"endColumn":8},
+  {"ruleId":null,"severity":1,"message":"Unused eslint-disable directive.","line":4,"column":1}
+],"errorCount":1,"warningCount":1}]"#;
+    assert_eq!(
+        parse(LinterKind::Eslint, report),
+        vec![
+            (
+                0,
+                6,
+                0,
+                7,
+                Some(1),
+                Some("no-unused-vars".to_string()),
+                "'a' is assigned a value but never used.".to_string()
+            ),
+            (
+                3,
+                0,
+                3,
+                0,
+                Some(2),
+                None,
+                "Unused eslint-disable directive.".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn shellcheck_codes_are_prefixed_and_levels_mapped() {
+    let report = r#"[
+  {"file":"snippet.sh","line":2,"endLine":2,"column":6,"endColumn":8,"level":"info","code":2086,"message":"Double quote to prevent globbing and word splitting."},
+  {"file":"snippet.sh","line":1,"endLine":1,"column":1,"endColumn":1,"level":"style","code":2148,"message":"Tips depend on target shell."}
+]"#;
+    assert_eq!(
+        parse(LinterKind::Shellcheck, report),
+        vec![
+            (
+                1,
+                5,
+                1,
+                7,
+                Some(3),
+                Some("SC2086".to_string()),
+                "Double quote to prevent globbing and word splitting.".to_string()
+            ),
+            (
+                0,
+                0,
+                0,
+                0,
+                Some(4),
+                Some("SC2148".to_string()),
+                "Tips depend on target shell.".to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn golangci_reads_the_json_line_before_the_text_summary() {
+    let report = r#"{"Issues":[{"FromLinter":"errcheck","Text":"Error return value is not checked","Severity":"","Pos":{"Filename":"main.go","Line":7,"Column":0}}],"Report":{}}
+level=info msg="1 issue found"
+"#;
+    assert_eq!(
+        parse(LinterKind::GolangciLint, report),
+        vec![(
+            6,
+            0,
+            6,
+            0,
+            Some(2),
+            Some("errcheck".to_string()),
+            "Error return value is not checked".to_string()
+        )]
+    );
+    assert!(parse(LinterKind::GolangciLint, "").is_empty());
+}
+
+#[test]
+fn malformed_output_is_reported() {
+    assert!(LinterKind::Ruff.parse("[{\"code\":").is_err());
+    assert!(LinterKind::Clippy.parse("{not json}").is_err());
+}
+
+#[tokio::test]
+async fn unsupported_languages_are_validation_errors() {
+    let error = lint_snippet("cobol", "DISPLAY 'HI'.").await.unwrap_err();
+    assert_eq!(error.code(), "validation");
+}
@@ -0,0 +1,194 @@
+//! 完整性检查与孤立记录修复的集成测试
+mod common;
+
+use common::{memory_pool, snippet, todo};
+use snippets_hub_lib::database::Database;
+use snippets_hub_lib::models::CreateTodoTagRequest;
+use snippets_hub_lib::repository::{FolderRepository, SnippetRepository, TodoRepository};
+use sqlx::SqlitePool;
+
+/// 全文索引中匹配关键字的行数
+async fn index_hits(pool: &SqlitePool, keyword: &str) -> i64 {
+    sqlx::query_scalar("SELECT COUNT(*) FROM snippets_fts WHERE snippets_fts MATCH ?")
+        .bind(keyword)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn deleting_a_todo_cascades_to_its_tag_relations() {
+    let db = Database::from_pool(memory_pool().await).await.unwrap();
+
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "backend".to_string(),
+            color_id: "blue".to_string(),
+        })
+        .await
+        .unwrap();
+    let mut req = todo("Tagged");
+    req.tags = Some(vec![tag.id.clone()]);
+    let tagged = db.create_todo(req).await.unwrap();
+
+    db.delete_todo(tagged.id).await.unwrap();
+
+    let report = db.check_integrity(false).await.unwrap();
+    assert!(report.integrity_errors.is_empty());
+    assert!(report.foreign_key_violations.is_empty());
+    assert!(report.orphans.is_empty());
+}
+
+#[tokio::test]
+async fn orphans_left_without_foreign_keys_are_reported_and_repaired() {
+    let pool = memory_pool().await;
+    let db = Database::from_pool(pool.clone()).await.unwrap();
+
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "legacy".to_string(),
+            color_id: "gray".to_string(),
+        })
+        .await
+        .unwrap();
+    let mut req = todo("Tagged");
+    req.tags = Some(vec![tag.id.clone()]);
+    let tagged = db.create_todo(req).await.unwrap();
+    let folder = db.create_folder("Old".to_string(), None).await.unwrap();
+    let mut req = snippet("Filed", "text", "filed");
+    req.folder_id = Some(folder.id.clone());
+    let filed = db.create_snippet(req).await.unwrap();
+
+    // 模拟启用外键之前的数据库：直接删除父记录，不触发级联
+    sqlx::query("PRAGMA foreign_keys = OFF")
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM todos WHERE id = ?")
+        .bind(&tagged.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("DELETE FROM folders WHERE id = ?")
+        .bind(&folder.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let report = db.check_integrity(false).await.unwrap();
+    assert!(!report.repaired);
+    assert_eq!(report.foreign_key_violations.len(), 1);
+    assert_eq!(report.foreign_key_violations[0].table, "todo_tag_relations");
+    let mut found: Vec<(String, String, i64, String)> = report
+        .orphans
+        .iter()
+        .map(|o| (o.table.clone(), o.column.clone(), o.count, o.repair.clone()))
+        .collect();
+    found.sort();
+    assert_eq!(
+        found,
+        vec![
+            (
+                "snippets".to_string(),
+                "folder_id".to_string(),
+                1,
+                "clear_reference".to_string()
+            ),
+            (
+                "todo_tag_relations".to_string(),
+                "todo_id".to_string(),
+                1,
+                "delete".to_string()
+            ),
+        ]
+    );
+
+    let repaired = db.check_integrity(true).await.unwrap();
+    assert!(repaired.repaired);
+    assert_eq!(repaired.orphans.len(), 2);
+
+    let after = db.check_integrity(false).await.unwrap();
+    assert!(after.foreign_key_violations.is_empty());
+    assert!(after.orphans.is_empty());
+    assert_eq!(
+        db.get_snippet(&filed.id).await.unwrap().unwrap().folder_id,
+        None
+    );
+}
+
+#[tokio::test]
+async fn stats_report_row_counts_for_every_table() {
+    let db = Database::from_pool(memory_pool().await).await.unwrap();
+    db.create_snippet(snippet("One", "text", "one"))
+        .await
+        .unwrap();
+    db.create_snippet(snippet("Two", "text", "two"))
+        .await
+        .unwrap();
+
+    db.vacuum().await.unwrap();
+    db.analyze().await.unwrap();
+    let stats = db.database_stats().await.unwrap();
+    let rows = |name: &str| {
+        stats
+            .tables
+            .iter()
+            .find(|table| table.name == name)
+            .map(|table| table.row_count)
+    };
+    assert_eq!(rows("snippets"), Some(2));
+    assert_eq!(rows("snippets_fts"), Some(2));
+    assert_eq!(rows("todos"), Some(0));
+    assert_eq!(rows("activity_log"), Some(2));
+    assert_eq!(rows("undo_journal"), Some(2));
+    assert!(stats.size_bytes > 0);
+}
+
+#[tokio::test]
+async fn search_index_drift_is_detected_and_repaired() {
+    let pool = memory_pool().await;
+    let db = Database::from_pool(pool.clone()).await.unwrap();
+
+    let dropped = db
+        .create_snippet(snippet("Dropped", "rust", "unindexed"))
+        .await
+        .unwrap();
+    let edited = db
+        .create_snippet(snippet("Edited", "rust", "original"))
+        .await
+        .unwrap();
+    db.optimize_fts().await.unwrap();
+
+    // 绕过触发器直接修改索引，模拟索引与表不同步
+    sqlx::query("DELETE FROM snippets_fts WHERE id = ?")
+        .bind(&dropped.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query("UPDATE snippets_fts SET code = 'tampered' WHERE id = ?")
+        .bind(&edited.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    sqlx::query(
+        "INSERT INTO snippets_fts(id, title, description, code, tags)
+         VALUES ('ghost', 'Ghost', '', '', '[]')",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let report = db.check_fts_sync(false).await.unwrap();
+    assert_eq!((report.missing, report.stale, report.outdated), (1, 1, 1));
+    assert!(!report.repaired);
+    assert_eq!(index_hits(&pool, "unindexed").await, 0);
+
+    assert!(db.check_fts_sync(true).await.unwrap().repaired);
+    let after = db.check_fts_sync(false).await.unwrap();
+    assert_eq!((after.missing, after.stale, after.outdated), (0, 0, 0));
+    assert_eq!(index_hits(&pool, "unindexed").await, 1);
+    assert_eq!(index_hits(&pool, "original").await, 1);
+
+    db.rebuild_fts().await.unwrap();
+    assert_eq!(index_hits(&pool, "original").await, 1);
+}
@@ -0,0 +1,139 @@
+//! Markdown 代码块导入与目录树导出的测试
+mod common;
+
+use common::memory_db;
+use snippets_hub_lib::formats::markdown::{collect, select, write_tree};
+use snippets_hub_lib::models::ImportAction;
+use std::fs;
+use std::path::PathBuf;
+
+/// 测试专用的临时目录，测试结束时删除
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("snippetshub_test_{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+fn files(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+    entries
+        .iter()
+        .map(|(path, content)| (path.to_string(), content.to_string()))
+        .collect()
+}
+
+#[test]
+fn re_export_rewrites_changed_files_and_removes_stale_ones() {
+    let temp = TempDir::new();
+    let root = temp.0.join("docs");
+
+    let first = files(&[("a.md", "A"), ("rust/b.md", "B")]);
+    let report = write_tree(&root, &first).unwrap();
+    assert_eq!(report.written, vec!["a.md", "rust/b.md"]);
+    fs::write(root.join("notes.md"), "hand written").unwrap();
+
+    let second = files(&[("a.md", "A2")]);
+    let report = write_tree(&root, &second).unwrap();
+    assert_eq!(report.written, vec!["a.md"]);
+    assert_eq!(report.removed, vec!["rust/b.md"]);
+    assert!(!root.join("rust").exists());
+    assert!(root.join("notes.md").exists());
+
+    let report = write_tree(&root, &second).unwrap();
+    assert!(report.written.is_empty());
+    assert_eq!(report.unchanged, 1);
+}
+
+#[test]
+fn manifest_entries_outside_the_export_directory_are_ignored() {
+    let temp = TempDir::new();
+    let root = temp.0.join("docs");
+    fs::create_dir_all(&root).unwrap();
+    let victim = temp.0.join("victim.txt");
+    fs::write(&victim, "keep me").unwrap();
+    fs::write(root.join("stale.md"), "old").unwrap();
+
+    let manifest = serde_json::json!([
+        "../victim.txt",
+        victim.to_string_lossy(),
+        "sub/../../victim.txt",
+        "",
+        "stale.md",
+    ]);
+    fs::write(root.join(".snippetshub-export.json"), manifest.to_string()).unwrap();
+
+    let report = write_tree(&root, &files(&[("a.md", "A")])).unwrap();
+    assert_eq!(report.removed, vec!["stale.md"]);
+    assert!(victim.exists());
+    assert!(!root.join("stale.md").exists());
+
+    let manifest = fs::read_to_string(root.join(".snippetshub-export.json")).unwrap();
+    let recorded: Vec<String> = serde_json::from_str(&manifest).unwrap();
+    assert_eq!(recorded, vec!["a.md"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn manifest_entries_through_symlinks_are_ignored() {
+    let temp = TempDir::new();
+    let root = temp.0.join("docs");
+    let outside = temp.0.join("outside");
+    fs::create_dir_all(&root).unwrap();
+    fs::create_dir_all(&outside).unwrap();
+    fs::write(outside.join("secret.md"), "keep me").unwrap();
+    std::os::unix::fs::symlink(&outside, root.join("link")).unwrap();
+    fs::write(
+        root.join(".snippetshub-export.json"),
+        r#"["link/secret.md"]"#,
+    )
+    .unwrap();
+
+    let report = write_tree(&root, &[]).unwrap();
+    assert!(report.removed.is_empty());
+    assert!(outside.join("secret.md").exists());
+}
+
+#[tokio::test]
+async fn importing_a_selection_reports_preview_indices() {
+    let temp = TempDir::new();
+    fs::write(
+        temp.0.join("notes.md"),
+        "# Build\n```sh\ncargo build\n```\n# Test\n```sh\ncargo test\n```\n# Lint\n```sh\ncargo clippy\n```\n",
+    )
+    .unwrap();
+
+    let (blocks, errors) = collect(&temp.0).unwrap();
+    assert_eq!(blocks.len(), 3);
+    let items = select(blocks, errors, Some(&[2, 1]));
+    assert_eq!(
+        items.iter().map(|(index, _)| *index).collect::<Vec<_>>(),
+        vec![1, 2]
+    );
+
+    let db = memory_db().await;
+    let drafts = items
+        .into_iter()
+        .map(|(index, item)| (index, item.unwrap()))
+        .collect();
+    let results = db.import_drafts(drafts, None, false).await.unwrap();
+    let imported: Vec<(usize, &str, ImportAction)> = results
+        .iter()
+        .map(|r| (r.index, r.title.as_deref().unwrap_or_default(), r.action))
+        .collect();
+    assert_eq!(
+        imported,
+        vec![
+            (1, "Test", ImportAction::Create),
+            (2, "Lint", ImportAction::Create)
+        ]
+    );
+}
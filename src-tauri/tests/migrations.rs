@@ -0,0 +1,67 @@
+//! 数据库结构迁移记录与校验的测试
+mod common;
+
+use common::memory_pool;
+use snippets_hub_lib::database::{Database, SCHEMA_VERSION};
+
+async fn applied_versions(pool: &sqlx::SqlitePool) -> Vec<i64> {
+    sqlx::query_scalar("SELECT version FROM schema_migrations ORDER BY version")
+        .fetch_all(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn migrations_run_once_and_are_recorded() {
+    let pool = memory_pool().await;
+    Database::from_pool(pool.clone()).await.unwrap();
+    let expected: Vec<i64> = (1..=SCHEMA_VERSION).collect();
+    assert_eq!(applied_versions(&pool).await, expected);
+
+    // 再次打开时校验通过，不会重复执行
+    Database::from_pool(pool.clone()).await.unwrap();
+    assert_eq!(applied_versions(&pool).await, expected);
+}
+
+#[tokio::test]
+async fn changed_migrations_are_rejected() {
+    let pool = memory_pool().await;
+    Database::from_pool(pool.clone()).await.unwrap();
+    sqlx::query("UPDATE schema_migrations SET checksum = 'edited' WHERE version = 1")
+        .execute(&pool)
+        .await
+        .unwrap();
+
+    let error = Database::from_pool(pool.clone()).await.err().unwrap();
+    assert_eq!(error.code(), "internal");
+    let message = error.to_string();
+    assert!(
+        message.contains("Migration 1 (initial_schema) has changed"),
+        "{}",
+        message
+    );
+}
+
+#[tokio::test]
+async fn databases_from_newer_versions_are_rejected() {
+    let pool = memory_pool().await;
+    Database::from_pool(pool.clone()).await.unwrap();
+    sqlx::query(
+        "INSERT INTO schema_migrations (version, name, checksum, applied_at)
+         VALUES (?, 'from_the_future', '', 0)",
+    )
+    .bind(SCHEMA_VERSION + 1)
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let error = Database::from_pool(pool.clone()).await.err().unwrap();
+    assert_eq!(error.code(), "unsupported_version");
+    assert!(
+        error
+            .to_string()
+            .contains(&format!("version {} is newer", SCHEMA_VERSION + 1)),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,120 @@
+//! 工作区、项目与 Git 仓库记录的集成测试
+mod common;
+
+use common::{memory_db, snippet};
+use snippets_hub_lib::models::{GitRemote, GitRepository, Project, Workspace};
+use snippets_hub_lib::repository::{GitRepoRepository, ProjectRepository, SnippetRepository};
+use std::collections::HashMap;
+
+fn workspace(id: &str) -> Workspace {
+    Workspace {
+        id: id.to_string(),
+        name: "Default".to_string(),
+        description: String::new(),
+        color: "#3b82f6".to_string(),
+        is_default: true,
+        settings: HashMap::new(),
+        created_at: "2026-02-01T00:00:00Z".to_string(),
+        updated_at: "2026-02-01T00:00:00Z".to_string(),
+    }
+}
+
+fn project(id: &str, workspace_id: &str) -> Project {
+    Project {
+        id: id.to_string(),
+        workspace_id: workspace_id.to_string(),
+        name: "SnippetsHub".to_string(),
+        description: "Desktop app".to_string(),
+        project_type: "tauri".to_string(),
+        template: None,
+        parent_id: None,
+        path: "/projects/snippets-hub".to_string(),
+        color: "#22c55e".to_string(),
+        icon: "folder".to_string(),
+        tags: vec!["rust".to_string(), "vue".to_string()],
+        settings: HashMap::new(),
+        metadata: HashMap::new(),
+        is_folder: false,
+        created_at: "2026-02-01T00:00:00Z".to_string(),
+        updated_at: "2026-02-01T00:00:00Z".to_string(),
+    }
+}
+
+#[tokio::test]
+async fn workspace_and_project_crud() {
+    let db = memory_db().await;
+
+    db.create_workspace(workspace("ws-1")).await.unwrap();
+    assert_eq!(db.get_workspaces().await.unwrap().len(), 1);
+    assert_eq!(db.get_workspace("ws-1").await.unwrap().name, "Default");
+
+    db.create_project(project("p-1", "ws-1")).await.unwrap();
+    let loaded = db.get_project("p-1").await.unwrap();
+    assert_eq!(loaded.workspace_id, "ws-1");
+    assert_eq!(loaded.tags, vec!["rust".to_string(), "vue".to_string()]);
+    assert_eq!(db.get_projects().await.unwrap().len(), 1);
+
+    let mut req = snippet("Tauri command", "rust", "#[tauri::command]");
+    req.project_id = Some("p-1".to_string());
+    db.create_snippet(req).await.unwrap();
+    db.create_snippet(snippet("Other", "text", "other"))
+        .await
+        .unwrap();
+    let snippets = db.get_snippets_by_project("p-1".to_string()).await.unwrap();
+    assert_eq!(snippets.len(), 1);
+    assert_eq!(snippets[0].title, "Tauri command");
+
+    db.delete_project("p-1".to_string()).await.unwrap();
+    assert_eq!(db.get_project("p-1").await.unwrap_err().code(), "not_found");
+
+    db.delete_workspace("ws-1".to_string()).await.unwrap();
+    assert_eq!(
+        db.get_workspace("ws-1").await.unwrap_err().code(),
+        "not_found"
+    );
+}
+
+#[tokio::test]
+async fn duplicate_project_id_is_a_conflict() {
+    let db = memory_db().await;
+
+    db.create_project(project("p-1", "ws-1")).await.unwrap();
+    let error = db.create_project(project("p-1", "ws-1")).await.unwrap_err();
+    assert_eq!(error.code(), "conflict");
+}
+
+#[tokio::test]
+async fn git_repository_crud() {
+    let db = memory_db().await;
+
+    let repository = GitRepository {
+        id: "repo-1".to_string(),
+        name: "snippets-hub".to_string(),
+        description: String::new(),
+        path: "/projects/snippets-hub".to_string(),
+        is_default: true,
+        remotes: vec![GitRemote {
+            name: "origin".to_string(),
+            url: "https://example.com/snippets-hub.git".to_string(),
+            fetch_url: None,
+            push_url: None,
+        }],
+        created_at: "2026-02-01T00:00:00Z".to_string(),
+        updated_at: "2026-02-01T00:00:00Z".to_string(),
+    };
+    db.create_git_repository(repository).await.unwrap();
+
+    let loaded = db.get_git_repository("repo-1").await.unwrap();
+    assert_eq!(loaded.remotes.len(), 1);
+    assert_eq!(loaded.remotes[0].name, "origin");
+    assert_eq!(db.get_git_repositories().await.unwrap().len(), 1);
+
+    db.delete_git_repository("repo-1".to_string())
+        .await
+        .unwrap();
+    assert_eq!(
+        db.get_git_repository("repo-1").await.unwrap_err().code(),
+        "not_found"
+    );
+    assert!(db.get_git_repositories().await.unwrap().is_empty());
+}
@@ -0,0 +1,176 @@
+//! 代码片段与文件夹仓储的集成测试
+mod common;
+
+use common::{code_snippet, memory_db, memory_pool, snippet, snippet_update};
+use snippets_hub_lib::database::Database;
+use snippets_hub_lib::models::{ImportAction, ImportConflictStrategy, SearchQuery};
+use snippets_hub_lib::repository::{FolderRepository, SnippetRepository};
+
+fn search(keyword: &str) -> SearchQuery {
+    SearchQuery {
+        keyword: keyword.to_string(),
+        tags: None,
+        language: None,
+    }
+}
+
+#[tokio::test]
+async fn snippet_crud_round_trip() {
+    let db = memory_db().await;
+
+    let mut req = snippet("Hello", "rust", "fn main() {}");
+    req.tags = vec!["demo".to_string()];
+    let created = db.create_snippet(req).await.unwrap();
+    assert!(!created.is_favorite);
+    assert_eq!(created.usage_count, 0);
+
+    let loaded = db.get_snippet(&created.id).await.unwrap().unwrap();
+    assert_eq!(loaded.title, "Hello");
+    assert_eq!(loaded.tags, vec!["demo".to_string()]);
+
+    let mut update = snippet_update(&created.id);
+    update.code = Some("fn main() { println!(\"hi\"); }".to_string());
+    update.is_favorite = Some(true);
+    let updated = db.update_snippet(update).await.unwrap();
+    assert_eq!(updated.title, "Hello");
+    assert!(updated.is_favorite);
+    assert!(updated.code.contains("println"));
+
+    assert_eq!(db.get_all_snippets().await.unwrap().len(), 1);
+    db.delete_snippet(&created.id).await.unwrap();
+    assert!(db.get_snippet(&created.id).await.unwrap().is_none());
+    assert!(db.get_all_snippets().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn updating_missing_snippet_is_not_found() {
+    let db = memory_db().await;
+
+    let error = db
+        .update_snippet(snippet_update("missing"))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), "not_found");
+}
+
+#[tokio::test]
+async fn search_matches_keyword_language_and_tags() {
+    let db = memory_db().await;
+
+    let mut sorting = snippet("Quick sort", "python", "def quick_sort(items): ...");
+    sorting.tags = vec!["algorithm".to_string()];
+    db.create_snippet(sorting).await.unwrap();
+    db.create_snippet(snippet("Fetch wrapper", "javascript", "await fetch(url)"))
+        .await
+        .unwrap();
+    db.create_snippet(snippet("Sort keys", "javascript", "Object.keys(o).sort()"))
+        .await
+        .unwrap();
+
+    let titles = |snippets: Vec<snippets_hub_lib::models::CodeSnippet>| {
+        let mut titles: Vec<String> = snippets.into_iter().map(|s| s.title).collect();
+        titles.sort();
+        titles
+    };
+
+    assert_eq!(
+        titles(db.search_snippets(search("sort")).await.unwrap()),
+        vec!["Quick sort", "Sort keys"]
+    );
+    assert_eq!(
+        titles(db.search_snippets(search("fetch")).await.unwrap()),
+        vec!["Fetch wrapper"]
+    );
+
+    let mut by_language = search("sort");
+    by_language.language = Some("javascript".to_string());
+    assert_eq!(
+        titles(db.search_snippets(by_language).await.unwrap()),
+        vec!["Sort keys"]
+    );
+
+    let mut by_tag = search("");
+    by_tag.tags = Some(vec!["algorithm".to_string()]);
+    assert_eq!(
+        titles(db.search_snippets(by_tag).await.unwrap()),
+        vec!["Quick sort"]
+    );
+    assert_eq!(
+        titles(db.get_snippets_by_tag("algorithm").await.unwrap()),
+        vec!["Quick sort"]
+    );
+}
+
+#[tokio::test]
+async fn folder_queries_include_subfolders() {
+    let db = memory_db().await;
+
+    let parent = db.create_folder("Backend".to_string(), None).await.unwrap();
+    let child = db
+        .create_folder("Rust".to_string(), Some(parent.id.clone()))
+        .await
+        .unwrap();
+    assert_eq!(db.get_all_folders().await.unwrap().len(), 2);
+
+    let mut top = snippet("Top", "text", "top");
+    top.folder_id = Some(parent.id.clone());
+    db.create_snippet(top).await.unwrap();
+    let mut nested = snippet("Nested", "rust", "nested");
+    nested.folder_id = Some(child.id.clone());
+    let nested = db.create_snippet(nested).await.unwrap();
+    db.create_snippet(snippet("Loose", "text", "loose"))
+        .await
+        .unwrap();
+
+    assert_eq!(
+        db.get_snippets_by_folder(&parent.id).await.unwrap().len(),
+        2
+    );
+    assert_eq!(db.get_snippets_by_folder(&child.id).await.unwrap().len(), 1);
+
+    db.delete_folder(&child.id).await.unwrap();
+    assert_eq!(db.get_all_folders().await.unwrap().len(), 1);
+    let orphan = db.get_snippet(&nested.id).await.unwrap().unwrap();
+    assert_eq!(orphan.folder_id, None);
+}
+
+#[tokio::test]
+async fn import_clears_unknown_folder_and_project() {
+    let db = memory_db().await;
+
+    let mut item = code_snippet("imported", "Imported");
+    item.folder_id = Some("missing-folder".to_string());
+    item.project_id = Some("missing-project".to_string());
+    let results = db
+        .import_snippets(vec![(0, item)], ImportConflictStrategy::Skip, false)
+        .await
+        .unwrap();
+    assert_eq!(results[0].action, ImportAction::Create);
+    assert!(results[0].message.as_deref().unwrap().contains("Project"));
+
+    let imported = db.get_snippet("imported").await.unwrap().unwrap();
+    assert_eq!(imported.folder_id, None);
+    assert_eq!(imported.project_id, None);
+}
+
+#[tokio::test]
+async fn failed_import_is_rolled_back() {
+    let pool = memory_pool().await;
+    let db = Database::from_pool(pool.clone()).await.unwrap();
+    sqlx::query(
+        "CREATE TRIGGER reject_import BEFORE INSERT ON snippets WHEN NEW.title = 'Broken'
+         BEGIN SELECT RAISE(ABORT, 'rejected'); END",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let items = vec![
+        (0, code_snippet("first", "First")),
+        (1, code_snippet("second", "Broken")),
+    ];
+    db.import_snippets(items, ImportConflictStrategy::Skip, false)
+        .await
+        .unwrap_err();
+    assert!(db.get_all_snippets().await.unwrap().is_empty());
+}
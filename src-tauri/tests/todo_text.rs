@@ -0,0 +1,212 @@
+//! Markdown 任务列表与 todo.txt 的读写测试
+mod common;
+
+use common::{memory_db, memory_pool, todo, todo_update};
+use snippets_hub_lib::database::Database;
+use snippets_hub_lib::formats::{checklist, todotxt, EntryError};
+use snippets_hub_lib::models::{CreateTodoTagRequest, Todo, TodoDraft};
+use snippets_hub_lib::repository::TodoRepository;
+use std::collections::{HashMap, HashSet};
+
+/// 一个带标签、优先级和截止日期的父任务，下挂两级子任务，其中最深的一级已完成
+async fn sample_todos(db: &Database) -> (Vec<Todo>, HashMap<String, String>) {
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "next release".to_string(),
+            color_id: "blue".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let mut request = todo("Ship 2.0");
+    request.priority = Some("high".to_string());
+    request.due_date = Some("2026-03-01".to_string());
+    request.tags = Some(vec![tag.id.clone()]);
+    let parent = db.create_todo(request).await.unwrap();
+
+    let mut request = todo("Write changelog");
+    request.parent_id = Some(parent.id.clone());
+    let child = db.create_todo(request).await.unwrap();
+
+    let mut request = todo("Proofread");
+    request.parent_id = Some(child.id.clone());
+    request.priority = Some("low".to_string());
+    let grandchild = db.create_todo(request).await.unwrap();
+    let mut update = todo_update(&grandchild.id);
+    update.completed = Some(true);
+    db.update_todo(update).await.unwrap();
+
+    let todos = db.get_todos_for_export(false).await.unwrap();
+    let tag_names = HashMap::from([(tag.id, tag.name)]);
+    (todos, tag_names)
+}
+
+fn parsed(items: Vec<Result<TodoDraft, EntryError>>) -> Vec<TodoDraft> {
+    items
+        .into_iter()
+        .map(|item| item.expect("parse task"))
+        .collect()
+}
+
+#[tokio::test]
+async fn checklist_round_trip_keeps_nesting_and_fields() {
+    let db = memory_db().await;
+    let (todos, tag_names) = sample_todos(&db).await;
+
+    let output = checklist::render(&todos, &tag_names);
+    assert_eq!(
+        output,
+        "- [ ] Ship 2.0 !high #next-release due:2026-03-01\n  \
+         - [ ] Write changelog\n    \
+         - [x] Proofread !low\n"
+    );
+
+    let drafts = parsed(checklist::parse(&output));
+    assert_eq!(drafts.len(), 3);
+    let (parent, child, grandchild) = (&drafts[0], &drafts[1], &drafts[2]);
+
+    assert_eq!(parent.title, "Ship 2.0");
+    assert_eq!(parent.priority.as_deref(), Some("high"));
+    assert_eq!(parent.due_date.as_deref(), Some("2026-03-01"));
+    assert_eq!(parent.tag_names, vec!["next-release"]);
+    assert_eq!(parent.parent_id, None);
+    assert!(!parent.completed);
+
+    assert_eq!(child.title, "Write changelog");
+    assert_eq!(child.parent_id, parent.id);
+    assert_eq!(grandchild.title, "Proofread");
+    assert_eq!(grandchild.parent_id, child.id);
+    assert!(grandchild.completed);
+    assert_eq!(grandchild.status, "completed");
+    assert!(drafts.iter().all(|d| d.notes.is_empty()));
+}
+
+#[test]
+fn checklist_skips_non_tasks_and_reports_malformed_items() {
+    let content = "\
+# Plan
+- plain bullet
+- [ ]
+-[ ] missing space
+- [y] unknown mark
+```
+- [ ] inside a fence
+```
+1. [x] Done #42 due:2026-13-40
+\t- [ ] Nested under the failed item
+";
+    let items = checklist::parse(content);
+    assert_eq!(items.len(), 3);
+
+    let error = items[0].as_ref().unwrap_err();
+    assert_eq!(error.message, "Line 3: task has no title");
+
+    let done = items[1].as_ref().unwrap();
+    assert_eq!(done.title, "Done #42");
+    assert!(done.completed);
+    assert_eq!(done.due_date, None);
+    assert_eq!(done.notes, vec!["Line 9: invalid due date 2026-13-40"]);
+
+    let nested = items[2].as_ref().unwrap();
+    assert_eq!(nested.title, "Nested under the failed item");
+    assert_eq!(nested.parent_id, done.id);
+}
+
+#[tokio::test]
+async fn todotxt_round_trip_keeps_fields_and_flattens_subtasks() {
+    let db = memory_db().await;
+    let (mut todos, tag_names) = sample_todos(&db).await;
+    todos[0].project_id = Some("p1".to_string());
+    let project_names = HashMap::from([("p1".to_string(), "Snippets Hub".to_string())]);
+
+    let output = todotxt::render(&todos, &tag_names, &project_names);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("(A) "), "{}", lines[0]);
+    assert!(
+        lines[0].ends_with(" Ship 2.0 +Snippets-Hub @next-release due:2026-03-01"),
+        "{}",
+        lines[0]
+    );
+    assert!(lines[2].starts_with("x "), "{}", lines[2]);
+    assert!(lines[2].ends_with(" Proofread pri:C"), "{}", lines[2]);
+
+    let drafts = parsed(todotxt::parse(&output));
+    assert_eq!(drafts.len(), 3);
+    let (parent, child, grandchild) = (&drafts[0], &drafts[1], &drafts[2]);
+
+    assert_eq!(parent.title, "Ship 2.0");
+    assert_eq!(parent.priority.as_deref(), Some("high"));
+    assert_eq!(parent.project_name.as_deref(), Some("Snippets-Hub"));
+    assert_eq!(parent.tag_names, vec!["next-release"]);
+    assert_eq!(parent.due_date.as_deref(), Some("2026-03-01"));
+    assert!(parent.created_at.is_some());
+
+    assert_eq!(child.title, "Write changelog");
+    assert_eq!(child.parent_id, None);
+
+    assert_eq!(grandchild.title, "Proofread");
+    assert!(grandchild.completed);
+    assert_eq!(grandchild.priority.as_deref(), Some("low"));
+    assert!(grandchild.updated_at.is_some());
+    assert!(drafts.iter().all(|d| d.notes.is_empty()));
+}
+
+#[test]
+fn todotxt_reports_malformed_lines() {
+    let content = "\
+(A) 2026-02-01
+
+x 2026-02-10 +Project @context
+(a) lowercase priority is text +One +Two due:tomorrow
+";
+    let items = todotxt::parse(content);
+    assert_eq!(items.len(), 3);
+
+    assert_eq!(
+        items[0].as_ref().unwrap_err().message,
+        "Line 1: task has no description"
+    );
+    assert_eq!(
+        items[1].as_ref().unwrap_err().message,
+        "Line 3: task has no description"
+    );
+
+    let task = items[2].as_ref().unwrap();
+    assert_eq!(task.title, "(a) lowercase priority is text");
+    assert_eq!(task.priority, None);
+    assert_eq!(task.project_name.as_deref(), Some("One"));
+    assert_eq!(task.due_date, None);
+    assert_eq!(
+        task.notes,
+        vec![
+            "Additional project +Two not preserved",
+            "Line 4: invalid due date tomorrow",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn a_failed_import_writes_nothing() {
+    let pool = memory_pool().await;
+    let db = Database::from_pool(pool.clone()).await.unwrap();
+    sqlx::query(
+        "CREATE TRIGGER reject_boom BEFORE INSERT ON todos WHEN new.title = 'Boom'
+         BEGIN SELECT RAISE(ABORT, 'boom'); END",
+    )
+    .execute(&pool)
+    .await
+    .unwrap();
+
+    let drafts = parsed(checklist::parse("- [ ] First\n- [ ] Boom\n- [ ] Last\n"));
+    let result = db
+        .import_todos(
+            drafts.into_iter().enumerate().collect(),
+            &HashSet::new(),
+            false,
+        )
+        .await;
+
+    assert!(result.is_err());
+    assert!(db.get_todos().await.unwrap().is_empty());
+}
@@ -0,0 +1,380 @@
+//! TODO 与 TODO 标签仓储的集成测试
+mod common;
+
+use common::{memory_db, todo, todo_query, todo_update};
+use snippets_hub_lib::database::build_todo_tree;
+use snippets_hub_lib::models::{
+    BatchTodoOperation, CreateTodoTagRequest, TodoSearchQuery, UpdateTodoTagRequest,
+};
+use snippets_hub_lib::repository::TodoRepository;
+
+fn batch(operation: &str, todo_ids: &[&str]) -> BatchTodoOperation {
+    BatchTodoOperation {
+        todo_ids: todo_ids.iter().map(|id| id.to_string()).collect(),
+        operation: operation.to_string(),
+        updates: None,
+    }
+}
+
+#[tokio::test]
+async fn todo_crud_with_subtasks_and_tags() {
+    let db = memory_db().await;
+
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "backend".to_string(),
+            color_id: "blue".to_string(),
+        })
+        .await
+        .unwrap();
+    assert_eq!(tag.color, "#3b82f6");
+
+    let mut req = todo("Ship release");
+    req.priority = Some("high".to_string());
+    req.tags = Some(vec![tag.id.clone()]);
+    let parent = db.create_todo(req).await.unwrap();
+    assert_eq!(parent.status, "todo");
+    assert_eq!(parent.tags, vec![tag.id.clone()]);
+
+    let mut child = todo("Write changelog");
+    child.parent_id = Some(parent.id.clone());
+    let child = db.create_todo(child).await.unwrap();
+
+    let loaded = db.get_todo(&parent.id).await.unwrap();
+    assert_eq!(loaded.subtasks.len(), 1);
+    assert_eq!(loaded.subtasks[0].id, child.id);
+
+    let mut update = todo_update(&parent.id);
+    update.title = Some("Ship 2.0 release".to_string());
+    update.progress = Some(50);
+    update.tags = Some(Vec::new());
+    let updated = db.update_todo(update).await.unwrap();
+    assert_eq!(updated.title, "Ship 2.0 release");
+    assert_eq!(updated.progress, 50);
+    assert!(updated.tags.is_empty());
+
+    db.delete_todo(parent.id.clone()).await.unwrap();
+    assert_eq!(
+        db.get_todo(&parent.id).await.unwrap_err().code(),
+        "not_found"
+    );
+    assert_eq!(
+        db.get_todo(&child.id).await.unwrap_err().code(),
+        "not_found"
+    );
+    assert!(db.get_todos().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn deleting_a_todo_removes_every_level_of_subtasks() {
+    let db = memory_db().await;
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "release".to_string(),
+            color_id: "blue".to_string(),
+        })
+        .await
+        .unwrap();
+    let root = db.create_todo(todo("Release")).await.unwrap();
+    let mut req = todo("Docs");
+    req.parent_id = Some(root.id.clone());
+    let child = db.create_todo(req).await.unwrap();
+    let mut req = todo("Changelog");
+    req.parent_id = Some(child.id.clone());
+    req.tags = Some(vec![tag.id.clone()]);
+    let grandchild = db.create_todo(req).await.unwrap();
+    let mut events = db.subscribe();
+
+    db.delete_todo(root.id.clone()).await.unwrap();
+
+    assert!(db.get_todos().await.unwrap().is_empty());
+    assert_eq!(
+        db.get_todo(&grandchild.id).await.unwrap_err().code(),
+        "not_found"
+    );
+    let event = events.try_recv().unwrap();
+    assert_eq!(event.name(), "todo:deleted");
+    let mut ids = event.ids;
+    ids.sort();
+    let mut expected = vec![root.id, child.id, grandchild.id];
+    expected.sort();
+    assert_eq!(ids, expected);
+
+    // 孙任务的标签关联随之级联删除，不留下孤立记录
+    assert!(db.check_integrity(false).await.unwrap().orphans.is_empty());
+}
+
+#[tokio::test]
+async fn todo_tags_can_be_renamed_and_deleted() {
+    let db = memory_db().await;
+
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "ops".to_string(),
+            color_id: "red".to_string(),
+        })
+        .await
+        .unwrap();
+    let renamed = db
+        .update_todo_tag(UpdateTodoTagRequest {
+            id: tag.id.clone(),
+            name: Some("infra".to_string()),
+            color_id: Some("green".to_string()),
+        })
+        .await
+        .unwrap();
+    assert_eq!(renamed.name, "infra");
+    assert_eq!(renamed.color_id, "green");
+    assert_eq!(db.get_todo_tags_list().await.unwrap().len(), 1);
+
+    db.delete_todo_tag(tag.id.clone()).await.unwrap();
+    assert_eq!(
+        db.get_todo_tag(&tag.id).await.unwrap_err().code(),
+        "not_found"
+    );
+
+    let error = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "bad".to_string(),
+            color_id: "plaid".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), "validation");
+}
+
+#[tokio::test]
+async fn search_filters_by_keyword_status_priority_and_tag() {
+    let db = memory_db().await;
+
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "docs".to_string(),
+            color_id: "gray".to_string(),
+        })
+        .await
+        .unwrap();
+
+    let mut docs = todo("Update README");
+    docs.tags = Some(vec![tag.id.clone()]);
+    docs.priority = Some("low".to_string());
+    db.create_todo(docs).await.unwrap();
+    let mut urgent = todo("Fix login crash");
+    urgent.priority = Some("high".to_string());
+    urgent.status = Some("in_progress".to_string());
+    db.create_todo(urgent).await.unwrap();
+    db.create_todo(todo("Update dependencies")).await.unwrap();
+
+    let titles = |todos: Vec<snippets_hub_lib::models::Todo>| {
+        let mut titles: Vec<String> = todos.into_iter().map(|t| t.title).collect();
+        titles.sort();
+        titles
+    };
+
+    let mut query = todo_query();
+    query.keyword = Some("update".to_string());
+    assert_eq!(
+        titles(db.search_todos(query).await.unwrap()),
+        vec!["Update README", "Update dependencies"]
+    );
+
+    let mut query = todo_query();
+    query.status = Some("in_progress".to_string());
+    assert_eq!(
+        titles(db.search_todos(query).await.unwrap()),
+        vec!["Fix login crash"]
+    );
+
+    let mut query = todo_query();
+    query.priority = Some("low".to_string());
+    query.tags = Some(vec![tag.id.clone()]);
+    assert_eq!(
+        titles(db.search_todos(query).await.unwrap()),
+        vec!["Update README"]
+    );
+
+    assert_eq!(db.search_todos(todo_query()).await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn search_binds_filter_values_and_supports_every_field() {
+    let db = memory_db().await;
+    let parent = db.create_todo(todo("Release")).await.unwrap();
+    let mut quoted = todo("It's done");
+    quoted.assignee = Some("noah".to_string());
+    quoted.project_id = Some("p1".to_string());
+    quoted.parent_id = Some(parent.id.clone());
+    quoted.due_date = Some("2026-03-10".to_string());
+    db.create_todo(quoted).await.unwrap();
+    let mut later = todo("100% coverage");
+    later.due_date = Some("2026-04-01".to_string());
+    db.create_todo(later).await.unwrap();
+
+    let titles = |todos: Vec<snippets_hub_lib::models::Todo>| {
+        todos.into_iter().map(|t| t.title).collect::<Vec<_>>()
+    };
+
+    let mut query = todo_query();
+    query.keyword = Some("it's".to_string());
+    assert_eq!(
+        titles(db.search_todos(query).await.unwrap()),
+        vec!["It's done"]
+    );
+
+    // 关键字按字面匹配，不会被当作 SQL 或通配符
+    let mut query = todo_query();
+    query.keyword = Some("' OR 1=1 --".to_string());
+    assert!(db.search_todos(query).await.unwrap().is_empty());
+    let mut query = todo_query();
+    query.keyword = Some("0%".to_string());
+    assert_eq!(
+        titles(db.search_todos(query).await.unwrap()),
+        vec!["100% coverage"]
+    );
+
+    let mut query = todo_query();
+    query.status = Some("todo' OR '1'='1".to_string());
+    assert!(db.search_todos(query).await.unwrap().is_empty());
+
+    for query in [
+        TodoSearchQuery {
+            assignee: Some("noah".to_string()),
+            ..todo_query()
+        },
+        TodoSearchQuery {
+            project_id: Some("p1".to_string()),
+            ..todo_query()
+        },
+        TodoSearchQuery {
+            parent_id: Some(parent.id.clone()),
+            ..todo_query()
+        },
+        TodoSearchQuery {
+            due_date_from: Some("2026-03-01".to_string()),
+            due_date_to: Some("2026-03-31".to_string()),
+            ..todo_query()
+        },
+    ] {
+        assert_eq!(
+            titles(db.search_todos(query).await.unwrap()),
+            vec!["It's done"]
+        );
+    }
+
+    let mut query = todo_query();
+    query.due_date_from = Some("2026-03-11".to_string());
+    assert_eq!(
+        titles(db.search_todos(query).await.unwrap()),
+        vec!["100% coverage"]
+    );
+}
+
+#[tokio::test]
+async fn batch_operations_update_every_listed_todo() {
+    let db = memory_db().await;
+
+    let a = db.create_todo(todo("A")).await.unwrap();
+    let b = db.create_todo(todo("B")).await.unwrap();
+    let c = db.create_todo(todo("C")).await.unwrap();
+
+    let completed = db
+        .batch_update_todos(batch("complete", &[&a.id, &b.id]))
+        .await
+        .unwrap();
+    assert_eq!(completed.len(), 2);
+    assert!(completed
+        .iter()
+        .all(|t| t.completed && t.status == "completed" && t.completed_at.is_some()));
+
+    let stats = db.get_todo_stats().await.unwrap();
+    assert_eq!((stats.total, stats.completed, stats.pending), (3, 2, 1));
+
+    let mut updates = todo_update("");
+    updates.priority = Some("high".to_string());
+    let mut operation = batch("update", &[&b.id, &c.id]);
+    operation.updates = Some(updates);
+    let prioritized = db.batch_update_todos(operation).await.unwrap();
+    assert!(prioritized
+        .iter()
+        .all(|t| t.priority.as_deref() == Some("high")));
+
+    let archived = db
+        .batch_update_todos(batch("archive", &[&a.id]))
+        .await
+        .unwrap();
+    assert!(archived[0].archived);
+    assert!(archived[0].archived_at.is_some());
+    assert_eq!(db.get_todos().await.unwrap().len(), 2);
+
+    let deleted = db
+        .batch_update_todos(batch("delete", &[&b.id, &c.id]))
+        .await
+        .unwrap();
+    assert!(deleted.is_empty());
+    assert!(db.get_todos().await.unwrap().is_empty());
+
+    let error = db
+        .batch_update_todos(batch("explode", &[&a.id]))
+        .await
+        .unwrap_err();
+    assert_eq!(error.code(), "validation");
+}
+
+#[tokio::test]
+async fn todos_load_tags_and_nested_subtasks() {
+    let db = memory_db().await;
+
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "release".to_string(),
+            color_id: "blue".to_string(),
+        })
+        .await
+        .unwrap();
+
+    // root -> child -> grandchild -> great-grandchild
+    let mut ids = Vec::new();
+    let mut parent_id = None;
+    for title in ["Root", "Child", "Grandchild", "Great-grandchild"] {
+        let mut req = todo(title);
+        req.parent_id = parent_id.clone();
+        req.tags = Some(vec![tag.id.clone()]);
+        let created = db.create_todo(req).await.unwrap();
+        parent_id = Some(created.id.clone());
+        ids.push(created.id);
+    }
+    let sibling = db.create_todo(todo("Sibling")).await.unwrap();
+
+    let flat = db.get_todos().await.unwrap();
+    assert_eq!(flat.len(), 5);
+    assert!(flat.iter().all(|t| t.subtasks.is_empty()));
+    for todo in &flat {
+        let expected = if todo.id == sibling.id { 0 } else { 1 };
+        assert_eq!(todo.tags.len(), expected, "{}", todo.title);
+    }
+
+    let root = db.get_todo(&ids[0]).await.unwrap();
+    let mut depth = 0;
+    let mut node = &root;
+    while let Some(subtask) = node.subtasks.first() {
+        assert_eq!(node.subtasks.len(), 1);
+        assert_eq!(subtask.tags, vec![tag.id.clone()]);
+        node = subtask;
+        depth += 1;
+    }
+    assert_eq!(depth, 3);
+    assert_eq!(node.id, ids[3]);
+
+    let tree = build_todo_tree(flat);
+    let mut roots: Vec<&str> = tree.iter().map(|t| t.title.as_str()).collect();
+    roots.sort();
+    assert_eq!(roots, vec!["Root", "Sibling"]);
+
+    // 只匹配到子任务时，子任务作为顶层返回
+    let mut query = todo_query();
+    query.keyword = Some("grandchild".to_string());
+    let tree = build_todo_tree(db.search_todos(query).await.unwrap());
+    assert_eq!(tree.len(), 1);
+    assert_eq!(tree[0].title, "Grandchild");
+    assert_eq!(tree[0].subtasks[0].title, "Great-grandchild");
+}
@@ -0,0 +1,242 @@
+//! 撤销/重做历史的集成测试
+mod common;
+
+use common::{code_snippet, memory_db, memory_pool, snippet, snippet_update, todo};
+use snippets_hub_lib::database::Database;
+use snippets_hub_lib::journal::HISTORY_LIMIT;
+use snippets_hub_lib::models::{
+    BatchTodoOperation, CreateTodoTagRequest, ImportConflictStrategy, SnippetDraft, TodoDraft,
+};
+use snippets_hub_lib::repository::{FolderRepository, SnippetRepository, TodoRepository};
+use std::collections::HashSet;
+
+#[tokio::test]
+async fn deleted_folder_can_be_undone_and_redone() {
+    let db = memory_db().await;
+    let folder = db.create_folder("Rust".to_string(), None).await.unwrap();
+    let mut req = snippet("Filed", "rust", "fn main() {}");
+    req.folder_id = Some(folder.id.clone());
+    let filed = db.create_snippet(req).await.unwrap();
+
+    db.delete_folder(&folder.id).await.unwrap();
+    assert!(db.get_all_folders().await.unwrap().is_empty());
+    let state = db.undo_state().await.unwrap();
+    assert_eq!(state.undo.unwrap().label, "Delete folder");
+    assert!(state.redo.is_none());
+
+    let undone = db.undo().await.unwrap().unwrap();
+    assert_eq!(undone.label, "Delete folder");
+    assert_eq!(db.get_all_folders().await.unwrap().len(), 1);
+    let restored = db.get_snippet(&filed.id).await.unwrap().unwrap();
+    assert_eq!(restored.folder_id, Some(folder.id.clone()));
+    let state = db.undo_state().await.unwrap();
+    assert_eq!(state.undo.unwrap().label, "Create snippet");
+
+    db.redo().await.unwrap().unwrap();
+    assert!(db.get_all_folders().await.unwrap().is_empty());
+    let moved = db.get_snippet(&filed.id).await.unwrap().unwrap();
+    assert_eq!(moved.folder_id, None);
+    assert!(db.redo().await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn undoing_a_todo_delete_restores_subtasks_and_tags() {
+    let db = memory_db().await;
+    let tag = db
+        .create_todo_tag(CreateTodoTagRequest {
+            name: "release".to_string(),
+            color_id: "blue".to_string(),
+        })
+        .await
+        .unwrap();
+    let mut req = todo("Ship");
+    req.tags = Some(vec![tag.id.clone()]);
+    let parent = db.create_todo(req).await.unwrap();
+    let mut req = todo("Changelog");
+    req.parent_id = Some(parent.id.clone());
+    req.tags = Some(vec![tag.id.clone()]);
+    let child = db.create_todo(req).await.unwrap();
+
+    db.delete_todo(parent.id.clone()).await.unwrap();
+    assert_eq!(
+        db.undo_state().await.unwrap().undo.unwrap().label,
+        "Delete todo"
+    );
+    assert!(db.get_todos().await.unwrap().is_empty());
+
+    db.undo().await.unwrap().unwrap();
+    let restored = db.get_todo(&parent.id).await.unwrap();
+    assert_eq!(restored.tags, vec![tag.id.clone()]);
+    assert_eq!(restored.subtasks.len(), 1);
+    assert_eq!(db.get_todo(&child.id).await.unwrap().tags, vec![tag.id]);
+}
+
+#[tokio::test]
+async fn undoing_a_todo_delete_restores_every_level_of_subtasks() {
+    let db = memory_db().await;
+    let root = db.create_todo(todo("Release")).await.unwrap();
+    let mut req = todo("Docs");
+    req.parent_id = Some(root.id.clone());
+    let child = db.create_todo(req).await.unwrap();
+    let mut req = todo("Changelog");
+    req.parent_id = Some(child.id.clone());
+    let grandchild = db.create_todo(req).await.unwrap();
+
+    db.delete_todo(root.id.clone()).await.unwrap();
+    assert!(db.get_todos().await.unwrap().is_empty());
+
+    db.undo().await.unwrap().unwrap();
+    let restored = db.get_todo(&root.id).await.unwrap();
+    assert_eq!(restored.subtasks.len(), 1);
+    assert_eq!(restored.subtasks[0].id, child.id);
+    assert_eq!(restored.subtasks[0].subtasks[0].id, grandchild.id);
+
+    db.redo().await.unwrap().unwrap();
+    assert!(db.get_todos().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn undo_is_refused_when_the_row_changed_afterwards() {
+    let pool = memory_pool().await;
+    let db = Database::from_pool(pool.clone()).await.unwrap();
+    let created = db
+        .create_snippet(snippet("Draft", "text", "v1"))
+        .await
+        .unwrap();
+
+    let mut update = snippet_update(&created.id);
+    update.code = Some("v2".to_string());
+    db.update_snippet(update).await.unwrap();
+
+    // 未记录历史的修改（如另一个窗口直接写入）
+    sqlx::query("UPDATE snippets SET code = 'v3' WHERE id = ?")
+        .bind(&created.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+    assert_eq!(
+        db.undo_state().await.unwrap().undo.unwrap().label,
+        "Edit snippet"
+    );
+
+    assert_eq!(db.undo().await.unwrap_err().code(), "conflict");
+    let current = db.get_snippet(&created.id).await.unwrap().unwrap();
+    assert_eq!(current.code, "v3");
+}
+
+#[tokio::test]
+async fn new_actions_discard_redo_and_history_is_bounded() {
+    let db = memory_db().await;
+
+    for i in 0..HISTORY_LIMIT + 5 {
+        db.create_folder(format!("F{}", i), None).await.unwrap();
+    }
+    let mut undone = 0;
+    while db.undo().await.unwrap().is_some() {
+        undone += 1;
+    }
+    assert_eq!(undone, HISTORY_LIMIT);
+    assert_eq!(db.get_all_folders().await.unwrap().len(), 5);
+
+    db.create_folder("New".to_string(), None).await.unwrap();
+    let state = db.undo_state().await.unwrap();
+    assert!(state.redo.is_none());
+    assert!(state.undo.is_some());
+}
+
+#[tokio::test]
+async fn batch_operations_are_undone_as_one_action() {
+    let db = memory_db().await;
+    let first = db.create_todo(todo("First")).await.unwrap();
+    let second = db.create_todo(todo("Second")).await.unwrap();
+
+    db.batch_update_todos(BatchTodoOperation {
+        todo_ids: vec![first.id.clone(), second.id.clone()],
+        operation: "complete".to_string(),
+        updates: None,
+    })
+    .await
+    .unwrap();
+    assert!(db.get_todo(&first.id).await.unwrap().completed);
+
+    let undone = db.undo().await.unwrap().unwrap();
+    assert_eq!(undone.label, "Complete todos");
+    assert!(!db.get_todo(&first.id).await.unwrap().completed);
+    assert!(!db.get_todo(&second.id).await.unwrap().completed);
+    assert_eq!(db.undo().await.unwrap().unwrap().label, "Create todo");
+}
+
+#[tokio::test]
+async fn snippet_imports_are_undone_as_one_action() {
+    let db = memory_db().await;
+    let existing = db
+        .create_snippet(snippet("Existing", "text", "v1"))
+        .await
+        .unwrap();
+
+    let mut overwrite = code_snippet(&existing.id, "Existing");
+    overwrite.code = "v2".to_string();
+    db.import_snippets(
+        vec![(0, overwrite), (1, code_snippet("new", "New"))],
+        ImportConflictStrategy::Overwrite,
+        false,
+    )
+    .await
+    .unwrap();
+    assert_eq!(db.get_all_snippets().await.unwrap().len(), 2);
+
+    let undone = db.undo().await.unwrap().unwrap();
+    assert_eq!(undone.label, "Import snippets");
+    let snippets = db.get_all_snippets().await.unwrap();
+    assert_eq!(snippets.len(), 1);
+    assert_eq!(snippets[0].code, "v1");
+
+    let mut draft = SnippetDraft::new(
+        "Deploy".to_string(),
+        "make".to_string(),
+        "shell".to_string(),
+    );
+    draft.folder_path = vec!["Imported".to_string(), "Shell".to_string()];
+    let second = SnippetDraft::new(
+        "Build".to_string(),
+        "cargo".to_string(),
+        "shell".to_string(),
+    );
+    db.import_drafts(vec![(0, draft), (1, second)], None, false)
+        .await
+        .unwrap();
+    assert_eq!(db.get_all_folders().await.unwrap().len(), 2);
+    assert_eq!(db.get_all_snippets().await.unwrap().len(), 3);
+
+    // 新建的文件夹与片段一并撤销
+    assert_eq!(db.undo().await.unwrap().unwrap().label, "Import snippets");
+    assert!(db.get_all_folders().await.unwrap().is_empty());
+    assert_eq!(db.get_all_snippets().await.unwrap().len(), 1);
+
+    db.redo().await.unwrap().unwrap();
+    assert_eq!(db.get_all_folders().await.unwrap().len(), 2);
+    assert_eq!(db.get_all_snippets().await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn todo_imports_are_undone_as_one_action() {
+    let db = memory_db().await;
+    let mut parent = TodoDraft::new("Release".to_string());
+    parent.id = Some("release".to_string());
+    parent.tag_names = vec!["ops".to_string()];
+    let mut child = TodoDraft::new("Changelog".to_string());
+    child.parent_id = Some("release".to_string());
+    let known_ids: HashSet<String> = ["release".to_string()].into_iter().collect();
+
+    db.import_todos(vec![(0, parent), (1, child)], &known_ids, false)
+        .await
+        .unwrap();
+    assert_eq!(db.get_todo("release").await.unwrap().subtasks.len(), 1);
+    assert_eq!(db.get_todo_tags_list().await.unwrap().len(), 1);
+
+    let undone = db.undo().await.unwrap().unwrap();
+    assert_eq!(undone.label, "Import todos");
+    assert!(db.get_todos().await.unwrap().is_empty());
+    assert!(db.get_todo_tags_list().await.unwrap().is_empty());
+    assert!(db.undo_state().await.unwrap().undo.is_none());
+}
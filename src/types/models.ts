@@ -28,3 +28,45 @@ export interface AppSettings {
     wordWrap: boolean;
     autoSave: boolean;
 }
+
+export type AppErrorCode =
+    | 'not_found'
+    | 'validation'
+    | 'invalid_data'
+    | 'unsupported_version'
+    | 'conflict'
+    | 'read_only'
+    | 'database_not_ready'
+    | 'database'
+    | 'io'
+    | 'internal';
+
+// Rejection value of every Tauri command
+export interface AppError {
+    code: AppErrorCode;
+    message: string;
+    details: string | null;
+}
+
+export type ChangeEntity =
+    | 'snippet'
+    | 'folder'
+    | 'workspace'
+    | 'project'
+    | 'git_repository'
+    | 'todo'
+    | 'todo_tag'
+    | 'library';
+
+export type ChangeAction = 'created' | 'updated' | 'deleted' | 'reloaded';
+
+// Payload of the `<entity>:<action>` events, e.g. `snippet:created`
+export interface ChangeEvent<T = unknown> {
+    entity: ChangeEntity;
+    action: ChangeAction;
+    ids: string[];
+    payload?: T;
+}
+
+// `layout` argument of get_todos / search_todos; defaults to 'flat'
+export type TodoLayout = 'flat' | 'tree';